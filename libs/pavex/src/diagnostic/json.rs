@@ -0,0 +1,157 @@
+use miette::{Diagnostic, LabeledSpan, Severity, SourceCode, SourceSpan};
+
+/// A machine-readable representation of a [`Diagnostic`], modelled after the JSON
+/// diagnostics emitted by `rustc` and `cargo` with `--message-format=json`.
+///
+/// Use [`JsonDiagnostic::new`] to convert any [`Diagnostic`] (e.g. a
+/// [`CompilerDiagnostic`](super::CompilerDiagnostic)) into its JSON representation.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct JsonDiagnostic {
+    /// `error`, `warning` or `advice`.
+    pub severity: &'static str,
+    pub message: String,
+    pub help: Option<String>,
+    pub labels: Vec<JsonLabel>,
+    pub related: Vec<JsonDiagnostic>,
+}
+
+/// A labeled span of source code attached to a [`JsonDiagnostic`].
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct JsonLabel {
+    pub label: Option<String>,
+    /// The path of the file the span points into, if known.
+    pub file_name: Option<String>,
+    /// Byte offset of the start of the span, relative to the beginning of the file.
+    pub byte_start: usize,
+    /// Byte offset of the end of the span (exclusive), relative to the beginning of the file.
+    pub byte_end: usize,
+    /// 1-based line number of the start of the span.
+    pub line_start: Option<usize>,
+    /// 1-based column number of the start of the span.
+    pub column_start: Option<usize>,
+    /// 1-based line number of the end of the span.
+    pub line_end: Option<usize>,
+    /// 1-based column number of the end of the span (exclusive).
+    pub column_end: Option<usize>,
+}
+
+impl JsonDiagnostic {
+    /// Build the JSON representation of a diagnostic, including all its related diagnostics.
+    pub fn new(diagnostic: &dyn Diagnostic) -> Self {
+        Self::_new(diagnostic, None)
+    }
+
+    fn _new(diagnostic: &dyn Diagnostic, parent_source: Option<&dyn SourceCode>) -> Self {
+        let severity = match diagnostic.severity() {
+            Some(Severity::Error) | None => "error",
+            Some(Severity::Warning) => "warning",
+            Some(Severity::Advice) => "advice",
+        };
+        // Related diagnostics without source code of their own point into their parent's.
+        let source = diagnostic.source_code().or(parent_source);
+        let labels = diagnostic
+            .labels()
+            .map(|labels| labels.map(|l| JsonLabel::new(l, source)).collect())
+            .unwrap_or_default();
+        let related = diagnostic
+            .related()
+            .map(|related| related.map(|d| Self::_new(d, source)).collect())
+            .unwrap_or_default();
+        Self {
+            severity,
+            message: diagnostic.to_string(),
+            help: diagnostic.help().map(|h| h.to_string()),
+            labels,
+            related,
+        }
+    }
+}
+
+impl JsonLabel {
+    fn new(label: LabeledSpan, source: Option<&dyn SourceCode>) -> Self {
+        let contents = source.and_then(|s| s.read_span(label.inner(), 0, 0).ok());
+        let byte_end = label.offset() + label.len();
+        // An empty span positioned right after the labeled one tells us where it ends.
+        let end_contents = source.and_then(|s| {
+            s.read_span(&SourceSpan::new(byte_end.into(), 0.into()), 0, 0)
+                .ok()
+        });
+        Self {
+            label: label.label().map(ToOwned::to_owned),
+            file_name: contents
+                .as_ref()
+                .and_then(|c| c.name().map(ToOwned::to_owned)),
+            byte_start: label.offset(),
+            byte_end,
+            line_start: contents.as_ref().map(|c| c.line() + 1),
+            column_start: contents.as_ref().map(|c| c.column() + 1),
+            line_end: end_contents.as_ref().map(|c| c.line() + 1),
+            column_end: end_contents.as_ref().map(|c| c.column() + 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use miette::{Diagnostic, NamedSource, SourceSpan};
+
+    use super::JsonDiagnostic;
+
+    #[derive(Debug, thiserror::Error, Diagnostic)]
+    #[error("I don't know how to build `Config`")]
+    #[diagnostic(help("Register a constructor for `Config`"))]
+    struct MissingConstructor {
+        #[source_code]
+        source_code: NamedSource,
+        #[label("It was requested here")]
+        span: SourceSpan,
+    }
+
+    #[test]
+    fn json_diagnostics_have_the_expected_shape() {
+        let source = "pub fn handler(\n    config: Config,\n) {}\n";
+        let start = source.find("config").unwrap();
+        let end = source.find(",").unwrap();
+        let diagnostic = MissingConstructor {
+            source_code: NamedSource::new("src/lib.rs", source),
+            span: (start, end - start).into(),
+        };
+
+        let json = serde_json::to_value(JsonDiagnostic::new(&diagnostic)).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "severity": "error",
+                "message": "I don't know how to build `Config`",
+                "help": "Register a constructor for `Config`",
+                "labels": [{
+                    "label": "It was requested here",
+                    "file_name": "src/lib.rs",
+                    "byte_start": 20,
+                    "byte_end": 34,
+                    "line_start": 2,
+                    "column_start": 5,
+                    "line_end": 2,
+                    "column_end": 19
+                }],
+                "related": []
+            })
+        );
+    }
+
+    #[test]
+    fn spans_can_cover_multiple_lines() {
+        let source = "pub fn handler(\n    config: Config,\n) {}\n";
+        let diagnostic = MissingConstructor {
+            source_code: NamedSource::new("src/lib.rs", source),
+            span: (0, source.find(')').unwrap() + 1).into(),
+        };
+
+        let json = JsonDiagnostic::new(&diagnostic);
+
+        let label = &json.labels[0];
+        assert_eq!((label.line_start, label.column_start), (Some(1), Some(1)));
+        assert_eq!((label.line_end, label.column_end), (Some(3), Some(2)));
+    }
+}
@@ -1,5 +1,6 @@
 //! A toolkit to assemble and report errors and warnings to the user.
 pub use compiler_diagnostic::{CompilerDiagnostic, CompilerDiagnosticBuilder};
+pub use json::{JsonDiagnostic, JsonLabel};
 pub use miette_utils::{
     convert_proc_macro_span, convert_rustdoc_span, OptionalSourceSpanExt, SourceSpanExt,
 };
@@ -8,6 +9,7 @@ pub use registration_locations::get_f_macro_invocation_span;
 pub use source_file::{read_source_file, LocationExt, ParsedSourceFile};
 
 mod compiler_diagnostic;
+mod json;
 mod miette_utils;
 mod proc_macro_utils;
 mod registration_locations;
@@ -15,7 +15,7 @@ use crate::diagnostic::{convert_proc_macro_span, ParsedSourceFile, ProcMacroSpan
 ///
 /// ```rust,ignore
 /// App::builder()
-///   .route(f!(crate::stream_file::<std::path::PathBuf>), "/home")
+///   .route(GET, "/home", f!(crate::stream_file::<std::path::PathBuf>))
 /// //^ `location` points here!
 /// ```
 ///
@@ -24,9 +24,9 @@ use crate::diagnostic::{convert_proc_macro_span, ParsedSourceFile, ProcMacroSpan
 ///
 /// ```rust,ignore
 /// App::builder()
-///   .route(f!(crate::stream_file::<std::path::PathBuf>), "/home")
-/// //       ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
-/// //       We want a SourceSpan that points at this!
+///   .route(GET, "/home", f!(crate::stream_file::<std::path::PathBuf>))
+/// //                     ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+/// //                     We want a SourceSpan that points at this!
 /// ```
 ///
 /// How do we do it?
@@ -40,6 +40,12 @@ use crate::diagnostic::{convert_proc_macro_span, ParsedSourceFile, ProcMacroSpan
 /// There are going to be multiple nodes that match if we are dealing with chained method calls.
 /// Luckily enough, the visit is pre-order, therefore the latest node that contains `location`
 /// is also the smallest node that contains it - exactly what we are looking for.
+///
+/// # Argument position
+///
+/// The callable is the first argument for all registration methods (e.g. `constructor` or
+/// `error_handler`) with the exception of `route`, where it comes after the method guard and
+/// the path.
 pub fn get_f_macro_invocation_span(
     source: &ParsedSourceFile,
     location: &Location,
@@ -74,7 +80,12 @@ pub fn get_f_macro_invocation_span(
     };
     locator.visit_file(parsed_source);
     if let Some(node) = locator.node {
-        if let Some(argument) = node.args.first() {
+        let argument = if node.method == "route" {
+            node.args.iter().nth(2)
+        } else {
+            node.args.first()
+        };
+        if let Some(argument) = argument {
             return Some(convert_proc_macro_span(raw_source, argument.span()));
         }
     }
@@ -4,6 +4,8 @@ use std::fmt::Write;
 
 use bimap::BiHashMap;
 use guppy::PackageId;
+use indexmap::IndexSet;
+use pavex_builder::StartupPolicy;
 
 use crate::language::{ResolvedPath, ResolvedType};
 
@@ -44,6 +46,17 @@ pub(crate) enum InvocationStyle {
         /// A map associating each field name to its type.
         field_names: BTreeMap<String, ResolvedType>,
     },
+    /// `<callable_path>(<comma-separated list of input parameters)`, wrapped in a call to one of
+    /// the functions in `pavex_runtime::startup` to enforce the startup policy of a singleton
+    /// constructor.
+    /// The wrapped invocation always returns a `Result`, since it can time out.
+    StartupGuard {
+        policy: StartupPolicy,
+        /// `true` if the guarded callable is `async`.
+        is_async: bool,
+        /// `true` if the guarded callable returns a `Result`.
+        is_fallible: bool,
+    },
 }
 
 impl Callable {
@@ -64,6 +77,16 @@ impl Callable {
         }
         buffer
     }
+
+    /// The names of the generic type parameters that have not been assigned a concrete type,
+    /// either in the input parameters or in the output type of the callable.
+    pub fn unassigned_generic_type_parameters(&self) -> IndexSet<String> {
+        let mut parameters = IndexSet::new();
+        for type_ in self.inputs.iter().chain(self.output.iter()) {
+            parameters.extend(type_.unassigned_generic_type_parameters());
+        }
+        parameters
+    }
 }
 
 impl std::fmt::Debug for Callable {
@@ -75,6 +75,21 @@ impl CallPath {
         Self::parse_from_path(callable_path.path, callable_path.qself)
     }
 
+    /// Parse the name of a type, as returned by [`std::any::type_name`].
+    ///
+    /// Unlike the path to a callable, it does not use the turbofish syntax for generic arguments
+    /// (e.g. `core::option::Option<alloc::string::String>`).
+    pub fn parse_type_name(
+        type_identifiers: &RawCallableIdentifiers,
+    ) -> Result<CallPathType, InvalidCallPath> {
+        let type_: Type =
+            syn::parse_str(type_identifiers.raw_path()).map_err(|e| InvalidCallPath {
+                raw_identifiers: type_identifiers.to_owned(),
+                parsing_error: e,
+            })?;
+        Self::parse_type(type_)
+    }
+
     fn parse_qself(qself: syn::QSelf) -> Result<CallPathQualifiedSelf, InvalidCallPath> {
         Ok(CallPathQualifiedSelf {
             position: qself.position,
@@ -7,6 +7,7 @@ use std::sync::Arc;
 use anyhow::Context;
 use bimap::BiHashMap;
 use guppy::PackageId;
+use indexmap::IndexSet;
 use itertools::Itertools;
 use quote::format_ident;
 
@@ -16,7 +17,7 @@ use crate::language::callable_path::{CallPathGenericArgument, CallPathLifetime,
 use crate::language::resolved_type::{GenericArgument, Lifetime, ScalarPrimitive, Slice};
 use crate::language::{CallPath, InvalidCallPath, ResolvedType, Tuple, TypeReference};
 use crate::rustdoc::{CrateCollection, GlobalItemId};
-use crate::rustdoc::{ResolvedItemWithParent, TOOLCHAIN_CRATES};
+use crate::rustdoc::{normalize_crate_name, ResolvedItemWithParent, TOOLCHAIN_CRATES};
 
 /// A resolved import path.
 ///
@@ -46,6 +47,16 @@ pub struct ResolvedPath {
     pub package_id: PackageId,
 }
 
+/// Where to look for the crate that the leading segment of a path refers to.
+#[derive(Clone, Copy, Debug)]
+enum CrateLookup {
+    /// The registration crate and its direct dependencies, the only crates that can be named
+    /// in the paths written by our users.
+    DirectDependencies,
+    /// Any crate in the dependency tree of the registration crate.
+    TransitiveDependencies,
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub struct ResolvedPathQualifiedSelf {
     pub position: usize,
@@ -138,9 +149,11 @@ impl ResolvedPathType {
     }
 }
 
-impl From<ResolvedType> for ResolvedPathType {
-    fn from(value: ResolvedType) -> Self {
-        match value {
+impl TryFrom<ResolvedType> for ResolvedPathType {
+    type Error = UnassignedGenericParameter;
+
+    fn try_from(value: ResolvedType) -> Result<Self, Self::Error> {
+        let t = match value {
             ResolvedType::ResolvedPath(p) => {
                 let mut segments: Vec<ResolvedPathSegment> = p
                     .base_type
@@ -155,14 +168,16 @@ impl From<ResolvedType> for ResolvedPathType {
                         .generic_arguments
                         .into_iter()
                         .map(|t| match t {
-                            GenericArgument::Type(t) => ResolvedPathGenericArgument::Type(t.into()),
+                            GenericArgument::Type(t) => {
+                                Ok(ResolvedPathGenericArgument::Type(t.try_into()?))
+                            }
                             GenericArgument::Lifetime(l) => match l {
-                                Lifetime::Static => ResolvedPathGenericArgument::Lifetime(
+                                Lifetime::Static => Ok(ResolvedPathGenericArgument::Lifetime(
                                     ResolvedPathLifetime::Static,
-                                ),
+                                )),
                             },
                         })
-                        .collect();
+                        .collect::<Result<_, _>>()?;
                 }
                 ResolvedPathType::ResolvedPath(ResolvedPathResolvedPathType {
                     path: Box::new(ResolvedPath {
@@ -175,19 +190,37 @@ impl From<ResolvedType> for ResolvedPathType {
             ResolvedType::Reference(r) => ResolvedPathType::Reference(ResolvedPathReference {
                 is_mutable: r.is_mutable,
                 is_static: r.is_static,
-                inner: Box::new((*r.inner).into()),
+                inner: Box::new((*r.inner).try_into()?),
             }),
             ResolvedType::Tuple(t) => ResolvedPathType::Tuple(ResolvedPathTuple {
-                elements: t.elements.into_iter().map(|e| e.into()).collect(),
+                elements: t
+                    .elements
+                    .into_iter()
+                    .map(|e| e.try_into())
+                    .collect::<Result<_, _>>()?,
             }),
             ResolvedType::ScalarPrimitive(s) => ResolvedPathType::ScalarPrimitive(s),
             ResolvedType::Slice(s) => ResolvedPathType::Slice(ResolvedPathSlice {
-                element: Box::new((*s.element_type).into()),
+                element: Box::new((*s.element_type).try_into()?),
             }),
-        }
+            ResolvedType::Generic(g) => {
+                return Err(UnassignedGenericParameter { name: g.name });
+            }
+        };
+        Ok(t)
     }
 }
 
+/// A generic type parameter must be assigned a concrete type before it can be used in a path.
+#[derive(Debug, thiserror::Error, Clone)]
+#[error(
+    "The generic type parameter `{name}` must be assigned a concrete type before it can be \
+    used in a path"
+)]
+pub struct UnassignedGenericParameter {
+    pub name: String,
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 pub struct ResolvedPathResolvedPathType {
     pub path: Box<ResolvedPath>,
@@ -328,17 +361,42 @@ impl ResolvedPath {
         if let Some(qself) = &mut path.qualified_self {
             replace_crate_in_type_with_registration_crate(&mut qself.type_, identifiers);
         }
-        Self::parse_call_path(&path, identifiers, graph)
+        Self::parse_call_path(&path, identifiers, graph, CrateLookup::DirectDependencies)
+    }
+
+    /// Parse the name of a type, as returned by [`std::any::type_name`], into a
+    /// [`ResolvedPathType`].
+    ///
+    /// `std::any::type_name` spells out the path where a type is defined: the crate it points at
+    /// may not be a direct dependency of `registered_at` (e.g. `sqlx_core::error::Error` for
+    /// `sqlx::Error`), therefore we look for it in the whole dependency tree of `registered_at`.
+    pub fn parse_type_name(
+        type_name: &str,
+        registered_at: &str,
+        graph: &guppy::graph::PackageGraph,
+    ) -> Result<ResolvedPathType, ParseError> {
+        let identifiers =
+            RawCallableIdentifiers::from_raw_parts(type_name.into(), registered_at.into());
+        let type_ = CallPath::parse_type_name(&identifiers)?;
+        Self::parse_call_path_type(
+            &type_,
+            &identifiers,
+            graph,
+            CrateLookup::TransitiveDependencies,
+        )
     }
 
     fn parse_call_path_generic_argument(
         arg: &CallPathGenericArgument,
         identifiers: &RawCallableIdentifiers,
         graph: &guppy::graph::PackageGraph,
+        crate_lookup: CrateLookup,
     ) -> Result<ResolvedPathGenericArgument, ParseError> {
         match arg {
-            CallPathGenericArgument::Type(t) => Self::parse_call_path_type(t, identifiers, graph)
-                .map(ResolvedPathGenericArgument::Type),
+            CallPathGenericArgument::Type(t) => {
+                Self::parse_call_path_type(t, identifiers, graph, crate_lookup)
+                    .map(ResolvedPathGenericArgument::Type)
+            }
             CallPathGenericArgument::Lifetime(l) => match l {
                 CallPathLifetime::Static => Ok(ResolvedPathGenericArgument::Lifetime(
                     ResolvedPathLifetime::Static,
@@ -351,10 +409,12 @@ impl ResolvedPath {
         type_: &CallPathType,
         identifiers: &RawCallableIdentifiers,
         graph: &guppy::graph::PackageGraph,
+        crate_lookup: CrateLookup,
     ) -> Result<ResolvedPathType, ParseError> {
         match type_ {
             CallPathType::ResolvedPath(p) => {
-                let resolved_path = Self::parse_call_path(p.path.deref(), identifiers, graph)?;
+                let resolved_path =
+                    Self::parse_call_path(p.path.deref(), identifiers, graph, crate_lookup)?;
                 Ok(ResolvedPathType::ResolvedPath(
                     ResolvedPathResolvedPathType {
                         path: Box::new(resolved_path),
@@ -368,18 +428,28 @@ impl ResolvedPath {
                     r.inner.deref(),
                     identifiers,
                     graph,
+                    crate_lookup,
                 )?),
             })),
             CallPathType::Tuple(t) => {
                 let mut elements = Vec::with_capacity(t.elements.len());
                 for element in t.elements.iter() {
-                    elements.push(Self::parse_call_path_type(element, identifiers, graph)?);
+                    elements.push(Self::parse_call_path_type(
+                        element,
+                        identifiers,
+                        graph,
+                        crate_lookup,
+                    )?);
                 }
                 Ok(ResolvedPathType::Tuple(ResolvedPathTuple { elements }))
             }
             CallPathType::Slice(s) => {
-                let element_type =
-                    Self::parse_call_path_type(s.element_type.deref(), identifiers, graph)?;
+                let element_type = Self::parse_call_path_type(
+                    s.element_type.deref(),
+                    identifiers,
+                    graph,
+                    crate_lookup,
+                )?;
                 Ok(ResolvedPathType::Slice(ResolvedPathSlice {
                     element: Box::new(element_type),
                 }))
@@ -391,6 +461,7 @@ impl ResolvedPath {
         path: &CallPath,
         identifiers: &RawCallableIdentifiers,
         graph: &guppy::graph::PackageGraph,
+        crate_lookup: CrateLookup,
     ) -> Result<Self, ParseError> {
         let registered_at = identifiers.registered_at();
         let krate_name_candidate = path.leading_path_segment().to_string();
@@ -400,7 +471,9 @@ impl ResolvedPath {
             let generic_arguments = raw_segment
                 .generic_arguments
                 .iter()
-                .map(|arg| Self::parse_call_path_generic_argument(arg, identifiers, graph))
+                .map(|arg| {
+                    Self::parse_call_path_generic_argument(arg, identifiers, graph, crate_lookup)
+                })
                 .collect::<Result<Vec<_>, _>>()?;
             let segment = ResolvedPathSegment {
                 ident: raw_segment.ident.to_string(),
@@ -412,7 +485,12 @@ impl ResolvedPath {
         let qself = if let Some(qself) = &path.qualified_self {
             Some(ResolvedPathQualifiedSelf {
                 position: qself.position,
-                type_: Self::parse_call_path_type(&qself.type_, identifiers, graph)?,
+                type_: Self::parse_call_path_type(
+                    &qself.type_,
+                    identifiers,
+                    graph,
+                    crate_lookup,
+                )?,
             })
         } else {
             None
@@ -430,6 +508,21 @@ impl ResolvedPath {
             dependency.to().id().to_owned()
         } else if TOOLCHAIN_CRATES.contains(&krate_name_candidate.as_str()) {
             PackageId::new(krate_name_candidate.clone())
+        } else if let Some(package_id) = match crate_lookup {
+            CrateLookup::DirectDependencies => None,
+            CrateLookup::TransitiveDependencies => graph
+                .query_forward(std::iter::once(registration_package.id()))
+                .expect("The registration crate is missing from the package graph")
+                .resolve()
+                .package_ids(guppy::graph::DependencyDirection::Forward)
+                .find(|id| {
+                    graph
+                        .metadata(id)
+                        .map(|p| normalize_crate_name(p.name()) == krate_name_candidate)
+                        .unwrap_or(false)
+                }),
+        } {
+            package_id.to_owned()
         } else {
             return Err(PathMustBeAbsolute {
                 relative_path: path.to_string(),
@@ -451,6 +544,22 @@ impl ResolvedPath {
         &self.segments.first().unwrap().ident
     }
 
+    /// Collect the ids of all the packages that this path refers to—the package it belongs
+    /// to, as well as the packages of its generic arguments and of its qualified self type.
+    pub fn collect_package_ids(&self, package_ids: &mut IndexSet<PackageId>) {
+        package_ids.insert(self.package_id.clone());
+        if let Some(qself) = &self.qualified_self {
+            qself.type_.collect_package_ids(package_ids);
+        }
+        for segment in &self.segments {
+            for generic_argument in &segment.generic_arguments {
+                if let ResolvedPathGenericArgument::Type(t) = generic_argument {
+                    t.collect_package_ids(package_ids);
+                }
+            }
+        }
+    }
+
     /// Return the unequivocal [`GlobalItemId`] that this path points at.
     ///
     /// This method only works for structs, enums and free functions.
@@ -543,6 +652,20 @@ impl ResolvedPath {
 }
 
 impl ResolvedPathType {
+    fn collect_package_ids(&self, package_ids: &mut IndexSet<PackageId>) {
+        match self {
+            ResolvedPathType::ResolvedPath(p) => p.path.collect_package_ids(package_ids),
+            ResolvedPathType::Reference(r) => r.inner.collect_package_ids(package_ids),
+            ResolvedPathType::Tuple(t) => {
+                for element in &t.elements {
+                    element.collect_package_ids(package_ids);
+                }
+            }
+            ResolvedPathType::ScalarPrimitive(_) => {}
+            ResolvedPathType::Slice(s) => s.element.collect_package_ids(package_ids),
+        }
+    }
+
     pub fn render_path(&self, id2name: &BiHashMap<PackageId, String>, buffer: &mut String) {
         match self {
             ResolvedPathType::ResolvedPath(p) => p.render_path(id2name, buffer),
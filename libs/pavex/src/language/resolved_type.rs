@@ -1,9 +1,11 @@
 use std::fmt::Write;
 use std::fmt::{Debug, Display, Formatter};
 
+use ahash::HashMap;
 use anyhow::Context;
 use bimap::BiHashMap;
 use guppy::PackageId;
+use indexmap::IndexSet;
 use serde::{Deserializer, Serializer};
 
 use crate::language::{ImportPath, ResolvedPath, ResolvedPathSegment};
@@ -15,10 +17,109 @@ pub enum ResolvedType {
     Tuple(Tuple),
     ScalarPrimitive(ScalarPrimitive),
     Slice(Slice),
+    Generic(Generic),
 }
 
 impl ResolvedType {
     pub const UNIT_TYPE: ResolvedType = ResolvedType::Tuple(Tuple { elements: vec![] });
+
+    /// The names of the generic type parameters that have not been assigned a concrete type
+    /// (e.g. `T` in `Json<T>`).
+    pub fn unassigned_generic_type_parameters(&self) -> IndexSet<String> {
+        let mut parameters = IndexSet::new();
+        self._unassigned_generic_type_parameters(&mut parameters);
+        parameters
+    }
+
+    fn _unassigned_generic_type_parameters(&self, parameters: &mut IndexSet<String>) {
+        match self {
+            ResolvedType::ResolvedPath(p) => {
+                for argument in &p.generic_arguments {
+                    if let GenericArgument::Type(t) = argument {
+                        t._unassigned_generic_type_parameters(parameters);
+                    }
+                }
+            }
+            ResolvedType::Reference(r) => r.inner._unassigned_generic_type_parameters(parameters),
+            ResolvedType::Tuple(t) => {
+                for element in &t.elements {
+                    element._unassigned_generic_type_parameters(parameters);
+                }
+            }
+            ResolvedType::Slice(s) => s
+                .element_type
+                ._unassigned_generic_type_parameters(parameters),
+            ResolvedType::ScalarPrimitive(_) => {}
+            ResolvedType::Generic(g) => {
+                parameters.insert(g.name.clone());
+            }
+        }
+    }
+
+    /// Check if `concrete_type` can be obtained from `self` by assigning a concrete type to
+    /// each of its unassigned generic type parameters (e.g. `Json<T>` is a template for
+    /// `Json<MyBody>`, with `T` bound to `MyBody`).
+    ///
+    /// The generic type parameters are bound in `bindings`.
+    /// A generic type parameter that has already been bound must be bound to the same type
+    /// wherever it appears.
+    pub fn is_a_template_for(
+        &self,
+        concrete_type: &ResolvedType,
+        bindings: &mut HashMap<String, ResolvedType>,
+    ) -> bool {
+        match (self, concrete_type) {
+            (ResolvedType::Generic(g), _) => match bindings.get(&g.name) {
+                Some(bound_type) => bound_type == concrete_type,
+                None => {
+                    bindings.insert(g.name.clone(), concrete_type.to_owned());
+                    true
+                }
+            },
+            (ResolvedType::ResolvedPath(template), ResolvedType::ResolvedPath(concrete)) => {
+                if template.package_id != concrete.package_id
+                    || template.base_type != concrete.base_type
+                    || template.generic_arguments.len() != concrete.generic_arguments.len()
+                {
+                    return false;
+                }
+                template
+                    .generic_arguments
+                    .iter()
+                    .zip(concrete.generic_arguments.iter())
+                    .all(|(template, concrete)| match (template, concrete) {
+                        (GenericArgument::Type(template), GenericArgument::Type(concrete)) => {
+                            template.is_a_template_for(concrete, bindings)
+                        }
+                        (
+                            GenericArgument::Lifetime(template),
+                            GenericArgument::Lifetime(concrete),
+                        ) => template == concrete,
+                        _ => false,
+                    })
+            }
+            (ResolvedType::Reference(template), ResolvedType::Reference(concrete)) => {
+                template.is_mutable == concrete.is_mutable
+                    && template.is_static == concrete.is_static
+                    && template.inner.is_a_template_for(&concrete.inner, bindings)
+            }
+            (ResolvedType::Tuple(template), ResolvedType::Tuple(concrete)) => {
+                template.elements.len() == concrete.elements.len()
+                    && template
+                        .elements
+                        .iter()
+                        .zip(concrete.elements.iter())
+                        .all(|(template, concrete)| template.is_a_template_for(concrete, bindings))
+            }
+            (ResolvedType::Slice(template), ResolvedType::Slice(concrete)) => template
+                .element_type
+                .is_a_template_for(&concrete.element_type, bindings),
+            (ResolvedType::ScalarPrimitive(template), ResolvedType::ScalarPrimitive(concrete)) => {
+                template == concrete
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(serde::Serialize, serde::Deserialize, Eq, PartialEq, Hash, Clone)]
@@ -110,6 +211,12 @@ pub struct Slice {
     pub element_type: Box<ResolvedType>,
 }
 
+/// A generic type parameter that has not been assigned a concrete type (e.g. `T` in `Json<T>`).
+#[derive(serde::Serialize, serde::Deserialize, Eq, PartialEq, Hash, Clone)]
+pub struct Generic {
+    pub name: String,
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Eq, PartialEq, Hash, Clone)]
 pub struct TypeReference {
     pub is_mutable: bool,
@@ -234,6 +341,9 @@ impl ResolvedType {
             ResolvedType::Slice(s) => {
                 write!(buffer, "[{}]", s.element_type.render_type(id2name)).unwrap();
             }
+            ResolvedType::Generic(g) => {
+                write!(buffer, "{}", g.name).unwrap();
+            }
         }
     }
 }
@@ -263,6 +373,7 @@ impl std::fmt::Debug for ResolvedType {
             ResolvedType::Tuple(t) => write!(f, "{t:?}"),
             ResolvedType::ScalarPrimitive(s) => write!(f, "{s:?}"),
             ResolvedType::Slice(s) => write!(f, "{s:?}"),
+            ResolvedType::Generic(g) => write!(f, "{g:?}"),
         }
     }
 }
@@ -302,6 +413,12 @@ impl std::fmt::Debug for ResolvedPathType {
     }
 }
 
+impl std::fmt::Debug for Generic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
 impl std::fmt::Debug for Slice {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(f, "[{:?}]", self.element_type)
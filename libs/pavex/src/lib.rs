@@ -1,4 +1,6 @@
-pub use web::App;
+pub use diagnostic::{JsonDiagnostic, JsonLabel};
+pub use rustdoc::{package_source_files, CrateCollection, RustdocConfig};
+pub use web::{compute_package_graph, App};
 
 mod diagnostic;
 pub(crate) mod language;
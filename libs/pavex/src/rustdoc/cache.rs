@@ -0,0 +1,440 @@
+use std::cell::OnceCell;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use guppy::graph::feature::StandardFeatures;
+use guppy::graph::{DependencyDirection, PackageMetadata};
+use sha2::{Digest, Sha256};
+
+use crate::rustdoc::config::RustdocConfig;
+use crate::rustdoc::package_id_spec::PackageIdSpecification;
+use crate::rustdoc::utils::{normalize_crate_name, package_source_files};
+
+/// A persistent cache for the JSON documentation generated by `rustdoc`.
+///
+/// It lives in `cargo`'s target directory, therefore it survives across `pavex` invocations
+/// and it's wiped by `cargo clean`.
+///
+/// Entries are keyed by [`CacheKey`]:
+///
+/// - third-party packages (from a registry or a git repository) are immutable for a given
+///   version, so the package id specification is enough to identify their source code;
+/// - local packages (workspace members and path dependencies) can be modified at any
+///   time: their key includes a fingerprint of their source files and of the lockfile.
+///
+/// The key of every package includes the resolved versions of its dependencies.
+///
+/// Keys are hashed with SHA-256 to determine the path of the corresponding entry: the
+/// same key maps to the same entry across `pavex` invocations and toolchain upgrades.
+pub(super) struct RustdocCache {
+    directory: PathBuf,
+    /// The version of the toolchain used to generate the docs.
+    /// It's computed lazily, the first time we need it.
+    /// It's `None` if we failed to determine it—caching is disabled in that case.
+    toolchain_version: OnceCell<Option<String>>,
+    /// A fingerprint of the workspace's `Cargo.lock`.
+    /// It's computed lazily, the first time we need it.
+    /// It's `None` if we failed to read it—local packages are not cached in that case.
+    lockfile_fingerprint: OnceCell<Option<String>>,
+    /// If `true`, entries are retrieved but new ones are never written to disk.
+    read_only: bool,
+}
+
+/// Everything that can influence the output of `rustdoc` for a package.
+#[derive(Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(super) struct CacheKey {
+    package_id_spec: String,
+    toolchain_version: String,
+    /// `None` if the docs were generated for the host target.
+    target: Option<String>,
+    enabled_features: Vec<String>,
+    /// The ids of the packages in the dependency tree of the package, sorted.
+    /// They include the version of each dependency, as resolved by `cargo`.
+    resolved_dependencies: Vec<String>,
+    /// `None` for third-party packages.
+    source_fingerprint: Option<String>,
+    /// `None` for third-party packages.
+    lockfile_fingerprint: Option<String>,
+}
+
+/// The format of an entry on disk.
+/// The key is stored alongside the docs to detect hash collisions.
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CacheEntry<Krate> {
+    key: CacheKey,
+    krate: Krate,
+}
+
+impl RustdocCache {
+    pub(super) fn new(target_directory: &Path) -> Self {
+        Self {
+            directory: target_directory.join("pavex").join("rustdoc"),
+            toolchain_version: OnceCell::new(),
+            lockfile_fingerprint: OnceCell::new(),
+            read_only: false,
+        }
+    }
+
+    /// Never write to disk: [`RustdocCache::insert`] becomes a no-op.
+    pub(super) fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    /// Compute the key for the docs of a package.
+    ///
+    /// It returns `None` if the key can't be determined: the docs must be computed from
+    /// scratch and they won't be cached.
+    pub(super) fn key(
+        &self,
+        config: &RustdocConfig,
+        package_metadata: &PackageMetadata,
+        package_id_spec: &PackageIdSpecification,
+    ) -> Option<CacheKey> {
+        let toolchain_version = self
+            .toolchain_version
+            .get_or_init(|| match get_toolchain_version(config) {
+                Ok(v) => Some(v),
+                Err(e) => {
+                    tracing::warn!(
+                        error.msg = %e,
+                        "Failed to determine the version of the toolchain. The JSON docs computed by `rustdoc` won't be cached."
+                    );
+                    None
+                }
+            })
+            .as_ref()?;
+        let standard_features = if config.all_features {
+            StandardFeatures::All
+        } else {
+            StandardFeatures::Default
+        };
+        let mut enabled_features = package_metadata
+            .graph()
+            .feature_graph()
+            .query_workspace(standard_features)
+            .resolve()
+            .features_for(package_metadata.id())
+            .ok()
+            .flatten()
+            .map(|features| {
+                features
+                    .named_features()
+                    .map(ToOwned::to_owned)
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        enabled_features.extend(config.features(package_id_spec).iter().cloned());
+        enabled_features.sort();
+        enabled_features.dedup();
+        let mut resolved_dependencies = package_metadata
+            .graph()
+            .query_forward(std::iter::once(package_metadata.id()))
+            .ok()?
+            .resolve_with_fn(|_, link| !link.dev_only())
+            .package_ids(DependencyDirection::Forward)
+            .filter(|id| *id != package_metadata.id())
+            .map(|id| id.repr().to_owned())
+            .collect::<Vec<_>>();
+        resolved_dependencies.sort();
+        let (source_fingerprint, lockfile_fingerprint) = if package_metadata.source().is_local() {
+            let package_directory = package_metadata.manifest_path().parent()?.as_std_path();
+            let source_fingerprint = match fingerprint_package_sources(package_directory) {
+                Ok(fingerprint) => fingerprint,
+                Err(e) => {
+                    tracing::warn!(
+                        error.msg = %e,
+                        "Failed to fingerprint the source files of `{package_id_spec}`. Its JSON docs won't be cached."
+                    );
+                    return None;
+                }
+            };
+            let lockfile_fingerprint = self
+                .lockfile_fingerprint
+                .get_or_init(|| {
+                    let lockfile = package_metadata.graph().workspace().root().join("Cargo.lock");
+                    match fs_err::read(lockfile) {
+                        Ok(content) => Some(fingerprint(&content)),
+                        Err(e) => {
+                            tracing::warn!(
+                                error.msg = %e,
+                                "Failed to read `Cargo.lock`. The JSON docs computed by `rustdoc` for local packages won't be cached."
+                            );
+                            None
+                        }
+                    }
+                })
+                .as_ref()?;
+            (
+                Some(source_fingerprint),
+                Some(lockfile_fingerprint.to_owned()),
+            )
+        } else {
+            (None, None)
+        };
+        Some(CacheKey {
+            package_id_spec: package_id_spec.to_string(),
+            toolchain_version: toolchain_version.to_owned(),
+            target: config.target.clone(),
+            enabled_features,
+            resolved_dependencies,
+            source_fingerprint,
+            lockfile_fingerprint,
+        })
+    }
+
+    /// Retrieve the docs associated with `key`, if they have been cached.
+    pub(super) fn get(&self, key: &CacheKey) -> Option<rustdoc_types::Crate> {
+        let path = self.entry_path(key);
+        let json = fs_err::read_to_string(path).ok()?;
+        match serde_json::from_str::<CacheEntry<rustdoc_types::Crate>>(&json) {
+            Ok(entry) if &entry.key == key => Some(entry.krate),
+            Ok(_) => None,
+            Err(e) => {
+                tracing::warn!(
+                    error.msg = %e,
+                    "Failed to deserialize an entry in the cache for the JSON docs computed by `rustdoc`. Ignoring it."
+                );
+                None
+            }
+        }
+    }
+
+    /// Store the docs for `key` in the cache, replacing the existing entry (if any).
+    ///
+    /// Nothing is stored if the cache is read-only.
+    pub(super) fn insert(
+        &self,
+        key: CacheKey,
+        krate: &rustdoc_types::Crate,
+    ) -> Result<(), anyhow::Error> {
+        if self.read_only {
+            return Ok(());
+        }
+        fs_err::create_dir_all(&self.directory)?;
+        let path = self.entry_path(&key);
+        let entry = CacheEntry { key, krate };
+        let json = serde_json::to_string(&entry)
+            .context("Failed to serialize the JSON docs computed by `rustdoc`")?;
+        // Write to a temporary file first, then rename it: a concurrent `pavex` invocation
+        // must never observe a partially-written entry.
+        let temporary_path = path.with_extension("json.tmp");
+        fs_err::write(&temporary_path, json)?;
+        fs_err::rename(&temporary_path, &path)?;
+        Ok(())
+    }
+
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        // `serde_json`'s output for a struct is deterministic, unlike `std::hash::Hash`
+        // implementations, which are allowed to change across Rust releases.
+        let serialized_key =
+            serde_json::to_vec(key).expect("Failed to serialize the key of a cache entry");
+        let key_hash = fingerprint(&serialized_key);
+        let crate_name = key
+            .package_id_spec
+            .rsplit('#')
+            .next()
+            .and_then(|s| s.split('@').next())
+            .map(normalize_crate_name)
+            .unwrap_or_default();
+        self.directory
+            .join(format!("{crate_name}-{}.json", &key_hash[..16]))
+    }
+}
+
+fn get_toolchain_version(config: &RustdocConfig) -> Result<String, anyhow::Error> {
+    let mut cmd = std::process::Command::new("rustdoc");
+    cmd.arg(format!("+{}", config.toolchain())).arg("--version");
+    let output = cmd
+        .output()
+        .with_context(|| format!("Failed to run `rustdoc`.\n{cmd:?}"))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "An invocation of `rustdoc` exited with non-zero status code.\n{:?}",
+            cmd
+        );
+    }
+    let version = std::str::from_utf8(&output.stdout)
+        .with_context(|| {
+            format!("An invocation of `rustdoc` returned non-UTF8 data as output.\n{cmd:?}")
+        })?
+        .trim()
+        .to_owned();
+    Ok(version)
+}
+
+/// Hash the content of the source files (and the manifest) of the package rooted
+/// in `package_directory`.
+/// See [`package_source_files`] for the files that are taken into account.
+fn fingerprint_package_sources(package_directory: &Path) -> Result<String, anyhow::Error> {
+    let source_files = package_source_files(package_directory)?;
+    let mut hasher = Sha256::new();
+    for path in &source_files {
+        let relative_path = path.strip_prefix(package_directory).unwrap_or(path);
+        update_with_chunk(&mut hasher, relative_path.to_string_lossy().as_bytes());
+        update_with_chunk(&mut hasher, &fs_err::read(path)?);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// The hex-encoded SHA-256 digest of `bytes`.
+fn fingerprint(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    update_with_chunk(&mut hasher, bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Feed `chunk` to `hasher`, prefixed by its length: moving bytes from one chunk
+/// to the next changes the final digest.
+fn update_with_chunk(hasher: &mut Sha256, chunk: &[u8]) {
+    hasher.update((chunk.len() as u64).to_le_bytes());
+    hasher.update(chunk);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{fingerprint_package_sources, CacheKey, RustdocCache};
+
+    /// A fresh directory, unique to the calling test.
+    fn scratch_directory(test_name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "pavex-rustdoc-cache-{}-{test_name}",
+            std::process::id()
+        ));
+        let _ = fs_err::remove_dir_all(&directory);
+        fs_err::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn key() -> CacheKey {
+        CacheKey {
+            package_id_spec: "registry+https://github.com/rust-lang/crates.io-index#http@0.2.8"
+                .into(),
+            toolchain_version: "rustdoc 1.68.0-nightly (afaf3e07a 2023-01-14)".into(),
+            target: None,
+            enabled_features: vec!["default".into(), "std".into()],
+            resolved_dependencies: vec![
+                "bytes 1.3.0 (registry+https://github.com/rust-lang/crates.io-index)".into(),
+            ],
+            source_fingerprint: None,
+            lockfile_fingerprint: None,
+        }
+    }
+
+    fn krate() -> rustdoc_types::Crate {
+        rustdoc_types::Crate {
+            root: rustdoc_types::Id("0:0:1".into()),
+            crate_version: Some("0.2.8".into()),
+            includes_private: false,
+            index: Default::default(),
+            paths: Default::default(),
+            external_crates: Default::default(),
+            format_version: rustdoc_types::FORMAT_VERSION,
+        }
+    }
+
+    #[test]
+    fn inserted_docs_can_be_retrieved() {
+        let cache = RustdocCache::new(&scratch_directory("hit"));
+        assert!(cache.get(&key()).is_none());
+
+        cache.insert(key(), &krate()).unwrap();
+        let cached = cache.get(&key()).unwrap();
+        assert_eq!(cached.crate_version, krate().crate_version);
+        assert_eq!(cached.root, krate().root);
+    }
+
+    #[test]
+    fn docs_are_not_retrieved_if_any_part_of_the_key_changes() {
+        let cache = RustdocCache::new(&scratch_directory("miss"));
+        cache.insert(key(), &krate()).unwrap();
+
+        let mut different_dependencies = key();
+        different_dependencies.resolved_dependencies =
+            vec!["bytes 1.4.0 (registry+https://github.com/rust-lang/crates.io-index)".into()];
+        let mut different_toolchain = key();
+        different_toolchain.toolchain_version =
+            "rustdoc 1.69.0-nightly (5e37043d6 2023-01-22)".into();
+        let mut different_lockfile = key();
+        different_lockfile.lockfile_fingerprint = Some("a-different-lockfile".into());
+        let mut different_features = key();
+        different_features.enabled_features = vec!["default".into()];
+        for different_key in [
+            different_dependencies,
+            different_toolchain,
+            different_lockfile,
+            different_features,
+        ] {
+            assert!(cache.get(&different_key).is_none(), "{different_key:?}");
+        }
+    }
+
+    #[test]
+    fn entries_for_a_different_key_are_ignored() {
+        let cache = RustdocCache::new(&scratch_directory("collision"));
+        let mut other_key = key();
+        other_key.target = Some("wasm32-unknown-unknown".into());
+        cache.insert(other_key, &krate()).unwrap();
+        // Simulate a collision: the entry for `other_key` ends up where we look for `key`.
+        let other_key_path = fs_err::read_dir(&cache.directory)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap()
+            .path();
+        fs_err::rename(other_key_path, cache.entry_path(&key())).unwrap();
+
+        assert!(cache.get(&key()).is_none());
+    }
+
+    #[test]
+    fn read_only_caches_do_not_write_to_disk() {
+        let directory = scratch_directory("read-only");
+        RustdocCache::new(&directory)
+            .insert(key(), &krate())
+            .unwrap();
+
+        let cache = RustdocCache::new(&directory).read_only();
+        let mut other_key = key();
+        other_key.target = Some("wasm32-unknown-unknown".into());
+        cache.insert(other_key, &krate()).unwrap();
+        // Existing entries are still retrieved.
+        assert!(cache.get(&key()).is_some());
+        assert_eq!(fs_err::read_dir(&cache.directory).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn entry_paths_are_stable_across_invocations() {
+        let cache = RustdocCache::new(&PathBuf::from("target"));
+        assert_eq!(
+            cache.entry_path(&key()),
+            PathBuf::from("target/pavex/rustdoc/http-61c1138b7599717d.json")
+        );
+    }
+
+    #[test]
+    fn source_fingerprints_track_source_files_only() {
+        let package_directory = scratch_directory("fingerprint");
+        fs_err::create_dir_all(package_directory.join("src")).unwrap();
+        fs_err::write(package_directory.join("Cargo.toml"), "[package]").unwrap();
+        fs_err::write(package_directory.join("src/lib.rs"), "pub struct A;").unwrap();
+        let original = fingerprint_package_sources(&package_directory).unwrap();
+
+        // Build artifacts and files that are not Rust sources are ignored.
+        fs_err::create_dir_all(package_directory.join("target")).unwrap();
+        fs_err::write(package_directory.join("target/lib.rs"), "").unwrap();
+        fs_err::write(package_directory.join("README.md"), "Hello!").unwrap();
+        assert_eq!(
+            fingerprint_package_sources(&package_directory).unwrap(),
+            original
+        );
+
+        fs_err::write(package_directory.join("src/lib.rs"), "pub struct B;").unwrap();
+        assert_ne!(
+            fingerprint_package_sources(&package_directory).unwrap(),
+            original
+        );
+    }
+}
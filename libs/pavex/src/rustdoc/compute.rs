@@ -1,12 +1,16 @@
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use ahash::{HashSet, HashSetExt};
 use anyhow::Context;
-use guppy::Version;
+use guppy::graph::PackageGraph;
+use guppy::{PackageId, Version};
 
+use crate::rustdoc::cache::RustdocCache;
+use crate::rustdoc::config::RustdocConfig;
 use crate::rustdoc::package_id_spec::PackageIdSpecification;
 use crate::rustdoc::utils::normalize_crate_name;
-use crate::rustdoc::TOOLCHAIN_CRATES;
+use crate::rustdoc::{STD_PACKAGE_ID, TOOLCHAIN_CRATES};
 
 #[derive(Debug, thiserror::Error, Clone)]
 #[error("I failed to retrieve information about the public types of a package in your workspace ('{package_spec}').")]
@@ -16,6 +20,111 @@ pub struct CannotGetCrateData {
     pub source: Arc<anyhow::Error>,
 }
 
+/// Something is wrong with the toolchain that we rely on to compute the JSON documentation
+/// of crates.
+///
+/// See [`preflight_checks`] for more details.
+#[derive(Debug, thiserror::Error, miette::Diagnostic)]
+pub enum ToolchainError {
+    #[error(
+        "I could not invoke `rustup`. I need it to locate the `{toolchain}` toolchain I use to \
+        compute the JSON documentation of your crates."
+    )]
+    #[diagnostic(help("Install `rustup` following the instructions at https://rustup.rs"))]
+    RustupNotFound {
+        toolchain: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error(
+        "The `{toolchain}` toolchain is not installed. I need it to compute the JSON documentation \
+        of your crates."
+    )]
+    #[diagnostic(help("Install it with `rustup toolchain install {toolchain}`"))]
+    MissingToolchain { toolchain: String },
+    #[error(
+        "The `rust-docs-json` component is not installed for the `{toolchain}` toolchain. I need it \
+        to access the JSON documentation of the standard library (`std`, `core` and `alloc`)."
+    )]
+    #[diagnostic(help(
+        "Install it with `rustup component add --toolchain {toolchain} rust-docs-json`"
+    ))]
+    MissingRustDocsJson { toolchain: String },
+    #[error(
+        "Your `{toolchain}` toolchain generates JSON documentation using version {actual} of the \
+        format, but I can only understand version {expected}."
+    )]
+    #[diagnostic(help("{help}"))]
+    IncompatibleFormatVersion {
+        toolchain: String,
+        actual: u32,
+        expected: u32,
+        help: String,
+    },
+}
+
+/// Verify, ahead of computing any documentation, that the configured toolchain is installed
+/// (with the `rust-docs-json` component) and that it generates JSON docs in the format
+/// version we expect.
+///
+/// We would otherwise fail later on with an opaque error message (e.g. a non-zero exit
+/// code from `cargo rustdoc` or a deserialization error).
+#[tracing::instrument(skip_all)]
+pub(super) fn preflight_checks(config: &RustdocConfig) -> Result<(), ToolchainError> {
+    let toolchain = config.toolchain().to_owned();
+    let mut cmd = std::process::Command::new("rustup");
+    cmd.arg("which")
+        .arg("--toolchain")
+        .arg(&toolchain)
+        .arg("cargo");
+    let output = match cmd.output() {
+        Ok(output) => output,
+        Err(source) => return Err(ToolchainError::RustupNotFound { toolchain, source }),
+    };
+    if !output.status.success() {
+        return Err(ToolchainError::MissingToolchain { toolchain });
+    }
+
+    let std_json_path = match get_json_docs_root_folder_via_rustup(config) {
+        Ok(root_folder) => root_folder.join(format!("{STD_PACKAGE_ID}.json")),
+        Err(_) => return Err(ToolchainError::MissingToolchain { toolchain }),
+    };
+    let Ok(json) = fs_err::read_to_string(std_json_path) else {
+        return Err(ToolchainError::MissingRustDocsJson { toolchain });
+    };
+
+    #[derive(serde::Deserialize)]
+    struct FormatVersion {
+        format_version: u32,
+    }
+    // If we can't extract the format version, it's such an old (or unexpected) format that
+    // we can't tell: we'll get a deserialization error later down the line.
+    let Ok(FormatVersion {
+        format_version: actual,
+    }) = serde_json::from_str(&json)
+    else {
+        return Ok(());
+    };
+    let expected = rustdoc_types::FORMAT_VERSION;
+    if actual != expected {
+        let help = if actual < expected {
+            format!("Your `{toolchain}` toolchain is outdated. Update it with `rustup update {toolchain}`")
+        } else {
+            format!(
+                "Your `{toolchain}` toolchain is more recent than the ones supported by this version of \
+                `pavex`. Update `pavex` to a version that supports version {actual} of the format"
+            )
+        };
+        return Err(ToolchainError::IncompatibleFormatVersion {
+            toolchain,
+            actual,
+            expected,
+            help,
+        });
+    }
+    Ok(())
+}
+
 fn format_optional_version(v: &Option<Version>) -> Option<tracing::field::DisplayValue<String>> {
     v.as_ref().map(|v| {
         use std::fmt::Write;
@@ -30,11 +139,10 @@ fn format_optional_version(v: &Option<Version>) -> Option<tracing::field::Displa
 /// Return the JSON documentation for a crate.
 /// The crate is singled out, within the current workspace, using a [`PackageIdSpecification`].
 ///
-/// The documentation is computed on the fly for crates that are local to the current workspace.
+/// The documentation is retrieved from the on-disk [`RustdocCache`], if possible.
+/// Otherwise, it is computed on the fly (and then cached) for the packages in the dependency
+/// tree of the current workspace.
 /// The documentation is retrieved via `rustup` for toolchain crates (e.g. `std`).
-///
-/// `root_folder` is `cargo`'s target directory for the current workspace: that is where we are
-/// going to look for the JSON files generated by `rustdoc`.
 #[tracing::instrument(
 skip_all,
 fields(
@@ -44,7 +152,10 @@ crate.source = package_id_spec.source
 )
 )]
 pub(super) fn compute_crate_docs(
-    root_folder: &Path,
+    cache: &RustdocCache,
+    config: &RustdocConfig,
+    package_graph: &PackageGraph,
+    package_id: &PackageId,
     package_id_spec: &PackageIdSpecification,
 ) -> Result<rustdoc_types::Crate, CannotGetCrateData> {
     // Some crates are not compiled as part of the dependency tree of the current workspace.
@@ -56,9 +167,9 @@ pub(super) fn compute_crate_docs(
     // documentation on the fly. We assume that their JSON docs have been pre-computed and are
     // available for us to look at.
     if TOOLCHAIN_CRATES.contains(&package_id_spec.name.as_str()) {
-        get_toolchain_crate_docs(package_id_spec)
+        get_toolchain_crate_docs(config, package_id_spec)
     } else {
-        _compute_crate_docs(root_folder, package_id_spec)
+        get_or_compute_crate_docs(cache, config, package_graph, package_id, package_id_spec)
     }
     .map_err(|e| CannotGetCrateData {
         package_spec: package_id_spec.to_string(),
@@ -66,10 +177,42 @@ pub(super) fn compute_crate_docs(
     })
 }
 
+/// Retrieve the JSON documentation for a crate from the cache or, if it's not there,
+/// compute it via `cargo rustdoc` and add it to the cache.
+fn get_or_compute_crate_docs(
+    cache: &RustdocCache,
+    config: &RustdocConfig,
+    package_graph: &PackageGraph,
+    package_id: &PackageId,
+    package_id_spec: &PackageIdSpecification,
+) -> Result<rustdoc_types::Crate, anyhow::Error> {
+    let package_metadata = package_graph
+        .metadata(package_id)
+        .expect("Unknown package ID");
+    let cache_key = cache.key(config, &package_metadata, package_id_spec);
+    if let Some(krate) = cache_key.as_ref().and_then(|key| cache.get(key)) {
+        return Ok(krate);
+    }
+    // `root_folder` is `cargo`'s target directory for the current workspace: that is where
+    // we are going to look for the JSON files generated by `rustdoc`.
+    let root_folder = package_graph.workspace().target_directory().as_std_path();
+    let krate = _compute_crate_docs(config, root_folder, package_id_spec)?;
+    if let Some(cache_key) = cache_key {
+        if let Err(e) = cache.insert(cache_key, &krate) {
+            tracing::warn!(
+                error.msg = %e,
+                "Failed to store the JSON docs for `{package_id_spec}` in the cache."
+            );
+        }
+    }
+    Ok(krate)
+}
+
 fn get_toolchain_crate_docs(
+    config: &RustdocConfig,
     package_id_spec: &PackageIdSpecification,
 ) -> Result<rustdoc_types::Crate, anyhow::Error> {
-    let root_folder = get_json_docs_root_folder_via_rustup()?;
+    let root_folder = get_json_docs_root_folder_via_rustup(config)?;
     let json_path = root_folder.join(format!("{}.json", package_id_spec.name));
     let json = fs_err::read_to_string(json_path).with_context(|| {
         format!(
@@ -87,22 +230,22 @@ fn get_toolchain_crate_docs(
         .map_err(Into::into)
 }
 
-fn get_json_docs_root_folder_via_rustup() -> Result<PathBuf, anyhow::Error> {
-    let nightly_toolchain = get_nightly_toolchain_root_folder_via_rustup()?;
-    Ok(nightly_toolchain.join("share/doc/rust/json"))
+fn get_json_docs_root_folder_via_rustup(config: &RustdocConfig) -> Result<PathBuf, anyhow::Error> {
+    let toolchain = get_toolchain_root_folder_via_rustup(config)?;
+    Ok(toolchain.join("share/doc/rust/json"))
 }
 
-/// In order to determine where all the components attached to the `nightly` toolchain are stored,
-/// we ask `rustup` to tell us the location of the `cargo` binary for `nightly`.
+/// In order to determine where all the components attached to the configured toolchain are
+/// stored, we ask `rustup` to tell us the location of its `cargo` binary.
 ///
 /// Experiments seem to suggest that the path to the `cargo` binary is always structured as
 /// `<toolchain root folder>/bin/cargo`. Therefore we compute `<toolchain root folder>` by chopping
 /// off the final two components of the path returned by `rustup`.
-fn get_nightly_toolchain_root_folder_via_rustup() -> Result<PathBuf, anyhow::Error> {
+fn get_toolchain_root_folder_via_rustup(config: &RustdocConfig) -> Result<PathBuf, anyhow::Error> {
     let mut cmd = std::process::Command::new("rustup");
     cmd.arg("which")
         .arg("--toolchain")
-        .arg("nightly")
+        .arg(config.toolchain())
         .arg("cargo");
 
     let output = cmd.output().with_context(|| {
@@ -123,24 +266,26 @@ fn get_nightly_toolchain_root_folder_via_rustup() -> Result<PathBuf, anyhow::Err
     let path = Path::new(path);
     debug_assert!(
         path.ends_with("bin/cargo"),
-        "The path to the `cargo` binary for nightly does not have the expected structure: {path:?}"
+        "The path to the `cargo` binary for the toolchain does not have the expected structure: {path:?}"
     );
     Ok(path.parent().unwrap().parent().unwrap().to_path_buf())
 }
 
 fn _compute_crate_docs(
+    config: &RustdocConfig,
     target_directory: &Path,
     package_id_spec: &PackageIdSpecification,
 ) -> Result<rustdoc_types::Crate, anyhow::Error> {
-    // TODO: check that we have the nightly toolchain available beforehand in order to return
-    // a good error.
+    // We assume that `preflight_checks` has been run beforehand: the configured toolchain
+    // is available.
     let mut cmd = std::process::Command::new("cargo");
-    cmd.arg("+nightly")
+    cmd.arg(format!("+{}", config.toolchain()))
         .arg("rustdoc")
         .arg("-q")
         .arg("-p")
         .arg(package_id_spec.to_string())
         .arg("--lib")
+        .args(config.cargo_args(package_id_spec))
         .arg("--")
         .arg("--document-private-items")
         .arg("-Zunstable-options")
@@ -157,16 +302,233 @@ fn _compute_crate_docs(
         );
     }
 
-    let json_path = target_directory.join("doc").join(format!(
+    read_json_docs(config, target_directory, package_id_spec, &cmd)
+}
+
+/// Compute the JSON documentation for a set of crates, using as few `cargo` invocations as
+/// possible.
+///
+/// Docs are retrieved from the on-disk [`RustdocCache`] where possible. The remaining crates
+/// are documented by `cargo doc`, with multiple `-p` flags: `cargo` takes care of documenting
+/// independent crates in parallel.
+///
+/// Failures are not fatal: they are logged and the affected crates are omitted from the
+/// returned docs. They are going to be computed (and the error reported) one at a time via
+/// [`compute_crate_docs`] when they are actually needed.
+#[tracing::instrument(skip_all, fields(n_crates = packages.len()))]
+pub(super) fn batch_compute_crate_docs(
+    cache: &RustdocCache,
+    config: &RustdocConfig,
+    package_graph: &PackageGraph,
+    packages: Vec<(PackageId, PackageIdSpecification)>,
+) -> Vec<(PackageId, PackageIdSpecification, rustdoc_types::Crate)> {
+    let mut computed = Vec::with_capacity(packages.len());
+    let mut to_be_computed = vec![];
+    for (package_id, package_id_spec) in packages {
+        // `cargo doc` can't enable a different set of features for each of the packages
+        // it documents: we leave packages with explicitly configured features out of the
+        // batch. They'll be documented one at a time.
+        if !config.all_features && !config.features(&package_id_spec).is_empty() {
+            continue;
+        }
+        let package_metadata = package_graph
+            .metadata(&package_id)
+            .expect("Unknown package ID");
+        let cache_key = cache.key(config, &package_metadata, &package_id_spec);
+        match cache_key.as_ref().and_then(|key| cache.get(key)) {
+            Some(krate) => computed.push((package_id, package_id_spec, krate)),
+            None => to_be_computed.push((package_id, package_id_spec, cache_key)),
+        }
+    }
+    tracing::info!(
+        n_cached = computed.len(),
+        n_to_be_computed = to_be_computed.len(),
+        "Retrieved the JSON docs for the cached crates"
+    );
+
+    let target_directory = package_graph.workspace().target_directory().as_std_path();
+    let n_to_be_computed = to_be_computed.len();
+    let mut n_computed = 0;
+    for batch in into_batches(to_be_computed) {
+        let cmd = match _batch_compute_crate_docs(config, batch.iter().map(|(_, spec, _)| spec)) {
+            Ok(cmd) => cmd,
+            Err(e) => {
+                tracing::warn!(
+                    error.msg = %e,
+                    "Failed to compute the JSON docs for a batch of crates. They'll be computed one at a time."
+                );
+                continue;
+            }
+        };
+        for (package_id, package_id_spec, cache_key) in batch {
+            let krate = match read_json_docs(config, target_directory, &package_id_spec, &cmd) {
+                Ok(krate) => krate,
+                Err(e) => {
+                    tracing::warn!(error.msg = %e, "Failed to retrieve the JSON docs for `{package_id_spec}`.");
+                    continue;
+                }
+            };
+            if let Some(cache_key) = cache_key {
+                if let Err(e) = cache.insert(cache_key, &krate) {
+                    tracing::warn!(
+                        error.msg = %e,
+                        "Failed to store the JSON docs for `{package_id_spec}` in the cache."
+                    );
+                }
+            }
+            n_computed += 1;
+            tracing::info!(
+                "Computed the JSON docs for `{package_id_spec}` ({n_computed}/{n_to_be_computed})"
+            );
+            computed.push((package_id, package_id_spec, krate));
+        }
+    }
+    computed
+}
+
+/// Split the packages that must be documented into batches, each one to be documented by
+/// a single `cargo doc` invocation.
+///
+/// `rustdoc` names its output after the crate, therefore we can't document two crates with
+/// the same name (e.g. two versions of the same dependency) in the same `cargo` invocation:
+/// one would overwrite the JSON docs of the other.
+fn into_batches<T>(
+    mut packages: Vec<(PackageId, PackageIdSpecification, T)>,
+) -> Vec<Vec<(PackageId, PackageIdSpecification, T)>> {
+    let mut batches = vec![];
+    while !packages.is_empty() {
+        let mut crate_names = HashSet::new();
+        let (batch, rest): (Vec<_>, Vec<_>) = packages
+            .into_iter()
+            .partition(|(_, spec, _)| crate_names.insert(normalize_crate_name(&spec.name)));
+        batches.push(batch);
+        packages = rest;
+    }
+    batches
+}
+
+/// Document multiple crates with a single `cargo doc` invocation.
+/// It returns the command that was executed, to provide context in error messages
+/// when reading its output.
+fn _batch_compute_crate_docs<'a>(
+    config: &RustdocConfig,
+    package_id_specs: impl Iterator<Item = &'a PackageIdSpecification>,
+) -> Result<std::process::Command, anyhow::Error> {
+    let mut cmd = std::process::Command::new("cargo");
+    cmd.arg(format!("+{}", config.toolchain()))
+        .arg("doc")
+        .arg("-q")
+        .arg("--no-deps")
+        .arg("--lib")
+        .arg("--document-private-items");
+    for package_id_spec in package_id_specs {
+        cmd.arg("-p").arg(package_id_spec.to_string());
+    }
+    if let Some(target) = &config.target {
+        cmd.arg("--target").arg(target);
+    }
+    if config.all_features {
+        cmd.arg("--all-features");
+    }
+    // `cargo doc` doesn't let us pass arguments to `rustdoc` on the command line.
+    cmd.env("RUSTDOCFLAGS", "-Zunstable-options -wjson");
+
+    let status = cmd
+        .status()
+        .with_context(|| format!("Failed to run `cargo doc`.\n{cmd:?}"))?;
+
+    if !status.success() {
+        anyhow::bail!(
+            "An invocation of `cargo doc` exited with non-zero status code.\n{:?}",
+            cmd
+        );
+    }
+    Ok(cmd)
+}
+
+/// Read and deserialize the JSON docs that `rustdoc` generated for a crate.
+/// `cmd` is the command that generated them.
+fn read_json_docs(
+    config: &RustdocConfig,
+    target_directory: &Path,
+    package_id_spec: &PackageIdSpecification,
+    cmd: &std::process::Command,
+) -> Result<rustdoc_types::Crate, anyhow::Error> {
+    let json_path = config.doc_directory(target_directory).join(format!(
         "{}.json",
         normalize_crate_name(&package_id_spec.name)
     ));
 
     let json = fs_err::read_to_string(json_path).with_context(|| {
-        format!("Failed to read the output of a `cargo rustdoc` invocation.\n{cmd:?}")
+        format!("Failed to read the JSON docs generated by `rustdoc`.\n{cmd:?}")
     })?;
     let krate = serde_json::from_str::<rustdoc_types::Crate>(&json).with_context(|| {
-        format!("Failed to deserialize the output of a `cargo rustdoc` invocation.\n{cmd:?}")
+        format!("Failed to deserialize the JSON docs generated by `rustdoc`.\n{cmd:?}")
     })?;
     Ok(krate)
 }
+
+#[cfg(test)]
+mod tests {
+    use guppy::{PackageId, Version};
+
+    use crate::rustdoc::package_id_spec::PackageIdSpecification;
+
+    use super::into_batches;
+
+    fn package(name: &str, version: &str) -> (PackageId, PackageIdSpecification, ()) {
+        let package_id = PackageId::new(format!("{name} {version}"));
+        let spec = PackageIdSpecification {
+            source: None,
+            name: name.to_owned(),
+            version: Some(Version::parse(version).unwrap()),
+        };
+        (package_id, spec, ())
+    }
+
+    fn package_ids(batches: &[Vec<(PackageId, PackageIdSpecification, ())>]) -> Vec<Vec<&str>> {
+        batches
+            .iter()
+            .map(|batch| batch.iter().map(|(id, _, _)| id.repr()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn crates_with_the_same_name_are_documented_in_different_batches() {
+        let batches = into_batches(vec![
+            package("hyper", "0.14.23"),
+            package("http", "0.2.8"),
+            package("hyper", "1.0.0"),
+            package("hyper", "0.13.10"),
+            package("bytes", "1.3.0"),
+        ]);
+
+        assert_eq!(
+            package_ids(&batches),
+            vec![
+                vec!["hyper 0.14.23", "http 0.2.8", "bytes 1.3.0"],
+                vec!["hyper 1.0.0"],
+                vec!["hyper 0.13.10"],
+            ]
+        );
+    }
+
+    #[test]
+    fn crate_names_are_normalized_before_being_compared() {
+        // `rustdoc` names the output of both after `my_crate`.
+        let batches = into_batches(vec![
+            package("my-crate", "1.0.0"),
+            package("my_crate", "2.0.0"),
+        ]);
+
+        assert_eq!(
+            package_ids(&batches),
+            vec![vec!["my-crate 1.0.0"], vec!["my_crate 2.0.0"]]
+        );
+    }
+
+    #[test]
+    fn there_are_no_batches_if_there_is_nothing_to_document() {
+        assert!(into_batches(Vec::<(PackageId, PackageIdSpecification, ())>::new()).is_empty());
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use anyhow::Context;
+
+use crate::rustdoc::package_id_spec::PackageIdSpecification;
+
+/// The toolchain name used when none is specified.
+pub const DEFAULT_TOOLCHAIN: &str = "nightly";
+
+/// Options that control how `rustdoc` is invoked to compute the JSON documentation of crates.
+///
+/// It can be loaded from a TOML file (see [`RustdocConfig::load`]):
+///
+/// ```toml
+/// toolchain = "nightly-2023-02-01"
+/// target = "x86_64-unknown-linux-gnu"
+/// all-features = false
+///
+/// [features]
+/// my_crate = ["a_feature", "another_feature"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct RustdocConfig {
+    /// The name of the `rustup` toolchain used to invoke `cargo rustdoc`.
+    /// It must be a `nightly` toolchain. Defaults to [`DEFAULT_TOOLCHAIN`].
+    pub toolchain: Option<String>,
+    /// The target triple that crates are documented for.
+    /// Defaults to the host target.
+    pub target: Option<String>,
+    /// Enable all the features of every documented package.
+    #[serde(default)]
+    pub all_features: bool,
+    /// The features to enable, on top of the default ones, keyed by package name.
+    #[serde(default)]
+    pub features: BTreeMap<String, Vec<String>>,
+}
+
+impl RustdocConfig {
+    /// Load the configuration from a TOML file.
+    pub fn load(path: &Path) -> Result<Self, anyhow::Error> {
+        let contents = fs_err::read_to_string(path)?;
+        toml::from_str(&contents).with_context(|| {
+            format!(
+                "Failed to parse the `rustdoc` configuration file at {}",
+                path.display()
+            )
+        })
+    }
+
+    /// The name of the `rustup` toolchain used to invoke `cargo rustdoc`.
+    pub fn toolchain(&self) -> &str {
+        self.toolchain.as_deref().unwrap_or(DEFAULT_TOOLCHAIN)
+    }
+
+    /// The features that must be explicitly enabled when documenting a package.
+    pub fn features(&self, package_id_spec: &PackageIdSpecification) -> &[String] {
+        self.features
+            .get(&package_id_spec.name)
+            .map(|f| f.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The `cargo` arguments required to document a package according to this configuration.
+    pub(super) fn cargo_args(&self, package_id_spec: &PackageIdSpecification) -> Vec<String> {
+        let mut args = vec![];
+        if let Some(target) = &self.target {
+            args.push("--target".to_string());
+            args.push(target.to_owned());
+        }
+        if self.all_features {
+            args.push("--all-features".to_string());
+        } else {
+            let features = self.features(package_id_spec);
+            if !features.is_empty() {
+                args.push("--features".to_string());
+                args.push(features.join(","));
+            }
+        }
+        args
+    }
+
+    /// The directory where `rustdoc` stores the docs it generates.
+    pub(super) fn doc_directory(&self, target_directory: &Path) -> std::path::PathBuf {
+        match &self.target {
+            Some(target) => target_directory.join(target).join("doc"),
+            None => target_directory.join("doc"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::path::{Path, PathBuf};
+
+    use crate::rustdoc::package_id_spec::PackageIdSpecification;
+
+    use super::RustdocConfig;
+
+    /// Write `contents` to a fresh configuration file, unique to the calling test.
+    fn config_file(test_name: &str, contents: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!(
+            "pavex-rustdoc-config-{}-{test_name}",
+            std::process::id()
+        ));
+        fs_err::create_dir_all(&directory).unwrap();
+        let path = directory.join("rustdoc.toml");
+        fs_err::write(&path, contents).unwrap();
+        path
+    }
+
+    fn spec(name: &str) -> PackageIdSpecification {
+        PackageIdSpecification {
+            source: None,
+            name: name.to_owned(),
+            version: None,
+        }
+    }
+
+    #[test]
+    fn all_options_can_be_loaded_from_a_file() {
+        let path = config_file(
+            "full",
+            r#"
+            toolchain = "nightly-2023-02-01"
+            target = "x86_64-unknown-linux-gnu"
+            all-features = false
+
+            [features]
+            my_crate = ["a_feature", "another_feature"]
+            "#,
+        );
+
+        let config = RustdocConfig::load(&path).unwrap();
+
+        assert_eq!(
+            config,
+            RustdocConfig {
+                toolchain: Some("nightly-2023-02-01".into()),
+                target: Some("x86_64-unknown-linux-gnu".into()),
+                all_features: false,
+                features: BTreeMap::from([(
+                    "my_crate".into(),
+                    vec!["a_feature".into(), "another_feature".into()]
+                )]),
+            }
+        );
+        assert_eq!(config.toolchain(), "nightly-2023-02-01");
+    }
+
+    #[test]
+    fn every_option_is_optional() {
+        let config = RustdocConfig::load(&config_file("empty", "")).unwrap();
+
+        assert_eq!(config, RustdocConfig::default());
+        assert_eq!(config.toolchain(), "nightly");
+    }
+
+    #[test]
+    fn unknown_options_are_rejected() {
+        let path = config_file("unknown", "toolchian = \"nightly\"");
+
+        let e = RustdocConfig::load(&path).unwrap_err();
+
+        assert!(e.to_string().contains(&path.display().to_string()), "{e}");
+    }
+
+    #[test]
+    fn missing_files_are_reported() {
+        assert!(RustdocConfig::load(Path::new("does/not/exist.toml")).is_err());
+    }
+
+    #[test]
+    fn the_default_configuration_requires_no_extra_arguments() {
+        assert!(RustdocConfig::default()
+            .cargo_args(&spec("my_crate"))
+            .is_empty());
+    }
+
+    #[test]
+    fn features_are_only_enabled_for_the_package_they_were_configured_for() {
+        let config = RustdocConfig {
+            target: Some("wasm32-unknown-unknown".into()),
+            features: BTreeMap::from([("my_crate".into(), vec!["a".into(), "b".into()])]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.cargo_args(&spec("my_crate")),
+            ["--target", "wasm32-unknown-unknown", "--features", "a,b"]
+        );
+        assert_eq!(
+            config.cargo_args(&spec("another_crate")),
+            ["--target", "wasm32-unknown-unknown"]
+        );
+    }
+
+    #[test]
+    fn all_features_takes_precedence_over_individual_features() {
+        let config = RustdocConfig {
+            all_features: true,
+            features: BTreeMap::from([("my_crate".into(), vec!["a".into()])]),
+            ..Default::default()
+        };
+
+        assert_eq!(config.cargo_args(&spec("my_crate")), ["--all-features"]);
+    }
+}
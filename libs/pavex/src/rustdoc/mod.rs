@@ -2,14 +2,18 @@
 //! JSON crate documentation generated by `rustdoc`.
 //!
 //! [`CrateCollection`] is the key entrypoint.
-pub use compute::CannotGetCrateData;
+pub use compute::{CannotGetCrateData, ToolchainError};
+pub use config::RustdocConfig;
 pub use package_id_spec::PackageIdSpecification;
 pub use queries::{
     Crate, CrateCollection, GetItemByResolvedPathError, GlobalItemId, ResolvedItem,
     ResolvedItemWithParent, RustdocKindExt, UnknownItemPath,
 };
+pub use utils::{normalize_crate_name, package_source_files};
 
+mod cache;
 mod compute;
+mod config;
 mod package_id_spec;
 mod queries;
 mod utils;
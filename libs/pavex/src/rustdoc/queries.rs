@@ -1,4 +1,5 @@
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::collections::BTreeSet;
 use std::fmt;
 use std::fmt::{Display, Formatter};
@@ -12,28 +13,116 @@ use indexmap::IndexSet;
 use rustdoc_types::{ExternalCrate, Item, ItemEnum, ItemKind, Visibility};
 
 use crate::language::ImportPath;
+use crate::rustdoc::cache::RustdocCache;
+use crate::rustdoc::compute::{batch_compute_crate_docs, compute_crate_docs, preflight_checks};
+use crate::rustdoc::config::RustdocConfig;
 use crate::rustdoc::package_id_spec::PackageIdSpecification;
-use crate::rustdoc::{compute::compute_crate_docs, utils, CannotGetCrateData, TOOLCHAIN_CRATES};
+use crate::rustdoc::{utils, CannotGetCrateData, ToolchainError, TOOLCHAIN_CRATES};
 
 /// The main entrypoint for accessing the documentation of the crates
 /// in a specific `PackageGraph`.
 ///
 /// It takes care of:
-/// - Computing and caching the JSON documentation for crates in the graph;
+/// - Computing and caching (in memory and on disk, see [`RustdocCache`]) the JSON
+///   documentation for crates in the graph;
 /// - Execute queries that span the documentation of multiple crates (e.g. following crate
 ///   re-exports or star re-exports).
-pub struct CrateCollection(FrozenMap<PackageIdSpecification, Box<Crate>>, PackageGraph);
+pub struct CrateCollection {
+    /// The documentation computed so far, in memory.
+    crates: FrozenMap<PackageIdSpecification, Box<Crate>>,
+    package_graph: PackageGraph,
+    disk_cache: RustdocCache,
+    /// Set to `true` once [`CrateCollection::preflight_checks`] succeeds, to avoid
+    /// re-running them.
+    preflight_checks_passed: Cell<bool>,
+    config: RustdocConfig,
+}
 
 impl fmt::Debug for CrateCollection {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{:?}", self.1)
+        write!(f, "{:?}", self.package_graph)
     }
 }
 
 impl CrateCollection {
     /// Initialise the collection for a `PackageGraph`.
-    pub fn new(package_graph: PackageGraph) -> Self {
-        Self(FrozenMap::new(), package_graph)
+    ///
+    /// `config` determines how `rustdoc` is invoked to compute the documentation of each crate.
+    pub fn new(package_graph: PackageGraph, config: RustdocConfig) -> Self {
+        let disk_cache =
+            RustdocCache::new(package_graph.workspace().target_directory().as_std_path());
+        Self {
+            crates: FrozenMap::new(),
+            package_graph,
+            disk_cache,
+            preflight_checks_passed: Cell::new(false),
+            config,
+        }
+    }
+
+    /// Retrieve docs from the on-disk cache, but never add new entries to it.
+    ///
+    /// Docs that are missing from the cache are still computed via `cargo rustdoc`, but they
+    /// are only kept in memory.
+    pub fn with_read_only_disk_cache(mut self) -> Self {
+        self.disk_cache = self.disk_cache.read_only();
+        self
+    }
+
+    /// Verify that the toolchain required to compute the documentation of crates is
+    /// available and compatible with `pavex`.
+    ///
+    /// The checks are skipped if they already succeeded for this collection.
+    pub fn preflight_checks(&self) -> Result<(), ToolchainError> {
+        if !self.preflight_checks_passed.get() {
+            preflight_checks(&self.config)?;
+            self.preflight_checks_passed.set(true);
+        }
+        Ok(())
+    }
+
+    /// The package graph of the workspace this collection was initialised for.
+    pub fn package_graph(&self) -> &PackageGraph {
+        &self.package_graph
+    }
+
+    /// The [`PackageId`]s of all the crates whose documentation has been computed so far.
+    pub fn cached_package_ids(&mut self) -> Vec<PackageId> {
+        self.crates
+            .as_mut()
+            .values()
+            .map(|krate| krate.core.package_id.clone())
+            .collect()
+    }
+
+    /// Swap in an up-to-date [`PackageGraph`] for the workspace, preserving the documentation
+    /// that has already been computed for packages that have not changed.
+    ///
+    /// The documentation of `changed_packages` is discarded, as well as the documentation of
+    /// all packages that depend on them, directly or indirectly: their public API might
+    /// re-export items from one of the changed packages.
+    pub fn refresh(&mut self, package_graph: PackageGraph, changed_packages: &[PackageId]) {
+        let stale_package_ids: Vec<PackageId> = match package_graph.query_reverse(changed_packages)
+        {
+            Ok(query) => query
+                .resolve()
+                .package_ids(guppy::graph::DependencyDirection::Forward)
+                .cloned()
+                .collect(),
+            // One of the changed packages is no longer part of the workspace:
+            // we can't tell which packages depend on it, so we start from scratch.
+            Err(_) => {
+                let config = std::mem::take(&mut self.config);
+                *self = Self::new(package_graph, config);
+                return;
+            }
+        };
+        let cache = self.crates.as_mut();
+        for package_id in &stale_package_ids {
+            let package_spec = PackageIdSpecification::from_package_id(package_id, &package_graph);
+            cache.remove(&package_spec);
+        }
+        self.package_graph = package_graph;
     }
 
     /// Compute the documentation for the crate associated with a specific [`PackageId`].
@@ -43,24 +132,62 @@ impl CrateCollection {
         &self,
         package_id: &PackageId,
     ) -> Result<&Crate, CannotGetCrateData> {
-        let package_spec = PackageIdSpecification::from_package_id(package_id, &self.1);
-        if self.0.get(&package_spec).is_none() {
+        let package_spec = PackageIdSpecification::from_package_id(package_id, &self.package_graph);
+        if self.crates.get(&package_spec).is_none() {
             let krate = compute_crate_docs(
-                self.1.workspace().target_directory().as_std_path(),
+                &self.disk_cache,
+                &self.config,
+                &self.package_graph,
+                package_id,
                 &package_spec,
             )?;
             let krate = Crate::new(self, krate, package_id.to_owned());
-            self.0.insert(package_spec.clone(), Box::new(krate));
+            self.crates.insert(package_spec.clone(), Box::new(krate));
         }
         Ok(self.get_crate_by_package_id_spec(&package_spec))
     }
 
+    /// Compute the documentation for multiple crates in one go, ahead of querying them.
+    ///
+    /// It's much faster than computing their documentation one at a time via
+    /// [`CrateCollection::get_or_compute_crate_by_package_id`]: `cargo` documents
+    /// independent crates in parallel.
+    ///
+    /// Failures are not reported here: they are going to surface when the documentation
+    /// of the affected crate is requested via [`CrateCollection::get_or_compute_crate_by_package_id`].
+    pub fn batch_compute_crates(&self, package_ids: impl IntoIterator<Item = PackageId>) {
+        let packages: Vec<_> = package_ids
+            .into_iter()
+            // Toolchain crates are not documented on the fly, we just need to read their
+            // pre-computed docs.
+            .filter(|package_id| !TOOLCHAIN_CRATES.contains(&package_id.repr()))
+            .map(|package_id| {
+                let package_spec =
+                    PackageIdSpecification::from_package_id(&package_id, &self.package_graph);
+                (package_id, package_spec)
+            })
+            .filter(|(_, package_spec)| self.crates.get(package_spec).is_none())
+            .collect();
+        if packages.is_empty() {
+            return;
+        }
+        for (package_id, package_spec, krate) in batch_compute_crate_docs(
+            &self.disk_cache,
+            &self.config,
+            &self.package_graph,
+            packages,
+        ) {
+            let krate = Crate::new(self, krate, package_id);
+            self.crates.insert(package_spec, Box::new(krate));
+        }
+    }
+
     /// Retrieve the documentation for the crate associated with [`PackageId`] from
     /// [`CrateCollection`]'s internal cache if it was computed before.
     ///
     /// It panics if no documentation is found for the specified [`PackageId`].
     pub fn get_crate_by_package_id(&self, package_id: &PackageId) -> &Crate {
-        let package_spec = PackageIdSpecification::from_package_id(package_id, &self.1);
+        let package_spec = PackageIdSpecification::from_package_id(package_id, &self.package_graph);
         self.get_crate_by_package_id_spec(&package_spec)
     }
 
@@ -69,7 +196,7 @@ impl CrateCollection {
     ///
     /// It panics if no documentation is found for the specified [`PackageIdSpecification`].
     pub fn get_crate_by_package_id_spec(&self, package_spec: &PackageIdSpecification) -> &Crate {
-        self.0.get(package_spec).unwrap_or_else(|| {
+        self.crates.get(package_spec).unwrap_or_else(|| {
             panic!(
                 "No JSON docs were found for the following package ID specification: {package_spec:?}"
             )
@@ -294,7 +421,7 @@ impl CrateCore {
             return self.package_id.clone();
         }
 
-        let package_graph = &collection.1;
+        let package_graph = &collection.package_graph;
         let (external_crate, external_crate_version) =
             self.get_external_crate_name(crate_id)
                 .ok_or_else(|| {
@@ -1,4 +1,44 @@
+use std::path::{Path, PathBuf};
+
 // Ensure that crate names are in canonical form! Damn automated hyphen substitution!
 pub fn normalize_crate_name(s: &str) -> String {
     s.replace('-', "_")
 }
+
+/// The Rust source files and the manifest of the package rooted in `package_directory`,
+/// sorted by path.
+/// Build artifacts, hidden directories and nested packages are skipped.
+///
+/// These are the files that can influence the documentation generated by `rustdoc`
+/// for the package.
+pub fn package_source_files(package_directory: &Path) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut source_files = vec![];
+    collect_source_files(package_directory, true, &mut source_files)?;
+    source_files.sort();
+    Ok(source_files)
+}
+
+fn collect_source_files(
+    directory: &Path,
+    is_package_root: bool,
+    source_files: &mut Vec<PathBuf>,
+) -> Result<(), std::io::Error> {
+    if !is_package_root && directory.join("Cargo.toml").exists() {
+        return Ok(());
+    }
+    for entry in fs_err::read_dir(directory)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name();
+        let file_name = file_name.to_string_lossy();
+        if path.is_dir() {
+            if file_name.starts_with('.') || file_name == "target" {
+                continue;
+            }
+            collect_source_files(&path, false, source_files)?;
+        } else if file_name.ends_with(".rs") || file_name == "Cargo.toml" {
+            source_files.push(path);
+        }
+    }
+    Ok(())
+}
@@ -21,6 +21,9 @@ use crate::web::analyses::call_graph::{
 use crate::web::analyses::components::{ComponentDb, ComponentId, HydratedComponent};
 use crate::web::analyses::computations::ComputationDb;
 use crate::web::analyses::constructibles::ConstructibleDb;
+use crate::web::analyses::raw_identifiers::RawCallableIdentifiersDb;
+use crate::web::analyses::scopes::ScopeGraph;
+use crate::web::analyses::user_components::UserComponentDb;
 use crate::web::app::GENERATED_APP_PACKAGE_ID;
 use crate::web::computation::Computation;
 
@@ -31,6 +34,9 @@ pub(crate) fn application_state_call_graph(
     computation_db: &mut ComputationDb,
     component_db: &mut ComponentDb,
     constructible_db: &mut ConstructibleDb,
+    scope_graph: &ScopeGraph,
+    user_component_db: &UserComponentDb,
+    raw_identifiers_db: &RawCallableIdentifiersDb,
 ) -> ApplicationStateCallGraph {
     fn lifecycle2invocations(lifecycle: &Lifecycle) -> Option<NumberOfAllowedInvocations> {
         match lifecycle {
@@ -70,6 +76,7 @@ pub(crate) fn application_state_call_graph(
         .get_or_intern_constructor(
             application_state_callable_id,
             Lifecycle::Singleton,
+            scope_graph.application_state_scope_id(),
             computation_db,
         )
         .unwrap();
@@ -78,6 +85,7 @@ pub(crate) fn application_state_call_graph(
         root_node_index,
     } = build_call_graph(
         application_state_id,
+        &IndexSet::new(),
         computation_db,
         component_db,
         constructible_db,
@@ -98,10 +106,7 @@ pub(crate) fn application_state_call_graph(
         // We only care about errors at this point.
         output_node_indexes.remove(&root_node_index);
         for output_node_index in output_node_indexes {
-            let CallGraphNode::Compute {
-                component_id,
-                ..
-            } = &call_graph[output_node_index] else {
+            let CallGraphNode::Compute { component_id, .. } = &call_graph[output_node_index] else {
                 unreachable!()
             };
             let component = component_db.hydrated_component(*component_id, computation_db);
@@ -202,11 +207,16 @@ pub(crate) fn application_state_call_graph(
             let fallible = component_db.hydrated_component(fallible_id, computation_db);
             let fallible_callable = match &fallible {
                 HydratedComponent::Constructor(c) => {
-                    let Computation::Callable(c) = &c.0 else { unreachable!() };
+                    let Computation::Callable(c) = &c.0 else {
+                        unreachable!()
+                    };
                     c
                 }
                 HydratedComponent::RequestHandler(r) => &r.callable,
-                HydratedComponent::ErrorHandler(_) | HydratedComponent::Transformer(_) => {
+                HydratedComponent::ErrorHandler(_)
+                | HydratedComponent::ErrorObserver(_)
+                | HydratedComponent::Middleware(_)
+                | HydratedComponent::Transformer(_) => {
                     unreachable!()
                 }
             };
@@ -223,7 +233,27 @@ pub(crate) fn application_state_call_graph(
             } else {
                 format!("{error_type_name}{n_duplicates}")
             };
-            error_variants.insert(error_type_name.clone(), error_type.clone());
+            // We prefer the path the user registered the constructor with, but constructors
+            // obtained by instantiating a generic template don't have one.
+            let constructor_path = match component_db.user_component_id(fallible_id) {
+                Some(user_component_id) => {
+                    let raw_identifiers_id =
+                        user_component_db[user_component_id].raw_callable_identifiers_id();
+                    raw_identifiers_db[raw_identifiers_id]
+                        .fully_qualified_path()
+                        .join("::")
+                }
+                None => fallible_callable.path.to_string(),
+            };
+            error_variants.insert(
+                error_type_name.clone(),
+                ApplicationStateErrorVariant {
+                    type_: error_type.clone(),
+                    constructor_path,
+                    // It is determined later on, in `App::build`, via a trait query.
+                    implements_error: false,
+                },
+            );
             *n_duplicates += 1;
             let error_variant_constructor = Callable {
                 is_async: false,
@@ -264,6 +294,7 @@ pub(crate) fn application_state_call_graph(
     // With all the transformers in place, we can now build the final call graph!
     let call_graph = build_call_graph(
         application_state_id,
+        &IndexSet::new(),
         computation_db,
         component_db,
         constructible_db,
@@ -278,5 +309,17 @@ pub(crate) fn application_state_call_graph(
 
 pub(crate) struct ApplicationStateCallGraph {
     pub(crate) call_graph: CallGraph,
-    pub(crate) error_variants: IndexMap<String, ResolvedType>,
+    pub(crate) error_variants: IndexMap<String, ApplicationStateErrorVariant>,
+}
+
+/// A variant of the `ApplicationStateError` enum, wrapping the error returned by one of the
+/// fallible constructors invoked to build the application state.
+pub(crate) struct ApplicationStateErrorVariant {
+    /// The error type returned by the constructor.
+    pub(crate) type_: ResolvedType,
+    /// The fully-qualified path of the constructor that failed.
+    pub(crate) constructor_path: String,
+    /// `true` if the error type implements `std::error::Error`: only those errors can be
+    /// exposed as the source of an `ApplicationStateError`.
+    pub(crate) implements_error: bool,
 }
@@ -5,18 +5,21 @@ use guppy::PackageId;
 use indexmap::{IndexMap, IndexSet};
 use petgraph::prelude::{DfsPostOrder, StableDiGraph};
 use petgraph::stable_graph::NodeIndex;
-use petgraph::visit::{Dfs, Reversed};
+use petgraph::visit::{Dfs, EdgeRef, Reversed};
 use petgraph::Direction;
 use proc_macro2::{Ident, TokenStream};
 use quote::{quote, ToTokens};
 use syn::ItemFn;
 
-pub(crate) use application_state::{application_state_call_graph, ApplicationStateCallGraph};
+pub(crate) use application_state::{
+    application_state_call_graph, ApplicationStateCallGraph, ApplicationStateErrorVariant,
+};
 use pavex_builder::Lifecycle;
+pub(crate) use pipeline::{pipeline_call_graph, server_state_type, RequestProcessingCallGraphs};
 pub(crate) use request_handler::handler_call_graph;
 
 use crate::language::ResolvedType;
-use crate::web::analyses::components::{ComponentDb, ComponentId, HydratedComponent};
+use crate::web::analyses::components::{Component, ComponentDb, ComponentId, HydratedComponent};
 use crate::web::analyses::computations::ComputationDb;
 use crate::web::analyses::constructibles::ConstructibleDb;
 use crate::web::codegen_utils;
@@ -25,14 +28,19 @@ use crate::web::computation::{Computation, MatchResultVariant};
 use crate::web::constructors::Constructor;
 
 mod application_state;
+mod pipeline;
 mod request_handler;
 
 /// Build a [`CallGraph`] rooted in the `root_id` component.
 /// The caller needs to provide the required look-up maps and a function that determines how
 /// many times a callable can be invoked given its [`Lifecycle`].
 /// All the graph-traversing machinery is taken care of.
+///
+/// The types in `prebuilt_types` are never constructed: they are taken as input parameters,
+/// either by value or by reference, even if a constructor has been registered for them.
 fn build_call_graph<F>(
     root_id: ComponentId,
+    prebuilt_types: &IndexSet<ResolvedType>,
     computation_db: &ComputationDb,
     component_db: &ComponentDb,
     constructible_db: &ConstructibleDb,
@@ -42,6 +50,10 @@ where
     F: Fn(&Lifecycle) -> Option<NumberOfAllowedInvocations> + Clone,
 {
     let mut call_graph = StableDiGraph::<CallGraphNode, ()>::new();
+    // All the dependencies in the call graph are resolved using the scope of its root component:
+    // constructors registered against a nested blueprint take precedence, for the routes of that
+    // blueprint, over the ones registered against its ancestors.
+    let scope_id = component_db.scope_id(root_id);
 
     let component_id2invocations = |component_id: ComponentId| {
         // We do not expect to invoke this function for response transformers, therefore
@@ -65,6 +77,7 @@ where
 
     let mut transformed_node_indexes = HashSet::new();
     let mut handled_error_node_indexes = HashSet::new();
+    let mut converted_error_node_indexes = HashSet::new();
 
     let mut nodes_to_be_visited: IndexSet<VisitorStackElement> =
         IndexSet::from_iter([VisitorStackElement::orphan(root_id)]);
@@ -95,6 +108,14 @@ where
             let current_index = {
                 let call_graph_node = component_id2node(component_id);
                 match call_graph_node {
+                    // Error handlers are never shared between error references, even if they
+                    // can be invoked at most once: they must run after the error observers of
+                    // the specific error reference they are attached to.
+                    CallGraphNode::Compute { component_id, .. }
+                        if matches!(component_db[component_id], Component::ErrorHandler { .. }) =>
+                    {
+                        call_graph.add_node(call_graph_node)
+                    }
                     CallGraphNode::Compute {
                         n_allowed_invocations: NumberOfAllowedInvocations::One,
                         ..
@@ -129,6 +150,7 @@ where
                         constructor.input_types().to_vec()
                     }
                     HydratedComponent::RequestHandler(r) => r.input_types().to_vec(),
+                    HydratedComponent::Middleware(m) => m.input_types().to_vec(),
                     HydratedComponent::ErrorHandler(error_handler) => error_handler
                         .input_types()
                         .iter()
@@ -136,13 +158,26 @@ where
                         .filter(|&t| error_handler.error_type() != t)
                         .map(|t| t.to_owned())
                         .collect(),
+                    // We have already added the error -> error observer edge at this stage.
+                    HydratedComponent::ErrorObserver(error_observer) => {
+                        error_observer.input_types()[1..].to_vec()
+                    }
                     HydratedComponent::Transformer(_) => {
                         // We do not allow/need dependency injection for transformers at the moment.
                         vec![]
                     }
                 };
                 for input_type in input_types {
-                    if let Some(constructor_id) = constructible_db.get(&input_type) {
+                    let is_prebuilt = match &input_type {
+                        ResolvedType::Reference(r) if !r.is_static => {
+                            prebuilt_types.contains(r.inner.as_ref())
+                        }
+                        t => prebuilt_types.contains(t),
+                    };
+                    if let Some(constructor_id) = constructible_db
+                        .get(scope_id, &input_type)
+                        .filter(|_| !is_prebuilt)
+                    {
                         nodes_to_be_visited.insert(VisitorStackElement {
                             component_id: constructor_id,
                             neighbour_index: Some(VisitorIndex::Child(current_index)),
@@ -171,11 +206,7 @@ where
             }
             'inner: {
                 let node = call_graph[node_index].clone();
-                let CallGraphNode::Compute {
-                    component_id,
-                    ..
-                } = node else
-                {
+                let CallGraphNode::Compute { component_id, .. } = node else {
                     break 'inner;
                 };
                 if let Some(error_handler_id) = component_db.error_handler_id(component_id) {
@@ -183,6 +214,41 @@ where
                         component_id: *error_handler_id,
                         neighbour_index: Some(VisitorIndex::Parent(node_index)),
                     });
+                    // Error observers that take `&pavex_runtime::Error` as input are fed
+                    // the error converted into a `pavex_runtime::Error`, if it has a
+                    // different type.
+                    let converted_error_index = component_db
+                        .error_conversion_ids(component_id)
+                        .map(|(conversion_id, borrow_id)| {
+                            let conversion_index =
+                                call_graph.add_node(component_id2node(conversion_id));
+                            call_graph.update_edge(node_index, conversion_index, ());
+                            let borrow_index = call_graph.add_node(component_id2node(borrow_id));
+                            call_graph.update_edge(conversion_index, borrow_index, ());
+                            // The borrow of `pavex_runtime::Error` might be the error reference
+                            // of another fallible component: we don't want to attach its error
+                            // handler or its transformers here.
+                            for index in [conversion_index, borrow_index] {
+                                handled_error_node_indexes.insert(index);
+                                transformed_node_indexes.insert(index);
+                                converted_error_node_indexes.insert(index);
+                            }
+                            borrow_index
+                        });
+                    let converted_error_observer_ids: HashSet<_> = component_db
+                        .converted_error_observer_ids(component_id)
+                        .collect();
+                    for error_observer_id in component_db.error_observer_ids(component_id) {
+                        let is_converted = converted_error_observer_ids.contains(error_observer_id);
+                        let parent_index = match converted_error_index {
+                            Some(index) if is_converted => index,
+                            _ => node_index,
+                        };
+                        nodes_to_be_visited.insert(VisitorStackElement {
+                            component_id: *error_observer_id,
+                            neighbour_index: Some(VisitorIndex::Parent(parent_index)),
+                        });
+                    }
                 }
             }
             handled_error_node_indexes.insert(node_index);
@@ -197,8 +263,10 @@ where
             'inner: {
                 let node = call_graph[node_index].clone();
                 let CallGraphNode::Compute {
-                    component_id, n_allowed_invocations,
-                } = node else {
+                    component_id,
+                    n_allowed_invocations,
+                } = node
+                else {
                     break 'inner;
                 };
                 let Some(transformer_ids) = component_db.transformer_ids(component_id) else {
@@ -222,6 +290,70 @@ where
         }
     }
 
+    // Error observers must be invoked before the error handler: we add an edge from each of
+    // them to the error handler that takes the same error reference as input.
+    let indexes = call_graph.node_indices().collect::<Vec<_>>();
+    for node_index in indexes {
+        if converted_error_node_indexes.contains(&node_index) {
+            continue;
+        }
+        let CallGraphNode::Compute { component_id, .. } = call_graph[node_index].clone() else {
+            continue;
+        };
+        let error_observer_ids = component_db.error_observer_ids(component_id);
+        if error_observer_ids.is_empty() {
+            continue;
+        }
+        let Some(error_handler_id) = component_db.error_handler_id(component_id) else {
+            continue;
+        };
+        let find_child = |parent_index: NodeIndex, id: &ComponentId| {
+            call_graph
+                .neighbors_directed(parent_index, Direction::Outgoing)
+                .find(|&i| {
+                    matches!(
+                        &call_graph[i],
+                        CallGraphNode::Compute { component_id, .. } if component_id == id
+                    )
+                })
+        };
+        // Error observers that take `&pavex_runtime::Error` as input hang off the borrow of the
+        // converted error, rather than off the error reference itself.
+        let converted_error_index =
+            component_db
+                .error_conversion_ids(component_id)
+                .map(|(conversion_id, borrow_id)| {
+                    let conversion_index = find_child(node_index, &conversion_id)
+                        .expect("The error conversion is missing from the call graph");
+                    find_child(conversion_index, &borrow_id)
+                        .expect("The borrow of the converted error is missing from the call graph")
+                });
+        let converted_error_observer_ids: HashSet<_> = component_db
+            .converted_error_observer_ids(component_id)
+            .collect();
+        // Error handlers and error observers are never shared between error references: we
+        // can always find them among the children of the current node.
+        let error_handler_node_index = find_child(node_index, error_handler_id)
+            .expect("The error handler is missing from the call graph");
+        // Dependencies are retrieved in reverse insertion order when generating code:
+        // we add the edges in reverse to invoke the error observers in registration order.
+        let error_observer_node_indexes = error_observer_ids
+            .iter()
+            .rev()
+            .map(|id| {
+                let parent_index = match converted_error_index {
+                    Some(index) if converted_error_observer_ids.contains(id) => index,
+                    _ => node_index,
+                };
+                find_child(parent_index, id)
+                    .expect("The error observer is missing from the call graph")
+            })
+            .collect::<Vec<_>>();
+        for error_observer_node_index in error_observer_node_indexes {
+            call_graph.update_edge(error_observer_node_index, error_handler_node_index, ());
+        }
+    }
+
     // We traverse the graph looking for fallible compute nodes.
     // For each of them we add a `MatchBranching` node, in between the ancestor `Compute` node
     // for a `Result` type and the corresponding descendants `MatchResult` nodes.
@@ -242,9 +374,7 @@ where
     let indexes = call_graph.node_indices().collect::<Vec<_>>();
     for node_index in indexes {
         let node = call_graph[node_index].clone();
-        let CallGraphNode::Compute {
-            component_id, ..
-        } = node else {
+        let CallGraphNode::Compute { component_id, .. } = node else {
             continue;
         };
         let Some((ok_match_id, err_match_id)) = component_db.match_ids(component_id) else {
@@ -456,12 +586,24 @@ impl CallGraph {
                                     e.callable.render_signature(package_ids2names)
                                 )
                             }
+                            HydratedComponent::ErrorObserver(o) => {
+                                format!(
+                                    "label = \"{}\"",
+                                    o.callable.render_signature(package_ids2names)
+                                )
+                            }
                             HydratedComponent::RequestHandler(r) => {
                                 format!(
                                     "label = \"{}\"",
                                     r.callable.render_signature(package_ids2names)
                                 )
                             }
+                            HydratedComponent::Middleware(m) => {
+                                format!(
+                                    "label = \"{}\"",
+                                    m.callable.render_signature(package_ids2names)
+                                )
+                            }
                         },
                         CallGraphNode::InputParameter(t) => {
                             format!("label = \"{}\"", t.render_type(package_ids2names))
@@ -529,6 +671,9 @@ fn debug_dot(
                             HydratedComponent::ErrorHandler(e) => {
                                 format!("label = \"{:?}\"", e.callable)
                             }
+                            HydratedComponent::ErrorObserver(o) => {
+                                format!("label = \"{:?}\"", o.callable)
+                            }
                             HydratedComponent::Transformer(t)
                             | HydratedComponent::Constructor(Constructor(t)) => match t {
                                 Computation::MatchResult(m) => {
@@ -544,6 +689,9 @@ fn debug_dot(
                             HydratedComponent::RequestHandler(r) => {
                                 format!("label = \"{:?}\"", r.callable)
                             }
+                            HydratedComponent::Middleware(m) => {
+                                format!("label = \"{:?}\"", m.callable)
+                            }
                         }
                     }
                     CallGraphNode::InputParameter(t) => {
@@ -579,6 +727,20 @@ fn codegen_callable_closure<'a>(
         call_graph,
         root_node_index: root_callable_node_index,
     } = call_graph;
+    let borrow_nodes: HashSet<NodeIndex> = call_graph
+        .node_indices()
+        .filter(|node_index| match &call_graph[*node_index] {
+            CallGraphNode::Compute { component_id, .. } => matches!(
+                component_db.hydrated_component(*component_id, computation_db),
+                HydratedComponent::Constructor(Constructor(Computation::BorrowSharedReference(_)))
+                    | HydratedComponent::Transformer(Computation::BorrowSharedReference(_))
+            ),
+            _ => false,
+        })
+        .collect();
+    let mut call_graph = call_graph.to_owned();
+    order_borrows_before_moves(&mut call_graph, &borrow_nodes);
+    let call_graph = &call_graph;
     let body = codegen_callable_closure_body(
         *root_callable_node_index,
         call_graph,
@@ -678,7 +840,9 @@ fn _codegen_callable_closure_body(
                 let computation = match component {
                     HydratedComponent::Constructor(c) => c.0,
                     HydratedComponent::RequestHandler(h) => h.callable.into(),
+                    HydratedComponent::Middleware(m) => m.callable.into(),
                     HydratedComponent::ErrorHandler(e) => e.callable.to_owned().into(),
+                    HydratedComponent::ErrorObserver(o) => o.callable.into(),
                     HydratedComponent::Transformer(t) => t,
                 };
                 match computation {
@@ -876,6 +1040,80 @@ fn _codegen_callable_closure_body(
     Ok(body)
 }
 
+/// Rust won't let us borrow a value after it has been moved: if a node is both borrowed and
+/// taken by value by its dependents (e.g. `&request` for `QueryParams::extract` and `request`
+/// for `Json::extract`), the borrows must be generated first.
+///
+/// The code-generation routines traverse the call graph via [`DfsPostOrder`], which explores
+/// the dependencies of a node starting from the least recently added edge.
+/// We re-insert the incoming edges of a node so that the dependencies that do not require a move
+/// are explored first, followed by those that require both a borrow and a move and, last, those
+/// that only require a move.
+/// Call graphs without a value that is both borrowed and moved are left untouched.
+fn order_borrows_before_moves<N>(
+    call_graph: &mut StableDiGraph<N, ()>,
+    borrow_nodes: &HashSet<NodeIndex>,
+) {
+    let mut borrows = HashSet::new();
+    let mut moves = HashSet::new();
+    for node_index in call_graph.node_indices() {
+        let (b, m): (Vec<_>, Vec<_>) = call_graph
+            .neighbors_directed(node_index, Direction::Outgoing)
+            .partition(|n| borrow_nodes.contains(n));
+        if !b.is_empty() && !m.is_empty() {
+            borrows.extend(b);
+            moves.extend(m);
+        }
+    }
+    if moves.is_empty() {
+        return;
+    }
+
+    let mut ranks = HashMap::new();
+    for node_index in call_graph.node_indices() {
+        let mut requires_a_borrow = false;
+        let mut requires_a_move = false;
+        let mut ancestors = Dfs::new(Reversed(&*call_graph), node_index);
+        while let Some(ancestor_index) = ancestors.next(Reversed(&*call_graph)) {
+            requires_a_borrow |= borrows.contains(&ancestor_index);
+            requires_a_move |= moves.contains(&ancestor_index);
+        }
+        let rank = match (requires_a_borrow, requires_a_move) {
+            (_, false) => 0,
+            (true, true) => 1,
+            (false, true) => 2,
+        };
+        ranks.insert(node_index, rank);
+    }
+
+    let node_indices: Vec<_> = call_graph.node_indices().collect();
+    for node_index in node_indices {
+        // `petgraph` lists the most recently added edge first.
+        let dependencies: Vec<_> = call_graph
+            .neighbors_directed(node_index, Direction::Incoming)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        let mut sorted_dependencies = dependencies.clone();
+        // The sort is stable: dependencies with the same rank keep their relative order.
+        sorted_dependencies.sort_by_key(|n| ranks[n]);
+        if sorted_dependencies == dependencies {
+            continue;
+        }
+        let edge_indices: Vec<_> = call_graph
+            .edges_directed(node_index, Direction::Incoming)
+            .map(|e| e.id())
+            .collect();
+        for edge_index in edge_indices {
+            call_graph.remove_edge(edge_index);
+        }
+        for dependency_index in sorted_dependencies {
+            call_graph.add_edge(dependency_index, node_index, ());
+        }
+    }
+}
+
 /// Returns a terminal descendant of the given node - i.e. a node that is reachable from
 /// `start_index` and has no outgoing edges.
 fn find_terminal_descendant(
@@ -939,3 +1177,107 @@ fn get_node_type_inputs<'a, 'b: 'a>(
             (n, type_)
         })
 }
+
+#[cfg(test)]
+mod tests {
+    use ahash::{HashSet, HashSetExt};
+    use petgraph::prelude::{DfsPostOrder, StableDiGraph};
+    use petgraph::stable_graph::NodeIndex;
+    use petgraph::visit::Reversed;
+    use petgraph::Direction;
+
+    use super::order_borrows_before_moves;
+
+    /// The order in which the code-generation routines emit the dependencies of `terminal`.
+    fn codegen_order(
+        call_graph: &StableDiGraph<&'static str, ()>,
+        terminal: NodeIndex,
+    ) -> Vec<&'static str> {
+        let mut dfs = DfsPostOrder::new(Reversed(call_graph), terminal);
+        let mut order = vec![];
+        while let Some(node_index) = dfs.next(Reversed(call_graph)) {
+            order.push(call_graph[node_index]);
+        }
+        order
+    }
+
+    fn position(order: &[&str], node: &str) -> usize {
+        order.iter().position(|n| *n == node).unwrap()
+    }
+
+    #[test]
+    fn borrows_are_generated_before_moves() {
+        let mut call_graph = StableDiGraph::new();
+        let request = call_graph.add_node("request");
+        let borrow = call_graph.add_node("&request");
+        let query = call_graph.add_node("query");
+        let json = call_graph.add_node("json");
+        let handler = call_graph.add_node("handler");
+        call_graph.add_edge(request, borrow, ());
+        call_graph.add_edge(borrow, query, ());
+        call_graph.add_edge(request, json, ());
+        call_graph.add_edge(json, handler, ());
+        call_graph.add_edge(query, handler, ());
+        // Without reordering, the move would be generated first.
+        let order = codegen_order(&call_graph, handler);
+        assert!(position(&order, "json") < position(&order, "&request"));
+
+        let mut borrow_nodes = HashSet::new();
+        borrow_nodes.insert(borrow);
+        order_borrows_before_moves(&mut call_graph, &borrow_nodes);
+
+        let order = codegen_order(&call_graph, handler);
+        assert!(position(&order, "query") < position(&order, "json"));
+        assert_eq!(call_graph.edge_count(), 5);
+    }
+
+    #[test]
+    fn dependencies_that_require_a_borrow_and_a_move_come_before_those_that_only_move() {
+        let mut call_graph = StableDiGraph::new();
+        let request = call_graph.add_node("request");
+        let borrow = call_graph.add_node("&request");
+        let json = call_graph.add_node("json");
+        let only_move = call_graph.add_node("only_move");
+        let both = call_graph.add_node("both");
+        let handler = call_graph.add_node("handler");
+        call_graph.add_edge(request, borrow, ());
+        call_graph.add_edge(request, json, ());
+        call_graph.add_edge(json, only_move, ());
+        call_graph.add_edge(json, both, ());
+        call_graph.add_edge(borrow, both, ());
+        call_graph.add_edge(only_move, handler, ());
+        call_graph.add_edge(both, handler, ());
+
+        let mut borrow_nodes = HashSet::new();
+        borrow_nodes.insert(borrow);
+        order_borrows_before_moves(&mut call_graph, &borrow_nodes);
+
+        let order = codegen_order(&call_graph, handler);
+        assert!(position(&order, "&request") < position(&order, "json"));
+    }
+
+    #[test]
+    fn call_graphs_without_borrowed_and_moved_values_are_left_untouched() {
+        let mut call_graph = StableDiGraph::new();
+        let request = call_graph.add_node("request");
+        let borrow = call_graph.add_node("&request");
+        let query = call_graph.add_node("query");
+        let logger = call_graph.add_node("logger");
+        let handler = call_graph.add_node("handler");
+        call_graph.add_edge(request, borrow, ());
+        call_graph.add_edge(borrow, query, ());
+        call_graph.add_edge(query, handler, ());
+        call_graph.add_edge(logger, handler, ());
+        let dependencies = |g: &StableDiGraph<&'static str, ()>| {
+            g.neighbors_directed(handler, Direction::Incoming)
+                .collect::<Vec<_>>()
+        };
+        let before = dependencies(&call_graph);
+
+        let mut borrow_nodes = HashSet::new();
+        borrow_nodes.insert(borrow);
+        order_borrows_before_moves(&mut call_graph, &borrow_nodes);
+
+        assert_eq!(dependencies(&call_graph), before);
+    }
+}
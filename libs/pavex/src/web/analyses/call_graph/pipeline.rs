@@ -0,0 +1,296 @@
+use bimap::BiHashMap;
+use guppy::PackageId;
+use indexmap::{IndexMap, IndexSet};
+use proc_macro2::Ident;
+use quote::format_ident;
+
+use pavex_builder::router::RouterKey;
+use pavex_builder::Lifecycle;
+
+use crate::language::{
+    Callable, GenericArgument, InvocationStyle, ResolvedPath, ResolvedPathSegment,
+    ResolvedPathType, ResolvedType, TypeReference,
+};
+use crate::rustdoc::ALLOC_PACKAGE_ID;
+use crate::web::analyses::call_graph::{handler_call_graph, CallGraph, CallGraphNode};
+use crate::web::analyses::components::{ComponentDb, ComponentId, HydratedComponent};
+use crate::web::analyses::computations::ComputationDb;
+use crate::web::analyses::constructibles::ConstructibleDb;
+use crate::web::app::GENERATED_APP_PACKAGE_ID;
+use crate::web::utils::is_result;
+
+/// The call graphs that are executed, at runtime, to process incoming requests.
+pub(crate) struct RequestProcessingCallGraphs {
+    /// The call graph of the request handler for each route.
+    pub(crate) handlers: IndexMap<RouterKey, CallGraph>,
+    /// The call graph of the fallback request handler, if one was registered.
+    pub(crate) fallback: Option<CallGraph>,
+    /// The call graph of each middleware, used by the pipelines that don't share
+    /// request-scoped types across their layers.
+    pub(crate) middlewares: IndexMap<ComponentId, CallGraph>,
+    /// The pipelines that share request-scoped types across their layers, keyed by the id
+    /// of their request handler.
+    pub(crate) pipelines: IndexMap<ComponentId, PipelineCallGraph>,
+}
+
+impl RequestProcessingCallGraphs {
+    /// Iterate over all call graphs, in no particular order.
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &CallGraph> {
+        self.handlers
+            .values()
+            .chain(self.fallback.iter())
+            .chain(self.middlewares.values())
+            .chain(
+                self.pipelines
+                    .values()
+                    .flat_map(|p| std::iter::once(&p.prelude).chain(p.middlewares.iter())),
+            )
+    }
+
+    /// Iterate over the call graphs of the request handlers, the fallback included,
+    /// alongside the id of the corresponding component.
+    pub(crate) fn request_handlers<'a>(
+        &'a self,
+        component_db: &'a ComponentDb,
+    ) -> impl Iterator<Item = (ComponentId, &'a CallGraph)> + 'a {
+        self.handlers
+            .iter()
+            .map(|(router_key, call_graph)| (component_db.router()[router_key], call_graph))
+            .chain(component_db.fallback().zip(self.fallback.as_ref()))
+    }
+}
+
+/// The call graphs for the middlewares that wrap a request handler, when two or more layers
+/// of the pipeline (i.e. the middlewares and the request handler) need the same
+/// request-scoped type.
+///
+/// Request-scoped types must be built at most once per request, therefore shared types
+/// are built by the `prelude` before invoking the outermost middleware.
+/// The middlewares and the request handler take them as inputs.
+pub(crate) struct PipelineCallGraph {
+    /// Build the shared request-scoped types and then invoke the pipeline, passing them
+    /// as inputs.
+    pub(crate) prelude: CallGraph,
+    /// The request-scoped types built by the prelude.
+    pub(crate) shared_types: IndexSet<ResolvedType>,
+    /// The inputs of the function that invokes the pipeline, in order:
+    /// a reference to the server state (if singletons are needed), the incoming request,
+    /// the route parameters (if needed) and the shared request-scoped types.
+    pub(crate) input_types: Vec<ResolvedType>,
+    /// The call graphs of the middlewares, from the outermost to the innermost.
+    pub(crate) middlewares: Vec<CallGraph>,
+}
+
+/// Determine if two or more layers of the pipeline for a request handler need the same
+/// request-scoped type.
+///
+/// If they do, return the call graph of the request handler, where shared types are taken
+/// as inputs, alongside the [`PipelineCallGraph`] that builds them.
+/// `pipeline_index` is used to give a unique name to the generated function that invokes
+/// the pipeline.
+#[tracing::instrument(name = "compute_pipeline_call_graph", skip_all)]
+pub(crate) fn pipeline_call_graph(
+    pipeline_index: usize,
+    request_handler: ComponentId,
+    request_scoped_framework_bindings: &BiHashMap<Ident, ResolvedType>,
+    computation_db: &mut ComputationDb,
+    component_db: &mut ComponentDb,
+    constructible_db: &ConstructibleDb,
+) -> Option<(CallGraph, PipelineCallGraph)> {
+    let layer_ids: Vec<_> = component_db
+        .middleware_ids(request_handler)
+        .iter()
+        .copied()
+        .chain(std::iter::once(request_handler))
+        .collect();
+    if layer_ids.len() < 2 {
+        return None;
+    }
+
+    let mut type2n_layers = IndexMap::<ResolvedType, usize>::new();
+    for layer_id in &layer_ids {
+        let call_graph = handler_call_graph(
+            *layer_id,
+            &IndexSet::new(),
+            computation_db,
+            component_db,
+            constructible_db,
+        );
+        for type_ in request_scoped_types(&call_graph, component_db, computation_db) {
+            *type2n_layers.entry(type_).or_default() += 1;
+        }
+    }
+    let shared_types: IndexSet<_> = type2n_layers
+        .into_iter()
+        .filter(|(_, n_layers)| *n_layers > 1)
+        .map(|(type_, _)| type_)
+        .collect();
+    if shared_types.is_empty() {
+        return None;
+    }
+
+    let mut layer_call_graphs: Vec<_> = layer_ids
+        .iter()
+        .map(|layer_id| {
+            handler_call_graph(
+                *layer_id,
+                &shared_types,
+                computation_db,
+                component_db,
+                constructible_db,
+            )
+        })
+        .collect();
+    let request_handler_call_graph = layer_call_graphs.pop().unwrap();
+    // The types taken as input by the layers, either by value or by reference.
+    let layer_input_types: IndexSet<_> = layer_call_graphs
+        .iter()
+        .chain(std::iter::once(&request_handler_call_graph))
+        .flat_map(|call_graph| call_graph.required_input_types())
+        .map(|input_type| match input_type {
+            ResolvedType::Reference(r) if !r.is_static => *r.inner,
+            _ => input_type,
+        })
+        .collect();
+    // Some shared types are only needed to build other shared types: the prelude builds them,
+    // but there is no need to pass them to the pipeline.
+    let shared_types: IndexSet<_> = shared_types
+        .into_iter()
+        .filter(|shared_type| layer_input_types.contains(shared_type))
+        .collect();
+
+    // We build a "mock" callable for the pipeline, taking the shared types as inputs,
+    // in order to drive the machinery that builds the dependency graph of the prelude.
+    let request_type = request_scoped_framework_bindings
+        .get_by_left(&format_ident!("request"))
+        .unwrap();
+    let route_params_type = request_scoped_framework_bindings
+        .get_by_left(&format_ident!("route_params"))
+        .unwrap();
+    let mut input_types = vec![];
+    // Singletons are the only inputs that are neither shared nor provided by the framework.
+    let requires_singletons = layer_input_types.iter().any(|input_type| {
+        !shared_types.contains(input_type)
+            && !request_scoped_framework_bindings.contains_right(input_type)
+    });
+    if requires_singletons {
+        input_types.push(ResolvedType::Reference(TypeReference {
+            is_mutable: false,
+            is_static: false,
+            inner: Box::new(server_state_type()),
+        }));
+    }
+    input_types.push(request_type.to_owned());
+    if layer_input_types.contains(route_params_type) {
+        input_types.push(route_params_type.to_owned());
+    }
+    input_types.extend(shared_types.iter().cloned());
+    let outermost_middleware_call_graph = &layer_call_graphs[0];
+    let CallGraphNode::Compute { component_id, .. } = &outermost_middleware_call_graph.call_graph
+        [outermost_middleware_call_graph.root_node_index]
+    else {
+        unreachable!()
+    };
+    let output_type = component_db
+        .hydrated_component(*component_id, computation_db)
+        .output_type()
+        .to_owned();
+    let pipeline = Callable {
+        is_async: true,
+        output: Some(output_type),
+        path: ResolvedPath {
+            segments: vec![
+                ResolvedPathSegment {
+                    ident: "crate".into(),
+                    generic_arguments: vec![],
+                },
+                ResolvedPathSegment {
+                    ident: format!("pipeline_{pipeline_index}"),
+                    generic_arguments: vec![],
+                },
+            ],
+            qualified_self: None,
+            package_id: PackageId::new(GENERATED_APP_PACKAGE_ID),
+        },
+        inputs: input_types.clone(),
+        invocation_style: InvocationStyle::FunctionCall,
+    };
+    // Dependencies are resolved using the scope of the request handler, as for its own
+    // call graph.
+    let scope_id = component_db.scope_id(request_handler);
+    let pipeline_id = computation_db.get_or_intern(pipeline);
+    let prelude_id = component_db
+        .get_or_intern_constructor(
+            pipeline_id,
+            Lifecycle::RequestScoped,
+            scope_id,
+            computation_db,
+        )
+        .unwrap();
+    let prelude = handler_call_graph(
+        prelude_id,
+        &IndexSet::new(),
+        computation_db,
+        component_db,
+        constructible_db,
+    );
+    Some((
+        request_handler_call_graph,
+        PipelineCallGraph {
+            prelude,
+            shared_types,
+            input_types,
+            middlewares: layer_call_graphs,
+        },
+    ))
+}
+
+/// The type of the handle to the server state used by the generated code,
+/// `std::sync::Arc<ServerState>`.
+pub(crate) fn server_state_type() -> ResolvedType {
+    let server_state = ResolvedPathType {
+        package_id: PackageId::new(GENERATED_APP_PACKAGE_ID),
+        rustdoc_id: None,
+        base_type: vec!["crate".into(), "ServerState".into()],
+        generic_arguments: vec![],
+    };
+    ResolvedPathType {
+        package_id: PackageId::new(ALLOC_PACKAGE_ID),
+        rustdoc_id: None,
+        base_type: vec!["alloc".into(), "sync".into(), "Arc".into()],
+        generic_arguments: vec![GenericArgument::Type(server_state.into())],
+    }
+    .into()
+}
+
+/// The request-scoped types that are constructed in a call graph.
+///
+/// `&T` and `Result<T, E>` are skipped: `T` is what gets built (and shared).
+fn request_scoped_types(
+    call_graph: &CallGraph,
+    component_db: &ComponentDb,
+    computation_db: &ComputationDb,
+) -> IndexSet<ResolvedType> {
+    call_graph
+        .call_graph
+        .node_weights()
+        .filter_map(|node| {
+            let CallGraphNode::Compute { component_id, .. } = node else {
+                return None;
+            };
+            if component_db.lifecycle(*component_id) != Some(&Lifecycle::RequestScoped) {
+                return None;
+            }
+            let HydratedComponent::Constructor(constructor) =
+                component_db.hydrated_component(*component_id, computation_db)
+            else {
+                return None;
+            };
+            let output_type = constructor.output_type();
+            if matches!(output_type, ResolvedType::Reference(_)) || is_result(output_type) {
+                return None;
+            }
+            Some(output_type.to_owned())
+        })
+        .collect()
+}
@@ -1,14 +1,20 @@
+use indexmap::IndexSet;
+
 use pavex_builder::Lifecycle;
 
+use crate::language::ResolvedType;
 use crate::web::analyses::call_graph::{build_call_graph, CallGraph, NumberOfAllowedInvocations};
 use crate::web::analyses::components::{ComponentDb, ComponentId};
 use crate::web::analyses::computations::ComputationDb;
 use crate::web::analyses::constructibles::ConstructibleDb;
 
 /// Build a [`CallGraph`] for a request handler.
+///
+/// The types in `prebuilt_types` are taken as inputs instead of being constructed.
 #[tracing::instrument(name = "compute_handler_call_graph", skip_all)]
 pub(crate) fn handler_call_graph(
     request_handler: ComponentId,
+    prebuilt_types: &IndexSet<ResolvedType>,
     computation_db: &ComputationDb,
     component_db: &ComponentDb,
     constructible_db: &ConstructibleDb,
@@ -25,6 +31,7 @@ pub(crate) fn handler_call_graph(
         root_node_index,
     } = build_call_graph(
         request_handler,
+        prebuilt_types,
         computation_db,
         component_db,
         constructible_db,
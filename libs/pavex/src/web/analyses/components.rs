@@ -1,49 +1,67 @@
 use std::borrow::Cow;
 use std::collections::BTreeMap;
 
-use ahash::{HashMap, HashMapExt};
+use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
+use bimap::BiHashMap;
 use guppy::graph::PackageGraph;
 use indexmap::IndexSet;
+use proc_macro2::Ident;
+use quote::format_ident;
 
+use pavex_builder::router::RouterKey;
 use pavex_builder::Lifecycle;
 
 use crate::diagnostic;
 use crate::diagnostic::{CompilerDiagnostic, LocationExt, SourceSpanExt};
-use crate::language::{ResolvedPath, ResolvedPathQualifiedSelf, ResolvedPathSegment, ResolvedType};
+use crate::language::{
+    Callable, InvocationStyle, ResolvedPath, ResolvedPathQualifiedSelf, ResolvedPathSegment,
+    ResolvedType,
+};
 use crate::rustdoc::CrateCollection;
 use crate::web::analyses::computations::{ComputationDb, ComputationId};
 use crate::web::analyses::raw_identifiers::RawCallableIdentifiersDb;
+use crate::web::analyses::scopes::{ScopeGraph, ScopeId};
 use crate::web::analyses::user_components::{UserComponent, UserComponentDb, UserComponentId};
 use crate::web::computation::{BorrowSharedReference, Computation, MatchResult};
 use crate::web::constructors::{Constructor, ConstructorValidationError};
 use crate::web::error_handlers::{ErrorHandler, ErrorHandlerValidationError};
+use crate::web::error_observers::{ErrorObserver, ErrorObserverValidationError};
 use crate::web::interner::Interner;
+use crate::web::middlewares::{Middleware, MiddlewareValidationError};
 use crate::web::request_handlers::{RequestHandler, RequestHandlerValidationError};
 use crate::web::resolvers::{CallableResolutionError, CallableType};
-use crate::web::traits::{assert_trait_is_implemented, MissingTraitImplementationError};
-use crate::web::utils::{get_ok_variant, is_result, process_framework_path};
+use crate::web::shutdown_hooks::{ShutdownHook, ShutdownHookValidationError};
+use crate::web::startup_policies::{guard_constructor, StartupPolicyValidationError};
+use crate::web::traits::{
+    assert_trait_is_implemented, implements_trait, MissingTraitImplementationError,
+};
+use crate::web::utils::{get_err_variant, get_ok_variant, is_result, process_framework_path};
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum Component {
     RequestHandler { user_component_id: UserComponentId },
-    ErrorHandler { user_component_id: UserComponentId },
-    Constructor { source_id: ConstructorSourceId },
+    ErrorHandler { source_id: SourceId },
+    ErrorObserver { user_component_id: UserComponentId },
+    Middleware { user_component_id: UserComponentId },
+    Constructor { source_id: SourceId },
     Transformer { computation_id: ComputationId },
 }
 
+/// Where the callable behind a component comes from: either it was registered by the user
+/// or it was synthesized by `pavex`.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Copy)]
-pub(crate) enum ConstructorSourceId {
+pub(crate) enum SourceId {
     ComputationId(ComputationId),
     UserComponentId(UserComponentId),
 }
 
-impl From<ComputationId> for ConstructorSourceId {
+impl From<ComputationId> for SourceId {
     fn from(value: ComputationId) -> Self {
         Self::ComputationId(value)
     }
 }
 
-impl From<UserComponentId> for ConstructorSourceId {
+impl From<UserComponentId> for SourceId {
     fn from(value: UserComponentId) -> Self {
         Self::UserComponentId(value)
     }
@@ -57,6 +75,8 @@ pub(crate) enum HydratedComponent<'a> {
     Constructor(Constructor<'a>),
     RequestHandler(RequestHandler<'a>),
     ErrorHandler(Cow<'a, ErrorHandler>),
+    ErrorObserver(ErrorObserver<'a>),
+    Middleware(Middleware<'a>),
     Transformer(Computation<'a>),
 }
 
@@ -66,6 +86,8 @@ impl<'a> HydratedComponent<'a> {
             HydratedComponent::Constructor(c) => c.input_types(),
             HydratedComponent::RequestHandler(r) => Cow::Borrowed(r.input_types()),
             HydratedComponent::ErrorHandler(e) => Cow::Borrowed(e.input_types()),
+            HydratedComponent::ErrorObserver(o) => Cow::Borrowed(o.input_types()),
+            HydratedComponent::Middleware(m) => Cow::Borrowed(m.input_types()),
             HydratedComponent::Transformer(c) => c.input_types(),
         }
     }
@@ -75,6 +97,8 @@ impl<'a> HydratedComponent<'a> {
             HydratedComponent::Constructor(c) => c.output_type(),
             HydratedComponent::RequestHandler(r) => r.output_type(),
             HydratedComponent::ErrorHandler(e) => e.output_type(),
+            HydratedComponent::ErrorObserver(o) => o.output_type(),
+            HydratedComponent::Middleware(m) => m.output_type(),
             // TODO: we are not enforcing that the output type of a transformer is not
             //  the unit type. In particular, you can successfully register a `Result<T, ()>`
             //  type, which will result into a `MatchResult` with output `()` for the error.
@@ -87,23 +111,58 @@ impl<'a> HydratedComponent<'a> {
 pub(crate) struct ComponentDb {
     interner: Interner<Component>,
     err_ref_id2error_handler_id: HashMap<ComponentId, ComponentId>,
+    /// The error observers to be invoked, in order, before the error handler for an error
+    /// reference.
+    err_ref_id2error_observer_ids: HashMap<ComponentId, Vec<ComponentId>>,
+    /// Error observers that take `&pavex_runtime::Error` as input observe every error: an error
+    /// reference of a different type, `&E`, is converted into a `pavex_runtime::Error`, which is
+    /// then borrowed.
+    /// It maps the id of the error reference to the ids of the conversion and of the borrow.
+    err_ref_id2error_conversion_ids: HashMap<ComponentId, (ComponentId, ComponentId)>,
+    /// The error observers that take `&pavex_runtime::Error` as input.
+    pavex_error_observer_ids: HashSet<ComponentId>,
+    pavex_error: ResolvedType,
     fallible_id2match_ids: HashMap<ComponentId, (ComponentId, ComponentId)>,
     match_id2fallible_id: HashMap<ComponentId, ComponentId>,
     id2transformer_ids: HashMap<ComponentId, IndexSet<ComponentId>>,
     id2lifecycle: HashMap<ComponentId, Lifecycle>,
     error_handler_id2error_handler: HashMap<ComponentId, ErrorHandler>,
-    router: BTreeMap<String, ComponentId>,
+    router: BTreeMap<RouterKey, ComponentId>,
+    fallback_id: Option<ComponentId>,
+    handler_id2middleware_ids: HashMap<ComponentId, Vec<ComponentId>>,
+    /// Constructors synthesized by `pavex` (e.g. the one that borrows `&T` from `T`) are shared
+    /// by all the scopes where the originating constructor was registered, therefore a
+    /// component can be associated with more than one scope.
+    id2scope_ids: HashMap<ComponentId, IndexSet<ScopeId>>,
+    /// The shutdown hook is not part of any call graph, therefore we keep track of it
+    /// using the id of the corresponding user component.
+    shutdown_hook_id: Option<UserComponentId>,
+}
+
+/// The analyses that [`ComponentDb::build`] relies on, computed earlier in the pipeline.
+pub(crate) struct ComponentDbInputs<'a> {
+    pub user_component_db: &'a UserComponentDb,
+    pub package_graph: &'a PackageGraph,
+    pub raw_identifiers_db: &'a RawCallableIdentifiersDb,
+    pub krate_collection: &'a CrateCollection,
+    pub request_scoped_framework_bindings: &'a BiHashMap<Ident, ResolvedType>,
+    pub scope_graph: &'a ScopeGraph,
 }
 
 impl ComponentDb {
     pub fn build(
-        user_component_db: &UserComponentDb,
+        inputs: ComponentDbInputs,
         computation_db: &mut ComputationDb,
-        package_graph: &PackageGraph,
-        raw_identifiers_db: &RawCallableIdentifiersDb,
-        krate_collection: &CrateCollection,
         diagnostics: &mut Vec<miette::Error>,
     ) -> Self {
+        let ComponentDbInputs {
+            user_component_db,
+            package_graph,
+            raw_identifiers_db,
+            krate_collection,
+            request_scoped_framework_bindings,
+            scope_graph,
+        } = inputs;
         enum ErrorHandlerId {
             Id(ComponentId),
             // Used when the error handler failed to pass its own validation.
@@ -119,12 +178,24 @@ impl ComponentDb {
         let mut self_ = Self {
             interner: Interner::new(),
             err_ref_id2error_handler_id: Default::default(),
+            err_ref_id2error_observer_ids: Default::default(),
+            err_ref_id2error_conversion_ids: Default::default(),
+            pavex_error_observer_ids: Default::default(),
+            pavex_error: process_framework_path(
+                "pavex_runtime::Error",
+                package_graph,
+                krate_collection,
+            ),
             fallible_id2match_ids: Default::default(),
             match_id2fallible_id: Default::default(),
             id2transformer_ids: Default::default(),
             id2lifecycle: Default::default(),
             error_handler_id2error_handler: Default::default(),
             router: Default::default(),
+            fallback_id: None,
+            handler_id2middleware_ids: Default::default(),
+            id2scope_ids: Default::default(),
+            shutdown_hook_id: None,
         };
 
         for (user_component_id, user_component) in user_component_db
@@ -144,18 +215,46 @@ impl ComponentDb {
                     );
                 }
                 Ok(c) => {
+                    let raw_identifiers_id = user_component.raw_callable_identifiers_id();
                     let lifecycle = raw_identifiers_db
-                        .get_lifecycle(user_component.raw_callable_identifiers_id())
+                        .get_lifecycle(raw_identifiers_id)
                         .unwrap();
+                    if let Some(policy) = raw_identifiers_db.get_startup_policy(raw_identifiers_id)
+                    {
+                        match guard_constructor(
+                            &computation_db[user_component_id],
+                            lifecycle,
+                            policy,
+                            package_graph,
+                            krate_collection,
+                        ) {
+                            Ok(Some(guarded)) => {
+                                computation_db.replace_callable(user_component_id, guarded)
+                            }
+                            Ok(None) => {}
+                            Err(e) => {
+                                Self::invalid_startup_policy(
+                                    e,
+                                    user_component_id,
+                                    user_component_db,
+                                    package_graph,
+                                    raw_identifiers_db,
+                                    diagnostics,
+                                );
+                            }
+                        }
+                    }
                     let constructor_id = self_.interner.get_or_intern(Component::Constructor {
-                        source_id: ConstructorSourceId::UserComponentId(user_component_id),
+                        source_id: SourceId::UserComponentId(user_component_id),
                     });
                     user_component_id2component_id.insert(user_component_id, constructor_id);
                     self_
                         .id2lifecycle
                         .insert(constructor_id, lifecycle.to_owned());
+                    let scope_id = user_component.scope_id();
+                    self_.add_scope_id(constructor_id, scope_id);
 
-                    self_.register_derived_constructors(constructor_id, computation_db);
+                    self_.register_derived_constructors(constructor_id, scope_id, computation_db);
                     if is_result(c.output_type()) && lifecycle != &Lifecycle::Singleton {
                         // We'll try to match all fallible constructors with an error handler later.
                         // We skip singletons since we do not "handle" errors when constructing them.
@@ -167,12 +266,108 @@ impl ComponentDb {
             }
         }
 
+        // Framework types are not built by a constructor: they are provided as input parameters
+        // to the request processing pipeline.
+        // We still need to register an inlineable constructor that transforms `T` in `&T` for
+        // each of them, so that constructors and handlers can borrow them (e.g. `&Request`).
+        let mut framework_bindings: Vec<_> = request_scoped_framework_bindings.iter().collect();
+        framework_bindings.sort_by_key(|(ident, _)| ident.to_string());
+        for (_, framework_type) in framework_bindings {
+            let c: Computation<'_> = BorrowSharedReference::new(framework_type.to_owned()).into();
+            self_.add_synthetic_constructor(
+                c.try_into().unwrap(),
+                Lifecycle::RequestScoped,
+                scope_graph.root_scope_id(),
+                computation_db,
+            );
+        }
+
+        // `Next` is provided by the framework, but only to middlewares.
+        let next_type = &request_scoped_framework_bindings
+            .get_by_left(&format_ident!("next"))
+            .unwrap();
+        for (user_component_id, user_component) in user_component_db
+            .iter()
+            .filter(|(_, c)| c.callable_type() == CallableType::Middleware)
+        {
+            let callable = &computation_db[user_component_id];
+            match Middleware::new(Cow::Borrowed(callable), next_type) {
+                Err(e) => {
+                    Self::invalid_middleware(
+                        e,
+                        user_component_id,
+                        user_component_db,
+                        package_graph,
+                        raw_identifiers_db,
+                        diagnostics,
+                    );
+                }
+                Ok(_) => {
+                    let middleware_id = self_
+                        .interner
+                        .get_or_intern(Component::Middleware { user_component_id });
+                    user_component_id2component_id.insert(user_component_id, middleware_id);
+                    self_
+                        .id2lifecycle
+                        .insert(middleware_id, Lifecycle::RequestScoped);
+                    self_.add_scope_id(middleware_id, user_component.scope_id());
+                }
+            }
+        }
+
+        for (user_component_id, _) in user_component_db
+            .iter()
+            .filter(|(_, c)| c.callable_type() == CallableType::ShutdownHook)
+        {
+            let callable = &computation_db[user_component_id];
+            match ShutdownHook::new(Cow::Borrowed(callable)) {
+                Err(e) => {
+                    Self::invalid_shutdown_hook(
+                        e,
+                        user_component_id,
+                        user_component_db,
+                        package_graph,
+                        raw_identifiers_db,
+                        diagnostics,
+                    );
+                }
+                Ok(_) => {
+                    self_.shutdown_hook_id = Some(user_component_id);
+                }
+            }
+        }
+
+        // Error observers are attached to the fallible components they can observe later on,
+        // once we know which fallible components have an error handler.
+        let mut error_observers = vec![];
+        for (user_component_id, _) in user_component_db
+            .iter()
+            .filter(|(_, c)| c.callable_type() == CallableType::ErrorObserver)
+        {
+            let callable = &computation_db[user_component_id];
+            match ErrorObserver::new(Cow::Borrowed(callable)) {
+                Err(e) => {
+                    Self::invalid_error_observer(
+                        e,
+                        user_component_id,
+                        user_component_db,
+                        package_graph,
+                        raw_identifiers_db,
+                        diagnostics,
+                    );
+                }
+                Ok(o) => {
+                    error_observers.push((user_component_id, o.error_type().to_owned()));
+                }
+            }
+        }
+
         for (user_component_id, user_component) in user_component_db
             .iter()
             .filter(|(_, c)| c.callable_type() == CallableType::RequestHandler)
         {
             let callable = &computation_db[user_component_id];
-            let UserComponent::RequestHandler { route, .. } = user_component else {
+            let UserComponent::RequestHandler { router_key, .. } = user_component else {
                 unreachable!()
             };
             match RequestHandler::new(Cow::Borrowed(callable)) {
@@ -191,9 +386,39 @@ impl ComponentDb {
                         .interner
                         .get_or_intern(Component::RequestHandler { user_component_id });
                     user_component_id2component_id.insert(user_component_id, handler_id);
-                    self_.router.insert(route.to_owned(), handler_id);
+                    self_.add_scope_id(handler_id, user_component.scope_id());
+                    match router_key {
+                        Some(router_key) => {
+                            if self_
+                                .router
+                                .insert(router_key.to_owned(), handler_id)
+                                .is_some()
+                            {
+                                Self::duplicated_route(
+                                    router_key,
+                                    user_component_id,
+                                    user_component_db,
+                                    package_graph,
+                                    raw_identifiers_db,
+                                    diagnostics,
+                                );
+                            }
+                        }
+                        None => {
+                            self_.fallback_id = Some(handler_id);
+                        }
+                    }
                     let lifecycle = Lifecycle::RequestScoped;
                     self_.id2lifecycle.insert(handler_id, lifecycle.clone());
+                    // Middlewares that failed validation have already been reported.
+                    let middleware_ids = user_component_db
+                        .middleware_ids(user_component_id)
+                        .iter()
+                        .filter_map(|id| user_component_id2component_id.get(id).copied())
+                        .collect();
+                    self_
+                        .handler_id2middleware_ids
+                        .insert(handler_id, middleware_ids);
 
                     if is_result(h.output_type()) {
                         // We'll try to match it with an error handler later.
@@ -236,7 +461,12 @@ impl ComponentDb {
                     fallible_callable_identifiers_id,
                     ..
                 } => Some((id, *fallible_callable_identifiers_id)),
-                UserComponent::RequestHandler { .. } | UserComponent::Constructor { .. } => None,
+                UserComponent::RequestHandler { .. }
+                | UserComponent::DefaultErrorHandler { .. }
+                | UserComponent::ErrorObserver { .. }
+                | UserComponent::Constructor { .. }
+                | UserComponent::Middleware { .. }
+                | UserComponent::ShutdownHook { .. } => None,
             })
         {
             let lifecycle = raw_identifiers_db
@@ -268,7 +498,9 @@ impl ComponentDb {
                     Ok(e) => {
                         let error_handler_id =
                             self_.interner.get_or_intern(Component::ErrorHandler {
-                                user_component_id: error_handler_user_component_id,
+                                source_id: SourceId::UserComponentId(
+                                    error_handler_user_component_id,
+                                ),
                             });
                         self_
                             .error_handler_id2error_handler
@@ -276,6 +508,10 @@ impl ComponentDb {
                         self_
                             .id2lifecycle
                             .insert(error_handler_id, lifecycle.to_owned());
+                        self_.add_scope_id(
+                            error_handler_id,
+                            user_component_db[error_handler_user_component_id].scope_id(),
+                        );
                         user_component_id2component_id
                             .insert(error_handler_user_component_id, error_handler_id);
                         fallible_component_id2error_handler_id.insert(
@@ -306,6 +542,250 @@ impl ComponentDb {
             }
         }
 
+        // Error handlers registered via `bp.error_handler_for` are used for all the fallible
+        // components that don't have a dedicated error handler, as long as their error type
+        // matches and they were registered against the same blueprint (or a nested one) as the
+        // error handler.
+        let mut scope_id2default_error_handlers =
+            HashMap::<ScopeId, Vec<(UserComponentId, ResolvedType)>>::new();
+        for (user_component_id, user_component) in user_component_db.iter() {
+            let UserComponent::DefaultErrorHandler {
+                error_type: error_type_name,
+                scope_id,
+                raw_callable_identifiers_id,
+            } = user_component
+            else {
+                continue;
+            };
+            // `error_type_name` comes from `std::any::type_name`: we resolve it against the
+            // docs of the crate it points at, the same way we resolve the error types returned
+            // by fallible components, to be able to compare them.
+            let registered_at = raw_identifiers_db[*raw_callable_identifiers_id].registered_at();
+            let error_type =
+                ResolvedPath::parse_type_name(error_type_name, registered_at, package_graph)
+                    .map_err(anyhow::Error::from)
+                    .and_then(|t| t.resolve(krate_collection));
+            let error_type = match error_type {
+                Ok(t) => t,
+                Err(_) => {
+                    Self::unknown_default_error_type(
+                        error_type_name,
+                        user_component_id,
+                        user_component_db,
+                        package_graph,
+                        raw_identifiers_db,
+                        diagnostics,
+                    );
+                    continue;
+                }
+            };
+            let takes_error_as_input =
+                computation_db[user_component_id]
+                    .inputs
+                    .iter()
+                    .any(|input| match input {
+                        ResolvedType::Reference(r) => {
+                            !r.is_mutable && r.inner.as_ref() == &error_type
+                        }
+                        _ => false,
+                    });
+            if !takes_error_as_input {
+                Self::default_error_handler_without_error_input(
+                    &error_type,
+                    user_component_id,
+                    user_component_db,
+                    package_graph,
+                    raw_identifiers_db,
+                    diagnostics,
+                );
+                continue;
+            }
+            let scope_defaults = scope_id2default_error_handlers
+                .entry(*scope_id)
+                .or_default();
+            if let Some((first_id, _)) = scope_defaults.iter().find(|(_, t)| t == &error_type) {
+                Self::conflicting_default_error_handlers(
+                    &error_type,
+                    *first_id,
+                    user_component_id,
+                    user_component_db,
+                    package_graph,
+                    raw_identifiers_db,
+                    diagnostics,
+                );
+                continue;
+            }
+            scope_defaults.push((user_component_id, error_type));
+        }
+
+        // We go through user components in registration order to keep the outcome deterministic.
+        let unhandled_user_component_ids: Vec<_> = user_component_db
+            .iter()
+            .map(|(id, _)| id)
+            .filter(|id| matches!(fallible_component_id2error_handler_id.get(id), Some(None)))
+            .collect();
+        let mut invalid_default_ids = HashSet::new();
+        for fallible_user_component_id in unhandled_user_component_ids {
+            let fallible_callable = &computation_db[fallible_user_component_id];
+            let error_type = get_err_variant(fallible_callable.output.as_ref().unwrap());
+            // The default registered against the innermost scope takes precedence.
+            let default_id = scope_graph
+                .ancestors(user_component_db[fallible_user_component_id].scope_id())
+                .find_map(|scope_id| {
+                    scope_id2default_error_handlers
+                        .get(&scope_id)?
+                        .iter()
+                        .find(|(_, t)| t == error_type)
+                        .map(|(id, _)| *id)
+                });
+            let Some(default_id) = default_id else {
+                continue;
+            };
+            fallible_component_id2error_handler_id.insert(
+                fallible_user_component_id,
+                Some(ErrorHandlerId::UserId(default_id)),
+            );
+            match ErrorHandler::new(computation_db[default_id].to_owned(), fallible_callable) {
+                Ok(e) => {
+                    // A default error handler is shared by all the fallible components it
+                    // applies to.
+                    let error_handler_id = self_.interner.get_or_intern(Component::ErrorHandler {
+                        source_id: SourceId::UserComponentId(default_id),
+                    });
+                    self_
+                        .error_handler_id2error_handler
+                        .entry(error_handler_id)
+                        .or_insert(e);
+                    let lifecycle = raw_identifiers_db
+                        .get_lifecycle(
+                            user_component_db[fallible_user_component_id]
+                                .raw_callable_identifiers_id(),
+                        )
+                        .unwrap();
+                    self_
+                        .id2lifecycle
+                        .entry(error_handler_id)
+                        .or_insert_with(|| lifecycle.to_owned());
+                    // Its inputs are resolved in the scope of the fallible component it handles,
+                    // just like an error handler registered directly against the component.
+                    self_.add_scope_id(
+                        error_handler_id,
+                        user_component_db[fallible_user_component_id].scope_id(),
+                    );
+                    user_component_id2component_id.insert(default_id, error_handler_id);
+                    fallible_component_id2error_handler_id.insert(
+                        fallible_user_component_id,
+                        Some(ErrorHandlerId::Id(error_handler_id)),
+                    );
+                }
+                Err(e) => {
+                    if invalid_default_ids.insert(default_id) {
+                        Self::invalid_error_handler(
+                            e,
+                            default_id,
+                            user_component_db,
+                            package_graph,
+                            raw_identifiers_db,
+                            diagnostics,
+                        );
+                    }
+                }
+            }
+        }
+
+        // If no error handler was registered, we fall back to the `ResponseError` implementation
+        // for the error type, if there is one.
+        let response_error = process_framework_path(
+            "pavex_runtime::response::ResponseError",
+            package_graph,
+            krate_collection,
+        );
+        let ResolvedType::ResolvedPath(response_error) = response_error else {
+            unreachable!()
+        };
+        let response_error_path = response_error.resolved_path();
+        let unhandled_user_component_ids: Vec<_> = user_component_db
+            .iter()
+            .map(|(id, _)| id)
+            .filter(|id| matches!(fallible_component_id2error_handler_id.get(id), Some(None)))
+            .collect();
+        for fallible_user_component_id in unhandled_user_component_ids {
+            let fallible_callable = &computation_db[fallible_user_component_id];
+            let error_type = get_err_variant(fallible_callable.output.as_ref().unwrap()).to_owned();
+            if !implements_trait(krate_collection, &error_type, &response_error) {
+                continue;
+            }
+            // The error type of a generic constructor is only known once it is instantiated.
+            let Ok(error_type_path) = error_type.clone().try_into() else {
+                continue;
+            };
+            let mut segments = response_error_path.segments.clone();
+            segments.push(ResolvedPathSegment {
+                ident: "error_response".into(),
+                generic_arguments: vec![],
+            });
+            let error_handler_path = ResolvedPath {
+                segments,
+                qualified_self: Some(ResolvedPathQualifiedSelf {
+                    position: response_error_path.segments.len(),
+                    type_: error_type_path,
+                }),
+                package_id: response_error_path.package_id.clone(),
+            };
+            let callable_id = match computation_db.resolve_callable(
+                krate_collection,
+                &error_handler_path,
+                None,
+            ) {
+                Ok(callable_id) => callable_id,
+                Err(e) => {
+                    Self::cannot_handle_response_error_implementation(
+                        e,
+                        &error_type,
+                        fallible_user_component_id,
+                        user_component_db,
+                        package_graph,
+                        raw_identifiers_db,
+                        diagnostics,
+                    );
+                    // We have already reported the failure: there is no need to complain
+                    // about a missing error handler as well.
+                    fallible_component_id2error_handler_id.remove(&fallible_user_component_id);
+                    continue;
+                }
+            };
+            let Computation::Callable(callable) = &computation_db[callable_id] else {
+                unreachable!()
+            };
+            let fallible_callable = &computation_db[fallible_user_component_id];
+            // `error_response` takes `&self`, therefore the validation can't fail.
+            let e = ErrorHandler::new(callable.clone().into_owned(), fallible_callable).unwrap();
+            let error_handler_id = self_.interner.get_or_intern(Component::ErrorHandler {
+                source_id: SourceId::ComputationId(callable_id),
+            });
+            self_
+                .error_handler_id2error_handler
+                .entry(error_handler_id)
+                .or_insert(e);
+            let lifecycle = raw_identifiers_db
+                .get_lifecycle(
+                    user_component_db[fallible_user_component_id].raw_callable_identifiers_id(),
+                )
+                .unwrap();
+            self_
+                .id2lifecycle
+                .entry(error_handler_id)
+                .or_insert_with(|| lifecycle.to_owned());
+            self_.add_scope_id(
+                error_handler_id,
+                user_component_db[fallible_user_component_id].scope_id(),
+            );
+            fallible_component_id2error_handler_id.insert(
+                fallible_user_component_id,
+                Some(ErrorHandlerId::Id(error_handler_id)),
+            );
+        }
+
         for (fallible_user_component_id, error_handler_id) in fallible_component_id2error_handler_id
         {
             match error_handler_id {
@@ -326,6 +806,42 @@ impl ComponentDb {
                         .hydrated_component(err_match_id, computation_db)
                         .output_type()
                         .to_owned();
+                    // Error observers registered against the scope of the fallible component, or
+                    // against one of its ancestors, are invoked if they observe its error type
+                    // or if they observe `pavex_runtime::Error`.
+                    let fallible_scope_ids: HashSet<_> = scope_graph
+                        .ancestors(user_component_db[fallible_user_component_id].scope_id())
+                        .collect();
+                    let mut error_observer_ids = vec![];
+                    for (observer_user_component_id, observed_type) in &error_observers {
+                        let observes_every_error = observed_type == &self_.pavex_error;
+                        if (observed_type != &err_type && !observes_every_error)
+                            || !fallible_scope_ids.contains(
+                                &user_component_db[*observer_user_component_id].scope_id(),
+                            )
+                        {
+                            continue;
+                        }
+                        let error_observer_id =
+                            self_.interner.get_or_intern(Component::ErrorObserver {
+                                user_component_id: *observer_user_component_id,
+                            });
+                        // Error observers don't return anything: there is no point in
+                        // sharing a single invocation between different errors.
+                        self_
+                            .id2lifecycle
+                            .insert(error_observer_id, Lifecycle::Transient);
+                        // Its inputs are resolved in the scope of the fallible component it
+                        // observes, just like the inputs of its error handler.
+                        self_.add_scope_id(
+                            error_observer_id,
+                            user_component_db[fallible_user_component_id].scope_id(),
+                        );
+                        if observes_every_error {
+                            self_.pavex_error_observer_ids.insert(error_observer_id);
+                        }
+                        error_observer_ids.push(error_observer_id);
+                    }
                     let err_ref_id = self_.add_synthetic_transformer(
                         BorrowSharedReference::new(err_type).into(),
                         err_match_id,
@@ -335,6 +851,12 @@ impl ComponentDb {
                     self_
                         .err_ref_id2error_handler_id
                         .insert(err_ref_id, error_handler_id);
+                    if !error_observer_ids.is_empty() {
+                        self_
+                            .err_ref_id2error_observer_ids
+                            .insert(err_ref_id, error_observer_ids);
+                        self_.add_error_conversion(err_ref_id, computation_db);
+                    }
                 }
                 Some(ErrorHandlerId::UserId(_)) => {}
             }
@@ -348,18 +870,34 @@ impl ComponentDb {
             package_graph,
             krate_collection,
         );
-        let ResolvedType::ResolvedPath(into_response) = into_response else { unreachable!() };
+        let ResolvedType::ResolvedPath(into_response) = into_response else {
+            unreachable!()
+        };
         let into_response_path = into_response.resolved_path();
         let iter: Vec<_> = self_
             .interner
             .iter()
             .filter_map(|(id, c)| match c {
-                Component::RequestHandler { user_component_id }
-                | Component::ErrorHandler { user_component_id } => Some((id, *user_component_id)),
-                _ => None,
+                Component::RequestHandler { .. }
+                | Component::ErrorHandler { .. }
+                | Component::Middleware { .. } => Some(id),
+                Component::Constructor { .. }
+                | Component::ErrorObserver { .. }
+                | Component::Transformer { .. } => None,
             })
             .collect();
-        for (component_id, user_component_id) in iter.into_iter() {
+        for component_id in iter.into_iter() {
+            // Error handlers synthesized by `pavex` return `pavex_runtime::response::Response`:
+            // there is no user component to blame if something goes wrong.
+            let user_component_id = self_.user_component_id(component_id);
+            let hydrated_component = self_.hydrated_component(component_id, computation_db);
+            let output = hydrated_component.output_type();
+            let output = if is_result(output) {
+                get_ok_variant(output)
+            } else {
+                output
+            }
+            .to_owned();
             // If the component is fallible, we want to attach the transformer to its Ok matcher.
             let component_id =
                 if let Some((ok_id, _)) = self_.fallible_id2match_ids.get(&component_id) {
@@ -367,26 +905,25 @@ impl ComponentDb {
                 } else {
                     component_id
                 };
-            let callable = &computation_db[user_component_id];
-            let output = callable.output.as_ref().unwrap();
-            let output = if is_result(output) {
-                get_ok_variant(output)
-            } else {
-                output
-            }
-            .to_owned();
             if let Err(e) = assert_trait_is_implemented(krate_collection, &output, &into_response) {
-                Self::invalid_response_type(
-                    e,
-                    &output,
-                    user_component_id,
-                    user_component_db,
-                    package_graph,
-                    raw_identifiers_db,
-                    diagnostics,
-                );
+                if let Some(user_component_id) = user_component_id {
+                    Self::invalid_response_type(
+                        e,
+                        &output,
+                        user_component_id,
+                        user_component_db,
+                        package_graph,
+                        raw_identifiers_db,
+                        diagnostics,
+                    );
+                }
                 continue;
             }
+            // Generic parameters that haven't been assigned a concrete type have already been
+            // reported.
+            let Ok(output_path) = output.clone().try_into() else {
+                continue;
+            };
             let mut transformer_segments = into_response_path.segments.clone();
             transformer_segments.push(ResolvedPathSegment {
                 ident: "into_response".into(),
@@ -396,7 +933,7 @@ impl ComponentDb {
                 segments: transformer_segments,
                 qualified_self: Some(ResolvedPathQualifiedSelf {
                     position: into_response_path.segments.len(),
-                    type_: output.clone().into(),
+                    type_: output_path,
                 }),
                 package_id: into_response_path.package_id.clone(),
             };
@@ -405,15 +942,17 @@ impl ComponentDb {
                     self_.get_or_intern_transformer(callable_id, component_id);
                 }
                 Err(e) => {
-                    Self::cannot_handle_into_response_implementation(
-                        e,
-                        &output,
-                        user_component_id,
-                        user_component_db,
-                        package_graph,
-                        raw_identifiers_db,
-                        diagnostics,
-                    );
+                    if let Some(user_component_id) = user_component_id {
+                        Self::cannot_handle_into_response_implementation(
+                            e,
+                            &output,
+                            user_component_id,
+                            user_component_db,
+                            package_graph,
+                            raw_identifiers_db,
+                            diagnostics,
+                        );
+                    }
                 }
             }
         }
@@ -427,10 +966,56 @@ impl ComponentDb {
     }
 
     /// The mapping from a route to its dedicated request handler.
-    pub fn router(&self) -> &BTreeMap<String, ComponentId> {
+    pub fn router(&self) -> &BTreeMap<RouterKey, ComponentId> {
         &self.router
     }
 
+    /// The request handler in charge of processing requests that do not match any route,
+    /// if one was registered.
+    pub fn fallback(&self) -> Option<ComponentId> {
+        self.fallback_id
+    }
+
+    /// The callable invoked when the server shuts down gracefully, if one was registered.
+    pub fn shutdown_hook<'a, 'b: 'a>(
+        &'a self,
+        computation_db: &'b ComputationDb,
+    ) -> Option<ShutdownHook<'a>> {
+        self.shutdown_hook_id.map(|user_component_id| ShutdownHook {
+            callable: Cow::Borrowed(&computation_db[user_component_id]),
+        })
+    }
+
+    /// The id of the user component for the shutdown hook, if one was registered.
+    pub fn shutdown_hook_id(&self) -> Option<UserComponentId> {
+        self.shutdown_hook_id
+    }
+
+    /// The middlewares that wrap a request handler, from the outermost to the innermost.
+    pub fn middleware_ids(&self, request_handler_id: ComponentId) -> &[ComponentId] {
+        self.handler_id2middleware_ids
+            .get(&request_handler_id)
+            .map(|ids| ids.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// The scopes that a component is associated with.
+    ///
+    /// It is empty for transformers, since they do not support dependency injection.
+    pub fn scope_ids(&self, id: ComponentId) -> impl Iterator<Item = ScopeId> + '_ {
+        self.id2scope_ids.get(&id).into_iter().flatten().copied()
+    }
+
+    /// The scope of the blueprint that a request handler or a middleware was registered
+    /// against.
+    pub fn scope_id(&self, id: ComponentId) -> ScopeId {
+        self.scope_ids(id).next().unwrap()
+    }
+
+    fn add_scope_id(&mut self, id: ComponentId, scope_id: ScopeId) {
+        self.id2scope_ids.entry(id).or_default().insert(scope_id);
+    }
+
     /// Iterate over all the components in the database alongside their ids.
     pub fn iter(
         &self,
@@ -443,6 +1028,7 @@ impl ComponentDb {
         &mut self,
         c: Constructor<'static>,
         l: Lifecycle,
+        scope_id: ScopeId,
         computation_db: &mut ComputationDb,
     ) -> ComponentId {
         let computation_id = computation_db.get_or_intern(c);
@@ -450,7 +1036,8 @@ impl ComponentDb {
             source_id: computation_id.into(),
         });
         self.id2lifecycle.insert(id, l);
-        self.register_derived_constructors(id, computation_db);
+        self.add_scope_id(id, scope_id);
+        self.register_derived_constructors(id, scope_id, computation_db);
         id
     }
 
@@ -473,14 +1060,75 @@ impl ComponentDb {
         id
     }
 
+    /// Convert the error reference into a `pavex_runtime::Error` if one of its error observers
+    /// takes `&pavex_runtime::Error` as input.
+    ///
+    /// The conversion is not registered as a transformer of the error reference: it is added to
+    /// the call graph alongside the error observers it feeds.
+    fn add_error_conversion(
+        &mut self,
+        err_ref_id: ComponentId,
+        computation_db: &mut ComputationDb,
+    ) {
+        let err_ref_type = self
+            .hydrated_component(err_ref_id, computation_db)
+            .output_type()
+            .to_owned();
+        let ResolvedType::Reference(err_ref) = &err_ref_type else {
+            unreachable!()
+        };
+        if err_ref.inner.as_ref() == &self.pavex_error
+            // The error type of a generic constructor is only known once it is instantiated.
+            || !err_ref_type.unassigned_generic_type_parameters().is_empty()
+            || !self
+                .error_observer_ids(err_ref_id)
+                .iter()
+                .any(|id| self.pavex_error_observer_ids.contains(id))
+        {
+            return;
+        }
+        let ResolvedType::ResolvedPath(pavex_error) = &self.pavex_error else {
+            unreachable!()
+        };
+        let mut from_ref_path = pavex_error.resolved_path();
+        from_ref_path.segments.push(ResolvedPathSegment {
+            ident: "from_ref".into(),
+            generic_arguments: vec![],
+        });
+        let conversion = Callable {
+            is_async: false,
+            path: from_ref_path,
+            output: Some(self.pavex_error.clone()),
+            inputs: vec![err_ref_type],
+            invocation_style: InvocationStyle::FunctionCall,
+        };
+        let lifecycle = self.id2lifecycle[&err_ref_id].clone();
+        let mut intern = |computation: Computation<'static>| {
+            let computation_id = computation_db.get_or_intern(computation);
+            let id = self
+                .interner
+                .get_or_intern(Component::Transformer { computation_id });
+            self.id2lifecycle.insert(id, lifecycle.clone());
+            id
+        };
+        let conversion_id = intern(conversion.into());
+        let borrow_id = intern(BorrowSharedReference::new(self.pavex_error.clone()).into());
+        self.err_ref_id2error_conversion_ids
+            .insert(err_ref_id, (conversion_id, borrow_id));
+    }
+
     fn register_derived_constructors(
         &mut self,
         constructor_id: ComponentId,
+        scope_id: ScopeId,
         computation_db: &mut ComputationDb,
     ) {
         let constructor = {
-            let HydratedComponent::Constructor(constructor) = self.hydrated_component(constructor_id, computation_db)
-                else { unreachable!() };
+            let HydratedComponent::Constructor(constructor) =
+                self.hydrated_component(constructor_id, computation_db)
+            else {
+                unreachable!()
+            };
             constructor.into_owned()
         };
         let output_type = constructor.output_type().to_owned();
@@ -495,6 +1143,7 @@ impl ComponentDb {
                 // that returns the unit type;
                 c.try_into().unwrap(),
                 lifecycle.to_owned(),
+                scope_id,
                 computation_db,
             );
         }
@@ -507,6 +1156,7 @@ impl ComponentDb {
             let ok_id = self.add_synthetic_constructor(
                 ok.into_owned(),
                 lifecycle.to_owned(),
+                scope_id,
                 computation_db,
             );
 
@@ -531,24 +1181,85 @@ impl ComponentDb {
         &mut self,
         callable_id: ComputationId,
         lifecycle: Lifecycle,
+        scope_id: ScopeId,
         computation_db: &mut ComputationDb,
     ) -> Result<ComponentId, ConstructorValidationError> {
         let callable = computation_db[callable_id].to_owned();
         TryInto::<Constructor>::try_into(callable)?;
         let constructor_id = self.interner.get_or_intern(Component::Constructor {
-            source_id: ConstructorSourceId::ComputationId(callable_id),
+            source_id: SourceId::ComputationId(callable_id),
         });
         self.id2lifecycle.insert(constructor_id, lifecycle);
-        self.register_derived_constructors(constructor_id, computation_db);
+        self.add_scope_id(constructor_id, scope_id);
+        self.register_derived_constructors(constructor_id, scope_id, computation_db);
         Ok(constructor_id)
     }
 
-    pub fn get_or_intern_transformer(
+    /// Register a constructor obtained by assigning a concrete type to the generic parameters
+    /// of a user-registered generic constructor, its template.
+    ///
+    /// The instantiated constructor inherits the lifecycle and the scopes of its template.
+    /// If the template is fallible, errors returned by the instantiated constructor are
+    /// handled by the error handler that was registered for the template.
+    pub fn get_or_intern_instantiated_constructor(
         &mut self,
+        template_id: ComponentId,
         callable_id: ComputationId,
-        transformed_component_id: ComponentId,
-    ) -> ComponentId {
-        let transformer = Component::Transformer {
+        computation_db: &mut ComputationDb,
+    ) -> Result<ComponentId, ConstructorValidationError> {
+        let lifecycle = self.lifecycle(template_id).unwrap().to_owned();
+        let scope_ids: Vec<_> = self.scope_ids(template_id).collect();
+        let mut constructor_id = None;
+        for scope_id in scope_ids {
+            constructor_id = Some(self.get_or_intern_constructor(
+                callable_id,
+                lifecycle.clone(),
+                scope_id,
+                computation_db,
+            )?);
+        }
+        // User-registered constructors are always associated with a scope.
+        let constructor_id = constructor_id.unwrap();
+
+        if let (Some(&(_, template_err_id)), Some(&(_, err_id))) =
+            (self.match_ids(template_id), self.match_ids(constructor_id))
+        {
+            let error_handler_id = self
+                .transformer_ids(template_err_id)
+                .into_iter()
+                .flatten()
+                .find_map(|id| Some((*id, *self.error_handler_id(*id)?)));
+            if let Some((template_err_ref_id, error_handler_id)) = error_handler_id {
+                let err_type = self
+                    .hydrated_component(err_id, computation_db)
+                    .output_type()
+                    .to_owned();
+                let err_ref_id = self.add_synthetic_transformer(
+                    BorrowSharedReference::new(err_type).into(),
+                    err_id,
+                    lifecycle,
+                    computation_db,
+                );
+                self.err_ref_id2error_handler_id
+                    .insert(err_ref_id, error_handler_id);
+                if let Some(error_observer_ids) =
+                    self.err_ref_id2error_observer_ids.get(&template_err_ref_id)
+                {
+                    self.err_ref_id2error_observer_ids
+                        .insert(err_ref_id, error_observer_ids.to_owned());
+                    self.add_error_conversion(err_ref_id, computation_db);
+                }
+            }
+        }
+        Ok(constructor_id)
+    }
+
+    pub fn get_or_intern_transformer(
+        &mut self,
+        callable_id: ComputationId,
+        transformed_component_id: ComponentId,
+    ) -> ComponentId {
+        let transformer = Component::Transformer {
             computation_id: callable_id,
         };
         let transformer_id = self.interner.get_or_intern(transformer);
@@ -570,6 +1281,54 @@ impl ComponentDb {
         self.err_ref_id2error_handler_id.get(&err_ref_id)
     }
 
+    /// The ids of the error observers to be invoked, in order, before the error handler for an
+    /// error reference.
+    pub fn error_observer_ids(&self, err_ref_id: ComponentId) -> &[ComponentId] {
+        self.err_ref_id2error_observer_ids
+            .get(&err_ref_id)
+            .map(|ids| ids.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// If the error reference must be converted into a `pavex_runtime::Error` for some of its
+    /// error observers, return the ids of the conversion and of the borrow of its output,
+    /// `&pavex_runtime::Error`.
+    /// Otherwise, return `None`.
+    pub fn error_conversion_ids(
+        &self,
+        err_ref_id: ComponentId,
+    ) -> Option<(ComponentId, ComponentId)> {
+        self.err_ref_id2error_conversion_ids
+            .get(&err_ref_id)
+            .copied()
+    }
+
+    /// Iterate over the error references that must be converted into a `pavex_runtime::Error`,
+    /// alongside the ids of the error observers that take the converted error as input.
+    pub fn error_conversions(
+        &self,
+    ) -> impl Iterator<Item = (ComponentId, impl Iterator<Item = ComponentId> + '_)> + '_ {
+        self.err_ref_id2error_conversion_ids
+            .keys()
+            .map(|&err_ref_id| (err_ref_id, self.converted_error_observer_ids(err_ref_id)))
+    }
+
+    /// The ids of the error observers that take the error reference converted into a
+    /// `pavex_runtime::Error` as input.
+    /// It is empty if the error reference does not need to be converted.
+    pub fn converted_error_observer_ids(
+        &self,
+        err_ref_id: ComponentId,
+    ) -> impl Iterator<Item = ComponentId> + '_ {
+        let is_converted = self
+            .err_ref_id2error_conversion_ids
+            .contains_key(&err_ref_id);
+        self.error_observer_ids(err_ref_id)
+            .iter()
+            .copied()
+            .filter(move |id| is_converted && self.pavex_error_observer_ids.contains(id))
+    }
+
     /// If transformations must be applied to the component, return their ids.
     /// Otherwise, return `None`.
     pub fn transformer_ids(&self, component_id: ComponentId) -> Option<&IndexSet<ComponentId>> {
@@ -600,11 +1359,13 @@ impl ComponentDb {
         self.interner.iter().filter_map(|(id, c)| match c {
             Component::RequestHandler { .. }
             | Component::ErrorHandler { .. }
+            | Component::ErrorObserver { .. }
+            | Component::Middleware { .. }
             | Component::Transformer { .. } => None,
             Component::Constructor { source_id } => {
                 let computation = match source_id {
-                    ConstructorSourceId::ComputationId(id) => computation_db[*id].clone(),
-                    ConstructorSourceId::UserComponentId(id) => computation_db[*id].clone().into(),
+                    SourceId::ComputationId(id) => computation_db[*id].clone(),
+                    SourceId::UserComponentId(id) => computation_db[*id].clone().into(),
                 };
                 Some((id, Constructor(computation)))
             }
@@ -613,12 +1374,15 @@ impl ComponentDb {
 
     pub(crate) fn user_component_id(&self, id: ComponentId) -> Option<UserComponentId> {
         match &self[id] {
-            Component::Constructor { source_id } => match source_id {
-                ConstructorSourceId::ComputationId(_id) => None,
-                ConstructorSourceId::UserComponentId(id) => Some(*id),
-            },
-            Component::ErrorHandler { user_component_id }
-            | Component::RequestHandler { user_component_id } => Some(*user_component_id),
+            Component::Constructor { source_id } | Component::ErrorHandler { source_id } => {
+                match source_id {
+                    SourceId::ComputationId(_id) => None,
+                    SourceId::UserComponentId(id) => Some(*id),
+                }
+            }
+            Component::RequestHandler { user_component_id }
+            | Component::ErrorObserver { user_component_id }
+            | Component::Middleware { user_component_id } => Some(*user_component_id),
             Component::Transformer { .. } => None,
         }
     }
@@ -637,14 +1401,28 @@ impl ComponentDb {
                 };
                 HydratedComponent::RequestHandler(request_handler)
             }
+            Component::Middleware { user_component_id } => {
+                let callable = &computation_db[*user_component_id];
+                let middleware = Middleware {
+                    callable: Cow::Borrowed(callable),
+                };
+                HydratedComponent::Middleware(middleware)
+            }
             Component::ErrorHandler { .. } => {
                 let error_handler = &self.error_handler_id2error_handler[&id];
                 HydratedComponent::ErrorHandler(Cow::Borrowed(error_handler))
             }
+            Component::ErrorObserver { user_component_id } => {
+                let callable = &computation_db[*user_component_id];
+                let error_observer = ErrorObserver {
+                    callable: Cow::Borrowed(callable),
+                };
+                HydratedComponent::ErrorObserver(error_observer)
+            }
             Component::Constructor { source_id } => {
                 let c = match source_id {
-                    ConstructorSourceId::ComputationId(id) => computation_db[*id].clone(),
-                    ConstructorSourceId::UserComponentId(id) => computation_db[*id].clone().into(),
+                    SourceId::ComputationId(id) => computation_db[*id].clone(),
+                    SourceId::UserComponentId(id) => computation_db[*id].clone().into(),
                 };
                 HydratedComponent::Constructor(Constructor(c))
             }
@@ -676,7 +1454,8 @@ impl ComponentDb {
     ) {
         match e {
             ConstructorValidationError::CannotFalliblyReturnTheUnitType
-            | ConstructorValidationError::CannotReturnTheUnitType => {
+            | ConstructorValidationError::CannotReturnTheUnitType
+            | ConstructorValidationError::UnderconstrainedGenericParameters { .. } => {
                 let raw_identifier_id =
                     user_component_db[user_component_id].raw_callable_identifiers_id();
                 let location = raw_identifiers_db.get_location(raw_identifier_id);
@@ -727,6 +1506,185 @@ impl ComponentDb {
         }
     }
 
+    fn invalid_middleware(
+        e: MiddlewareValidationError,
+        user_component_id: UserComponentId,
+        user_component_db: &UserComponentDb,
+        package_graph: &PackageGraph,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let raw_identifier_id = user_component_db[user_component_id].raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled("The middleware was registered here".into()));
+        let help = match &e {
+            MiddlewareValidationError::CannotReturnTheUnitType => None,
+            MiddlewareValidationError::CannotBeFallible => Some(
+                "Handle the error inside the middleware and convert it into a response.".into(),
+            ),
+            MiddlewareValidationError::MustTakeNextAsInputOnce { next_type } => Some(format!(
+                "Make sure that the middleware takes exactly one input parameter of type \
+                `{next_type:?}`."
+            )),
+        };
+        let diagnostic = CompilerDiagnostic::builder(source, e)
+            .optional_label(label)
+            .optional_help(help)
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+
+    fn invalid_error_observer(
+        e: ErrorObserverValidationError,
+        user_component_id: UserComponentId,
+        user_component_db: &UserComponentDb,
+        package_graph: &PackageGraph,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let raw_identifier_id = user_component_db[user_component_id].raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled("The error observer was registered here".into()));
+        let help = match &e {
+            ErrorObserverValidationError::MustReturnTheUnitType { .. } => None,
+            ErrorObserverValidationError::MustTakeErrorReferenceAsFirstInput => Some(
+                "Take `&pavex_runtime::Error`, or a reference to the error type you want to \
+                observe, as the first input parameter of the error observer."
+                    .into(),
+            ),
+        };
+        let diagnostic = CompilerDiagnostic::builder(source, e)
+            .optional_label(label)
+            .optional_help(help)
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+
+    fn invalid_shutdown_hook(
+        e: ShutdownHookValidationError,
+        user_component_id: UserComponentId,
+        user_component_db: &UserComponentDb,
+        package_graph: &PackageGraph,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let raw_identifier_id = user_component_db[user_component_id].raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled("The shutdown hook was registered here".into()));
+        let help = match &e {
+            ShutdownHookValidationError::MustReturnTheUnitType { .. } => {
+                "If the shutdown hook can fail, handle the error inside the hook (e.g. log it)."
+                    .into()
+            }
+        };
+        let diagnostic = CompilerDiagnostic::builder(source, e)
+            .optional_label(label)
+            .help(help)
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+
+    pub(crate) fn invalid_startup_policy(
+        e: StartupPolicyValidationError,
+        user_component_id: UserComponentId,
+        user_component_db: &UserComponentDb,
+        package_graph: &PackageGraph,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let raw_identifier_id = user_component_db[user_component_id].raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled("The constructor was registered here".into()));
+        let help = match &e {
+            StartupPolicyValidationError::NotASingleton { .. } => {
+                "Register the constructor with `Lifecycle::Singleton` or remove its startup policy."
+                    .into()
+            }
+            StartupPolicyValidationError::CannotTimeOutASynchronousConstructor => {
+                "Make the constructor `async` or remove `.startup_timeout`.".into()
+            }
+            StartupPolicyValidationError::CannotRetryAnInfallibleConstructor => {
+                "Remove `.retry`: this constructor cannot fail.".into()
+            }
+            StartupPolicyValidationError::CannotCloneInputForRetries { input } => format!(
+                "Implement `Clone` for `{input:?}` or take a shared reference as input, \
+                `&{input:?}`."
+            ),
+        };
+        let diagnostic = CompilerDiagnostic::builder(source, e)
+            .optional_label(label)
+            .help(help)
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+
+    fn duplicated_route(
+        router_key: &RouterKey,
+        user_component_id: UserComponentId,
+        user_component_db: &UserComponentDb,
+        package_graph: &PackageGraph,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let raw_identifier_id = user_component_db[user_component_id].raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled("The conflicting request handler was registered here".into()));
+        let error = anyhow::anyhow!(
+            "I cannot register more than one request handler for `{router_key}`.\n\
+            A request handler for the same path and method guard has already been registered \
+            against another blueprint."
+        );
+        let diagnostic = CompilerDiagnostic::builder(source, error)
+            .optional_label(label)
+            .help(
+                "Double-check the path prefixes passed to `nest`: routes registered against \
+                a nested blueprint are prefixed with the path prefix of that blueprint."
+                    .into(),
+            )
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+
     fn invalid_response_type(
         e: MissingTraitImplementationError,
         output_type: &ResolvedType,
@@ -798,6 +1756,40 @@ impl ComponentDb {
         diagnostics.push(diagnostic.into());
     }
 
+    fn cannot_handle_response_error_implementation(
+        e: CallableResolutionError,
+        error_type: &ResolvedType,
+        fallible_id: UserComponentId,
+        user_component_db: &UserComponentDb,
+        package_graph: &PackageGraph,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let fallible_kind = user_component_db[fallible_id].callable_type();
+        let raw_identifier_id = user_component_db[fallible_id].raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled(format!("The fallible {fallible_kind} was registered here")));
+        let error = anyhow::Error::from(e).context(format!(
+            "Something went wrong when I tried to analyze the implementation of \
+                `pavex_runtime::response::ResponseError` for {error_type:?}, the error type \
+                returned by one of your {fallible_kind}s.\n\
+                This is definitely a bug, I am sorry! Please file an issue on \
+                https://github.com/LukeMathWalker/pavex"
+        ));
+        let diagnostic = CompilerDiagnostic::builder(source, error)
+            .optional_label(label)
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+
     fn invalid_error_handler(
         e: ErrorHandlerValidationError,
         user_component_id: UserComponentId,
@@ -928,7 +1920,138 @@ impl ComponentDb {
             );
         let diagnostic = CompilerDiagnostic::builder(source, error)
             .optional_label(label)
-            .help("Add an error handler via `.error_handler`".to_string())
+            .help(
+                "Add an error handler via `.error_handler`, register one for its error type \
+                against the blueprint via `bp.error_handler_for` or implement \
+                `pavex_runtime::response::ResponseError` for its error type"
+                    .to_string(),
+            )
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+
+    fn unknown_default_error_type(
+        error_type_name: &str,
+        user_component_id: UserComponentId,
+        user_component_db: &UserComponentDb,
+        package_graph: &PackageGraph,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let raw_identifier_id = user_component_db[user_component_id].raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled("The error handler was registered here".into()));
+        let error = anyhow::anyhow!(
+            "You registered an error handler for `{error_type_name}`, but I can't find that type \
+            in the documentation of its crate."
+        );
+        let diagnostic = CompilerDiagnostic::builder(source, error)
+            .optional_label(label)
+            .help(
+                "I look the error type up using the path returned by `std::any::type_name`, the \
+                path where the type is defined: it must be publicly importable from there. \
+                If it isn't, register the error handler via `.error_handler` on each fallible \
+                component instead."
+                    .into(),
+            )
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+
+    fn default_error_handler_without_error_input(
+        error_type: &ResolvedType,
+        user_component_id: UserComponentId,
+        user_component_db: &UserComponentDb,
+        package_graph: &PackageGraph,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let raw_identifier_id = user_component_db[user_component_id].raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled("The error handler was registered here".into()));
+        let error = anyhow::anyhow!(
+            "You registered an error handler for `{error_type:?}`, but it doesn't take \
+            `&{error_type:?}` as one of its input parameters.\n\
+            I need a reference to the error in order to build a response out of it!"
+        );
+        let diagnostic = CompilerDiagnostic::builder(source, error)
+            .optional_label(label)
+            .help(format!(
+                "Add `&{error_type:?}` to the input parameters of the error handler, or change \
+                the type you are registering it for."
+            ))
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+
+    fn conflicting_default_error_handlers(
+        error_type: &ResolvedType,
+        first_id: UserComponentId,
+        second_id: UserComponentId,
+        user_component_db: &UserComponentDb,
+        package_graph: &PackageGraph,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let raw_identifier_id = user_component_db[second_id].raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled("The conflicting error handler was registered here".into()));
+        let first_location = raw_identifiers_db
+            .get_location(user_component_db[first_id].raw_callable_identifiers_id());
+        let first_registration = match first_location.source_file(package_graph) {
+            Ok(first_source) => {
+                diagnostic::get_f_macro_invocation_span(&first_source, first_location).map(|s| {
+                    CompilerDiagnostic::builder(first_source, anyhow::anyhow!(""))
+                        .label(s.labeled(format!(
+                            "The first error handler for `{error_type:?}` was registered here"
+                        )))
+                        .build()
+                })
+            }
+            Err(e) => {
+                diagnostics.push(e.into());
+                None
+            }
+        };
+        let error = anyhow::anyhow!(
+            "I cannot register more than one error handler for `{error_type:?}` against the \
+            same blueprint.\n\
+            I wouldn't know which one to use when a fallible component returns \
+            `{error_type:?}`!"
+        );
+        let diagnostic = CompilerDiagnostic::builder(source, error)
+            .optional_label(label)
+            .optional_related_error(first_registration)
+            .help(
+                "Remove one of the two registrations. If you need a different error handler for \
+                some of your routes, register it against a nested blueprint or use \
+                `.error_handler` on the fallible component."
+                    .into(),
+            )
             .build();
         diagnostics.push(diagnostic.into());
     }
@@ -1,5 +1,6 @@
 use ahash::HashMap;
 use guppy::graph::PackageGraph;
+use indexmap::IndexSet;
 use miette::{miette, NamedSource};
 use rustdoc_types::ItemEnum;
 use syn::spanned::Spanned;
@@ -16,7 +17,7 @@ use crate::web::analyses::resolved_paths::ResolvedPathDb;
 use crate::web::analyses::user_components::{UserComponentDb, UserComponentId};
 use crate::web::computation::Computation;
 use crate::web::interner::Interner;
-use crate::web::resolvers::{resolve_callable, CallableResolutionError};
+use crate::web::resolvers::{resolve_callable, CallableResolutionError, CallableType};
 
 pub(crate) type ComputationId = la_arena::Idx<Computation<'static>>;
 
@@ -38,19 +39,41 @@ impl ComputationDb {
             interner: Interner::new(),
             component_id2callable_id: Default::default(),
         };
-        for (component_id, _) in component_db.iter() {
+        for (component_id, component) in component_db.iter() {
             let resolved_path = &resolved_path_db[component_id];
-            if let Err(e) =
-                self_.resolve_callable(krate_collection, resolved_path, Some(component_id))
-            {
-                Self::capture_diagnostics(
-                    e,
-                    component_id,
-                    component_db,
-                    raw_identifiers_db,
-                    package_graph,
-                    diagnostics,
-                );
+            match self_.resolve_callable(krate_collection, resolved_path, Some(component_id)) {
+                Ok(callable_id) => {
+                    // We can only infer the generic parameters of constructors, using the type
+                    // they are asked to build.
+                    if component.callable_type() == CallableType::Constructor {
+                        continue;
+                    }
+                    let Computation::Callable(callable) = &self_[callable_id] else {
+                        unreachable!()
+                    };
+                    let unassigned_parameters = callable.unassigned_generic_type_parameters();
+                    if !unassigned_parameters.is_empty() {
+                        Self::unassigned_generic_parameters(
+                            &unassigned_parameters,
+                            &callable.path,
+                            component_id,
+                            component_db,
+                            raw_identifiers_db,
+                            package_graph,
+                            diagnostics,
+                        );
+                    }
+                }
+                Err(e) => {
+                    Self::capture_diagnostics(
+                        e,
+                        component_id,
+                        component_db,
+                        raw_identifiers_db,
+                        package_graph,
+                        diagnostics,
+                    );
+                }
             }
         }
         self_
@@ -71,6 +94,15 @@ impl ComputationDb {
         Ok(callable_id)
     }
 
+    /// Replace the callable associated with a user component.
+    ///
+    /// It is used to wrap the invocation of singleton constructors that have a startup policy.
+    pub(crate) fn replace_callable(&mut self, component_id: UserComponentId, callable: Callable) {
+        let callable_id = self.interner.get_or_intern(callable.into());
+        self.component_id2callable_id
+            .insert(component_id, callable_id);
+    }
+
     pub(crate) fn get_or_intern(
         &mut self,
         computation: impl Into<Computation<'static>>,
@@ -78,6 +110,54 @@ impl ComputationDb {
         self.interner.get_or_intern(computation.into())
     }
 
+    fn unassigned_generic_parameters(
+        unassigned_parameters: &IndexSet<String>,
+        callable_path: &ResolvedPath,
+        component_id: UserComponentId,
+        component_db: &UserComponentDb,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        package_graph: &PackageGraph,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let component = &component_db[component_id];
+        let callable_type = component.callable_type();
+        let raw_identifier_id = component.raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(source) => source,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let parameters = unassigned_parameters
+            .iter()
+            .map(|p| format!("`{p}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let parameters = if unassigned_parameters.len() == 1 {
+            format!("generic parameter {parameters}")
+        } else {
+            format!("generic parameters {parameters}")
+        };
+        let error = anyhow::anyhow!(
+            "I cannot infer the concrete type of the {parameters} of `{callable_path}`.\n\
+            I can only infer the generic parameters of constructors, using the type they are \
+            asked to build."
+        );
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled(format!("The {callable_type} was registered here")));
+        let diagnostic = CompilerDiagnostic::builder(source, error)
+            .optional_label(label)
+            .help(
+                "Specify the concrete type of each generic parameter using the turbofish syntax \
+                (e.g. `f!(crate::my_function::<crate::MyType>)`)."
+                    .into(),
+            )
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+
     fn capture_diagnostics(
         e: CallableResolutionError,
         component_id: UserComponentId,
@@ -103,7 +183,11 @@ impl ComputationDb {
                     .map(|s| s.labeled(format!("The {callable_type} that we cannot resolve")));
                 let diagnostic = CompilerDiagnostic::builder(source, e.clone())
                     .optional_label(label)
-                    .help("This is most likely a bug in `pavex` or `rustdoc`.\nPlease file a GitHub issue!".into())
+                    .help(
+                        "Check that the path is spelled correctly and points at a function or a \
+                        method."
+                            .into(),
+                    )
                     .build();
                 diagnostics.push(diagnostic.into());
             }
@@ -277,6 +361,35 @@ impl ComputationDb {
             CallableResolutionError::CannotGetCrateData(_) => {
                 diagnostics.push(miette!(e.clone()));
             }
+            CallableResolutionError::GenericArgumentCountMismatch(_) => {
+                let label = diagnostic::get_f_macro_invocation_span(&source, location)
+                    .map(|s| s.labeled(format!("The {callable_type} was registered here")));
+                diagnostics.push(
+                    CompilerDiagnostic::builder(source, e.clone())
+                        .optional_label(label)
+                        .help(
+                            "Specify a concrete type for each generic parameter, in the order \
+                            they are declared."
+                                .into(),
+                        )
+                        .build()
+                        .into(),
+                );
+            }
+            CallableResolutionError::GenericArgumentResolutionError(ref inner_error) => {
+                let label = diagnostic::get_f_macro_invocation_span(&source, location)
+                    .map(|s| s.labeled(format!("The {callable_type} was registered here")));
+                diagnostics.push(
+                    CompilerDiagnostic::builder(source, e.clone())
+                        .optional_label(label)
+                        .help(format!(
+                            "Check that `{}` is spelled correctly and points at a type.",
+                            inner_error.generic_argument
+                        ))
+                        .build()
+                        .into(),
+                );
+            }
         }
     }
 }
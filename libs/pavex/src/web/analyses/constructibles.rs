@@ -15,93 +15,398 @@ use crate::rustdoc::CrateCollection;
 use crate::web::analyses::components::{ComponentDb, ComponentId, HydratedComponent};
 use crate::web::analyses::computations::ComputationDb;
 use crate::web::analyses::raw_identifiers::RawCallableIdentifiersDb;
+use crate::web::analyses::scopes::{ScopeGraph, ScopeId};
 use crate::web::analyses::user_components::{UserComponentDb, UserComponentId};
+use crate::web::computation::Computation;
+use crate::web::resolvers::{instantiate_callable_path, InstantiationError};
+use crate::web::startup_policies::{guard_constructor, StartupPolicyValidationError};
+use crate::web::utils::{get_ok_variant, is_result};
 
 #[derive(Debug)]
 pub(crate) struct ConstructibleDb {
-    type2constructor_id: HashMap<ResolvedType, ComponentId>,
+    scope_graph: ScopeGraph,
+    scope_id2constructibles: HashMap<ScopeId, HashMap<ResolvedType, ComponentId>>,
+    /// Generic constructors (e.g. `fn extract_json<T>(..) -> Json<T>`) cannot build anything
+    /// by themselves: they are templates that we instantiate, on demand, when one of the types
+    /// they can build is needed.
+    scope_id2templates: HashMap<ScopeId, Vec<ComponentId>>,
+    instance_id2template_id: HashMap<ComponentId, ComponentId>,
+}
+
+/// The analyses needed to instantiate a constructor template, on top of the component and
+/// computation databases it adds the instance to.
+struct TemplateContext<'a> {
+    package_graph: &'a PackageGraph,
+    krate_collection: &'a CrateCollection,
+    user_component_db: &'a UserComponentDb,
+    raw_identifiers_db: &'a RawCallableIdentifiersDb,
 }
 
 impl ConstructibleDb {
     pub(crate) fn build(
-        component_db: &ComponentDb,
-        computation_db: &ComputationDb,
+        component_db: &mut ComponentDb,
+        computation_db: &mut ComputationDb,
         package_graph: &PackageGraph,
         krate_collection: &CrateCollection,
         user_component_db: &UserComponentDb,
         raw_identifiers_db: &RawCallableIdentifiersDb,
         request_scoped_framework_types: &HashSet<&ResolvedType>,
+        scope_graph: &ScopeGraph,
         diagnostics: &mut Vec<miette::Error>,
     ) -> Self {
-        let mut type2constructor_id = HashMap::new();
-        for (component_id, component) in component_db.constructors(computation_db) {
-            let output = component.output_type();
-            type2constructor_id.insert(output.to_owned(), component_id);
-        }
-        let self_ = Self {
-            type2constructor_id,
+        let mut self_ = Self {
+            scope_graph: scope_graph.to_owned(),
+            scope_id2constructibles: Default::default(),
+            scope_id2templates: Default::default(),
+            instance_id2template_id: Default::default(),
         };
+        self_.register_constructors(
+            0,
+            component_db,
+            computation_db,
+            package_graph,
+            user_component_db,
+            raw_identifiers_db,
+            diagnostics,
+        );
 
-        for (component_id, _) in component_db.iter() {
-            let resolved_component = component_db.hydrated_component(component_id, computation_db);
-            // We don't support dependency injection for transformers (yet).
-            if let HydratedComponent::Transformer(_) = &resolved_component {
-                continue;
+        let template_context = TemplateContext {
+            package_graph,
+            krate_collection,
+            user_component_db,
+            raw_identifiers_db,
+        };
+        // Instantiating a template adds new components to the database: we keep going until
+        // we have checked all of them.
+        let mut n_checked_components = 0;
+        loop {
+            let component_ids: Vec<_> = component_db
+                .iter()
+                .skip(n_checked_components)
+                .map(|(id, _)| id)
+                .collect();
+            if component_ids.is_empty() {
+                break;
             }
+            n_checked_components += component_ids.len();
 
-            if let HydratedComponent::Constructor(_) = &resolved_component {
-                let lifecycle = component_db.lifecycle(component_id).unwrap();
-                if lifecycle == &Lifecycle::Singleton {
+            for component_id in component_ids {
+                let resolved_component =
+                    component_db.hydrated_component(component_id, computation_db);
+                // We don't support dependency injection for transformers (yet).
+                if let HydratedComponent::Transformer(_) = &resolved_component {
                     continue;
                 }
-            }
 
-            let input_types = {
-                let mut input_types: Vec<Option<ResolvedType>> = resolved_component
-                    .input_types()
-                    .iter()
-                    .map(|i| Some(i.to_owned()))
-                    .collect();
-                // Errors happen, they are not "constructed" (we use a transformer instead).
-                // Therefore we skip the error input type for error handlers.
-                if let HydratedComponent::ErrorHandler(e) = &resolved_component {
-                    input_types[e.error_input_index] = None;
+                if let HydratedComponent::Constructor(c) = &resolved_component {
+                    let lifecycle = component_db.lifecycle(component_id).unwrap();
+                    if lifecycle == &Lifecycle::Singleton {
+                        continue;
+                    }
+                    // We only check the dependencies of the instances of a template.
+                    if !c
+                        .output_type()
+                        .unassigned_generic_type_parameters()
+                        .is_empty()
+                    {
+                        continue;
+                    }
                 }
-                input_types
-            };
 
-            for (input_index, input) in input_types.into_iter().enumerate() {
-                let input = match input.as_ref() {
-                    Some(i) => i,
-                    None => {
-                        continue;
+                let input_types = {
+                    let mut input_types: Vec<Option<ResolvedType>> = resolved_component
+                        .input_types()
+                        .iter()
+                        .map(|i| Some(i.to_owned()))
+                        .collect();
+                    // Errors happen, they are not "constructed" (we use a transformer instead).
+                    // Therefore we skip the error input type for error handlers.
+                    if let HydratedComponent::ErrorHandler(e) = &resolved_component {
+                        input_types[e.error_input_index] = None;
+                    }
+                    // The same goes for error observers, which take the error as their first
+                    // input parameter.
+                    if let HydratedComponent::ErrorObserver(_) = &resolved_component {
+                        input_types[0] = None;
                     }
+                    input_types
                 };
-                if request_scoped_framework_types.contains(input) {
-                    continue;
+
+                for (input_index, input) in input_types.into_iter().enumerate() {
+                    let input = match input.as_ref() {
+                        Some(i) => i,
+                        None => {
+                            continue;
+                        }
+                    };
+                    if request_scoped_framework_types.contains(input) {
+                        continue;
+                    }
+                    let mut is_constructible = true;
+                    let scope_ids: Vec<_> = component_db.scope_ids(component_id).collect();
+                    for scope_id in scope_ids {
+                        if self_.get(scope_id, input).is_some() {
+                            continue;
+                        }
+                        match self_.instantiate_template(
+                            scope_id,
+                            input,
+                            component_db,
+                            computation_db,
+                            &template_context,
+                            diagnostics,
+                        ) {
+                            Ok(true) => {}
+                            Ok(false) => {
+                                is_constructible = false;
+                            }
+                            Err((template_id, e)) => {
+                                if let Some(user_component_id) =
+                                    component_db.user_component_id(template_id)
+                                {
+                                    Self::cannot_instantiate_template(
+                                        e,
+                                        input,
+                                        user_component_id,
+                                        user_component_db,
+                                        package_graph,
+                                        raw_identifiers_db,
+                                        diagnostics,
+                                    );
+                                }
+                                break;
+                            }
+                        }
+                    }
+                    if is_constructible {
+                        continue;
+                    }
+                    // Instances of a template are not user components: we report missing
+                    // dependencies against the template they were derived from.
+                    let user_component_id =
+                        component_db.user_component_id(component_id).or_else(|| {
+                            self_
+                                .instance_id2template_id
+                                .get(&component_id)
+                                .and_then(|id| component_db.user_component_id(*id))
+                        });
+                    if let Some(user_component_id) = user_component_id {
+                        ConstructibleDb::missing_constructor(
+                            user_component_id,
+                            user_component_db,
+                            input,
+                            input_index,
+                            package_graph,
+                            krate_collection,
+                            raw_identifiers_db,
+                            computation_db,
+                            diagnostics,
+                        )
+                    } else {
+                        unreachable!()
+                    }
                 }
-                if self_.get(input).is_some() {
-                    continue;
+            }
+        }
+
+        self_
+    }
+
+    /// Register the constructors in the component database, skipping the first
+    /// `n_skipped_constructors`.
+    ///
+    /// Generic constructors are registered as templates.
+    fn register_constructors(
+        &mut self,
+        n_skipped_constructors: usize,
+        component_db: &ComponentDb,
+        computation_db: &ComputationDb,
+        package_graph: &PackageGraph,
+        user_component_db: &UserComponentDb,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let application_state_scope_id = self.scope_graph.application_state_scope_id();
+        for (component_id, component) in component_db
+            .constructors(computation_db)
+            .skip(n_skipped_constructors)
+        {
+            let output = component.output_type();
+            if !output.unassigned_generic_type_parameters().is_empty() {
+                // The constructors derived from a template (e.g. `&Json<T>` from `Json<T>`)
+                // are re-derived from each of its instances.
+                if component_db.user_component_id(component_id).is_some() {
+                    for scope_id in component_db.scope_ids(component_id) {
+                        self.scope_id2templates
+                            .entry(scope_id)
+                            .or_default()
+                            .push(component_id);
+                    }
                 }
-                if let Some(user_component_id) = component_db.user_component_id(component_id) {
-                    ConstructibleDb::missing_constructor(
-                        user_component_id,
-                        user_component_db,
-                        input,
-                        input_index,
-                        package_graph,
-                        krate_collection,
-                        raw_identifiers_db,
-                        computation_db,
-                        diagnostics,
-                    )
-                } else {
-                    unreachable!()
+                continue;
+            }
+            for scope_id in component_db.scope_ids(component_id) {
+                self.scope_id2constructibles
+                    .entry(scope_id)
+                    .or_default()
+                    .insert(output.to_owned(), component_id);
+            }
+            // There is a single application state, shared by all routes: it must be able to
+            // build all the singletons, no matter which blueprint they were registered against.
+            if component_db.lifecycle(component_id) == Some(&Lifecycle::Singleton) {
+                let singletons = self
+                    .scope_id2constructibles
+                    .entry(application_state_scope_id)
+                    .or_default();
+                match singletons.get(output) {
+                    Some(&previous_id)
+                        if component_db.hydrated_component(previous_id, computation_db)
+                            != HydratedComponent::Constructor(component.clone()) =>
+                    {
+                        if let Some(user_component_id) =
+                            component_db.user_component_id(component_id)
+                        {
+                            Self::conflicting_singletons(
+                                user_component_id,
+                                user_component_db,
+                                output,
+                                package_graph,
+                                raw_identifiers_db,
+                                diagnostics,
+                            );
+                        }
+                    }
+                    Some(_) => {}
+                    None => {
+                        singletons.insert(output.to_owned(), component_id);
+                    }
                 }
             }
         }
+    }
 
-        self_
+    /// Try to build a constructor for `type_`, visible from `scope_id`, by instantiating
+    /// a template that is visible from `scope_id`.
+    ///
+    /// Templates registered against a blueprint take precedence over the ones registered
+    /// against the blueprints it is nested into. Within the same blueprint, the latest
+    /// registered template takes precedence.
+    ///
+    /// It returns `true` if a suitable template was found and instantiated.
+    fn instantiate_template(
+        &mut self,
+        scope_id: ScopeId,
+        type_: &ResolvedType,
+        component_db: &mut ComponentDb,
+        computation_db: &mut ComputationDb,
+        context: &TemplateContext,
+        diagnostics: &mut Vec<miette::Error>,
+    ) -> Result<bool, (ComponentId, Box<InstantiationError>)> {
+        let TemplateContext {
+            package_graph,
+            krate_collection,
+            user_component_db,
+            raw_identifiers_db,
+        } = *context;
+        // A constructor for `T` gives us a constructor for `&T` as well.
+        let type_ = match type_ {
+            ResolvedType::Reference(r) if !r.is_static => &r.inner,
+            t => t,
+        };
+        let template = self.scope_graph.ancestors(scope_id).find_map(|scope_id| {
+            self.scope_id2templates
+                .get(&scope_id)?
+                .iter()
+                .rev()
+                .find_map(|&template_id| {
+                    let HydratedComponent::Constructor(template) =
+                        component_db.hydrated_component(template_id, computation_db)
+                    else {
+                        unreachable!()
+                    };
+                    let output = template.output_type();
+                    let output = if is_result(output) {
+                        get_ok_variant(output)
+                    } else {
+                        output
+                    };
+                    let mut bindings = HashMap::new();
+                    output
+                        .is_a_template_for(type_, &mut bindings)
+                        .then_some((template_id, bindings))
+                })
+        });
+        let Some((template_id, bindings)) = template else {
+            return Ok(false);
+        };
+        let user_component_id = component_db.user_component_id(template_id).unwrap();
+        let template_path = computation_db[user_component_id].path.clone();
+        let instance_path = instantiate_callable_path(krate_collection, &template_path, &bindings)
+            .map_err(|e| (template_id, e))?;
+        let callable_id =
+            match computation_db.resolve_callable(krate_collection, &instance_path, None) {
+                Ok(callable_id) => callable_id,
+                Err(e) => {
+                    tracing::warn!(
+                        "Failed to resolve `{}`, an instance of `{}`: {:?}",
+                        instance_path,
+                        template_path,
+                        e
+                    );
+                    return Ok(false);
+                }
+            };
+        // Instances inherit the startup policy of their template.
+        let raw_identifiers_id = user_component_db[user_component_id].raw_callable_identifiers_id();
+        let callable_id = match raw_identifiers_db.get_startup_policy(raw_identifiers_id) {
+            Some(policy) => {
+                let Computation::Callable(instance) = &computation_db[callable_id] else {
+                    unreachable!()
+                };
+                let lifecycle = raw_identifiers_db
+                    .get_lifecycle(raw_identifiers_id)
+                    .unwrap();
+                let guarded =
+                    guard_constructor(instance, lifecycle, policy, package_graph, krate_collection);
+                match guarded {
+                    Ok(Some(guarded)) => computation_db.get_or_intern(guarded),
+                    Ok(None) => callable_id,
+                    // Whether the inputs can be cloned depends on the generic arguments:
+                    // it can only be checked for instances.
+                    Err(e @ StartupPolicyValidationError::CannotCloneInputForRetries { .. }) => {
+                        ComponentDb::invalid_startup_policy(
+                            e,
+                            user_component_id,
+                            user_component_db,
+                            package_graph,
+                            raw_identifiers_db,
+                            diagnostics,
+                        );
+                        callable_id
+                    }
+                    // Any other violation has already been reported for the template.
+                    Err(_) => callable_id,
+                }
+            }
+            None => callable_id,
+        };
+        let n_constructors = component_db.constructors(computation_db).count();
+        let instance_id = component_db
+            .get_or_intern_instantiated_constructor(template_id, callable_id, computation_db)
+            // It's fine to unwrap here: the template passed validation, and so do its instances.
+            .unwrap();
+        self.register_constructors(
+            n_constructors,
+            component_db,
+            computation_db,
+            package_graph,
+            user_component_db,
+            raw_identifiers_db,
+            diagnostics,
+        );
+        self.instance_id2template_id
+            .insert(instance_id, template_id);
+        Ok(true)
     }
 
     fn missing_constructor(
@@ -207,15 +512,99 @@ impl ConstructibleDb {
         diagnostics.push(diagnostic.into());
     }
 
-    pub(crate) fn get(&self, t: &ResolvedType) -> Option<ComponentId> {
-        self.type2constructor_id.get(t).cloned()
+    fn cannot_instantiate_template(
+        e: Box<InstantiationError>,
+        requested_type: &ResolvedType,
+        template_user_component_id: UserComponentId,
+        user_component_db: &UserComponentDb,
+        package_graph: &PackageGraph,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let raw_identifier_id =
+            user_component_db[template_user_component_id].raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled("The generic constructor was registered here".into()));
+        let help = match &*e {
+            InstantiationError::UnsatisfiedTraitBound(e) => Some(format!(
+                "Implement the `{:?}` trait for `{:?}` or register a constructor for \
+                `{requested_type:?}`.",
+                e.trait_, e.type_
+            )),
+            InstantiationError::UnassignedGenericParameters(_) => Some(
+                "Specify the concrete type of each generic parameter using the turbofish syntax \
+                (e.g. `f!(crate::my_function::<crate::MyType>)`)."
+                    .into(),
+            ),
+            InstantiationError::GenericArgumentResolutionError(_)
+            | InstantiationError::CannotResolveTemplate { .. } => None,
+        };
+        let template_path = e.template_path();
+        let e = anyhow::anyhow!(
+            "I cannot use the generic constructor `{template_path}` to build an instance of \
+            `{requested_type:?}`.\n{e}"
+        );
+        let diagnostic = CompilerDiagnostic::builder(source, e)
+            .optional_label(label)
+            .optional_help(help)
+            .build();
+        diagnostics.push(diagnostic.into());
     }
-}
 
-impl std::ops::Index<&ResolvedType> for ConstructibleDb {
-    type Output = ComponentId;
+    fn conflicting_singletons(
+        user_component_id: UserComponentId,
+        user_component_db: &UserComponentDb,
+        singleton_type: &ResolvedType,
+        package_graph: &PackageGraph,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let raw_identifier_id = user_component_db[user_component_id].raw_callable_identifiers_id();
+        let location = raw_identifiers_db.get_location(raw_identifier_id);
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled("The conflicting constructor was registered here".into()));
+        let e = anyhow::anyhow!(
+            "There can only be one constructor for each singleton type, but you registered \
+            different singleton constructors for `{singleton_type:?}` against different \
+            blueprints."
+        );
+        let diagnostic = CompilerDiagnostic::builder(source, e)
+            .optional_label(label)
+            .help(
+                "Singletons are shared by all the routes of your application. \
+                Register the constructor once, against a blueprint that all the routes that \
+                need it are nested into."
+                    .into(),
+            )
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
 
-    fn index(&self, index: &ResolvedType) -> &Self::Output {
-        &self.type2constructor_id[index]
+    /// Find the constructor for `t` that is visible from `scope_id`, if there is one.
+    ///
+    /// Constructors registered against a blueprint take precedence over the ones registered
+    /// against the blueprints it is nested into.
+    pub(crate) fn get(&self, scope_id: ScopeId, t: &ResolvedType) -> Option<ComponentId> {
+        self.scope_graph.ancestors(scope_id).find_map(|scope_id| {
+            self.scope_id2constructibles
+                .get(&scope_id)
+                .and_then(|constructibles| constructibles.get(t))
+                .copied()
+        })
     }
 }
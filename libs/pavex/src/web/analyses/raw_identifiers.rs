@@ -1,54 +1,119 @@
 use ahash::{HashMap, HashMapExt};
 
-use pavex_builder::{AppBlueprint, Lifecycle, Location, RawCallableIdentifiers};
+use pavex_builder::{Lifecycle, Location, RawCallableIdentifiers, StartupPolicy};
 
+use crate::web::analyses::scopes::{ScopeId, ScopedBlueprint};
 use crate::web::interner::Interner;
 
-pub(crate) type RawCallableIdentifierId = la_arena::Idx<RawCallableIdentifiers>;
+pub(crate) type RawCallableIdentifierId = la_arena::Idx<ScopedRawCallableIdentifiers>;
+
+/// The same callable can be registered against multiple blueprints, with a different
+/// location (and, possibly, a different lifecycle) for each registration.
+/// We keep track of the scope of the blueprint it was registered against to tell them apart.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ScopedRawCallableIdentifiers {
+    identifiers: RawCallableIdentifiers,
+    scope_id: ScopeId,
+}
 
 pub(crate) struct RawCallableIdentifiersDb {
-    interner: Interner<RawCallableIdentifiers>,
+    interner: Interner<ScopedRawCallableIdentifiers>,
     id2locations: HashMap<RawCallableIdentifierId, Location>,
     id2lifecycle: HashMap<RawCallableIdentifierId, Lifecycle>,
+    id2startup_policy: HashMap<RawCallableIdentifierId, StartupPolicy>,
 }
 
 impl RawCallableIdentifiersDb {
-    pub fn build(bp: &AppBlueprint) -> Self {
+    pub fn build(scoped_blueprints: &[ScopedBlueprint]) -> Self {
         let mut interner = Interner::new();
         let mut id2locations = HashMap::new();
         let mut id2lifecycle = HashMap::new();
-
-        for (route, request_handler) in &bp.router {
-            let location = &bp.request_handler_locations[route];
-            let id = interner.get_or_intern(request_handler.to_owned());
-            id2locations.insert(id, location.to_owned());
-            id2lifecycle.insert(id, Lifecycle::RequestScoped);
-        }
-
-        for (route, error_handler) in &bp.request_handlers_error_handlers {
-            let location = &bp.request_error_handler_locations[route];
-            let error_handler_id = interner.get_or_intern(error_handler.to_owned());
-            id2locations.insert(error_handler_id, location.to_owned());
-        }
-
-        for (fallible_constructor, error_handler) in &bp.constructors_error_handlers {
-            let location = &bp.error_handler_locations[fallible_constructor];
-            let error_handler_id = interner.get_or_intern(error_handler.to_owned());
-            id2locations.insert(error_handler_id, location.to_owned());
-        }
-
-        for constructor in &bp.constructors {
-            let location = &bp.constructor_locations[constructor];
-            let lifecycle = &bp.component_lifecycles[constructor];
-            let id = interner.get_or_intern(constructor.to_owned());
-            id2locations.insert(id, location.to_owned());
-            id2lifecycle.insert(id, lifecycle.to_owned());
+        let mut id2startup_policy = HashMap::new();
+
+        for ScopedBlueprint {
+            scope_id,
+            blueprint: bp,
+            ..
+        } in scoped_blueprints
+        {
+            let mut intern = |identifiers: &RawCallableIdentifiers| {
+                interner.get_or_intern(ScopedRawCallableIdentifiers {
+                    identifiers: identifiers.to_owned(),
+                    scope_id: *scope_id,
+                })
+            };
+
+            for (router_key, request_handler) in &bp.router {
+                let location = &bp.request_handler_locations[router_key];
+                let id = intern(request_handler);
+                id2locations.insert(id, location.to_owned());
+                id2lifecycle.insert(id, Lifecycle::RequestScoped);
+            }
+
+            if let Some(fallback) = &bp.fallback_request_handler {
+                let location = bp.fallback_request_handler_location.as_ref().unwrap();
+                let id = intern(fallback);
+                id2locations.insert(id, location.to_owned());
+                id2lifecycle.insert(id, Lifecycle::RequestScoped);
+            }
+
+            if let Some(shutdown_hook) = &bp.shutdown_hook {
+                let location = bp.shutdown_hook_location.as_ref().unwrap();
+                let id = intern(shutdown_hook);
+                id2locations.insert(id, location.to_owned());
+            }
+
+            for (router_key, error_handler) in &bp.request_handlers_error_handlers {
+                let location = &bp.request_error_handler_locations[router_key];
+                let error_handler_id = intern(error_handler);
+                id2locations.insert(error_handler_id, location.to_owned());
+            }
+
+            if let Some(error_handler) = &bp.fallback_error_handler {
+                let location = bp.fallback_error_handler_location.as_ref().unwrap();
+                let error_handler_id = intern(error_handler);
+                id2locations.insert(error_handler_id, location.to_owned());
+            }
+
+            for (fallible_constructor, error_handler) in &bp.constructors_error_handlers {
+                let location = &bp.error_handler_locations[fallible_constructor];
+                let error_handler_id = intern(error_handler);
+                id2locations.insert(error_handler_id, location.to_owned());
+            }
+
+            for default_error_handler in &bp.default_error_handlers {
+                let error_handler_id = intern(&default_error_handler.error_handler);
+                id2locations.insert(error_handler_id, default_error_handler.location.to_owned());
+            }
+
+            for (error_observer, location) in &bp.error_observer_locations {
+                let id = intern(error_observer);
+                id2locations.insert(id, location.to_owned());
+            }
+
+            for (middleware, location) in &bp.middleware_locations {
+                let id = intern(middleware);
+                id2locations.insert(id, location.to_owned());
+                id2lifecycle.insert(id, Lifecycle::RequestScoped);
+            }
+
+            for constructor in &bp.constructors {
+                let location = &bp.constructor_locations[constructor];
+                let lifecycle = &bp.component_lifecycles[constructor];
+                let id = intern(constructor);
+                id2locations.insert(id, location.to_owned());
+                id2lifecycle.insert(id, lifecycle.to_owned());
+                if let Some(policy) = bp.constructors_startup_policies.get(constructor) {
+                    id2startup_policy.insert(id, policy.to_owned());
+                }
+            }
         }
 
         Self {
             interner,
             id2locations,
             id2lifecycle,
+            id2startup_policy,
         }
     }
 
@@ -56,23 +121,32 @@ impl RawCallableIdentifiersDb {
         self.id2lifecycle.get(&id)
     }
 
+    pub fn get_startup_policy(&self, id: RawCallableIdentifierId) -> Option<&StartupPolicy> {
+        self.id2startup_policy.get(&id)
+    }
+
     pub fn get_location(&self, id: RawCallableIdentifierId) -> &Location {
         &self.id2locations[&id]
     }
+
+    /// Retrieve the id of a callable registered against the blueprint associated with
+    /// `scope_id`.
+    pub fn get_id(
+        &self,
+        identifiers: &RawCallableIdentifiers,
+        scope_id: ScopeId,
+    ) -> RawCallableIdentifierId {
+        self.interner[&ScopedRawCallableIdentifiers {
+            identifiers: identifiers.to_owned(),
+            scope_id,
+        }]
+    }
 }
 
 impl std::ops::Index<RawCallableIdentifierId> for RawCallableIdentifiersDb {
     type Output = RawCallableIdentifiers;
 
     fn index(&self, index: RawCallableIdentifierId) -> &Self::Output {
-        &self.interner[index]
-    }
-}
-
-impl std::ops::Index<&RawCallableIdentifiers> for RawCallableIdentifiersDb {
-    type Output = RawCallableIdentifierId;
-
-    fn index(&self, index: &RawCallableIdentifiers) -> &Self::Output {
-        &self.interner[index]
+        &self.interner[index].identifiers
     }
 }
@@ -1,5 +1,7 @@
 use ahash::{HashMap, HashMapExt};
 use guppy::graph::PackageGraph;
+use guppy::PackageId;
+use indexmap::IndexSet;
 
 use crate::diagnostic;
 use crate::diagnostic::{CompilerDiagnostic, LocationExt, OptionalSourceSpanExt};
@@ -49,6 +51,15 @@ impl ResolvedPathDb {
         }
     }
 
+    /// The ids of all the packages referenced by the resolved paths in this database.
+    pub fn package_ids(&self) -> IndexSet<PackageId> {
+        let mut package_ids = IndexSet::new();
+        for (_, path) in self.interner.iter() {
+            path.collect_package_ids(&mut package_ids);
+        }
+        package_ids
+    }
+
     fn capture_diagnostics(
         e: ParseError,
         component_id: UserComponentId,
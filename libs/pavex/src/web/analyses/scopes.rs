@@ -0,0 +1,178 @@
+use guppy::graph::PackageGraph;
+
+use pavex_builder::{AppBlueprint, Location};
+
+use crate::diagnostic;
+use crate::diagnostic::{CompilerDiagnostic, LocationExt, OptionalSourceSpanExt};
+
+/// Each blueprint gets its own scope: the root blueprint is associated with the root scope,
+/// while blueprints registered via `AppBlueprint::nest` are associated with a child scope of
+/// the blueprint they were nested into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub(crate) struct ScopeId(usize);
+
+/// The tree of scopes for an application, mirroring the tree of nested blueprints.
+///
+/// On top of the scopes associated with blueprints, there is a detached scope for the
+/// application state: it can see the singletons registered against any blueprint.
+#[derive(Debug, Clone)]
+pub(crate) struct ScopeGraph {
+    /// The parent of each scope, indexed by [`ScopeId`].
+    /// It is `None` for the root scope and for the application state scope.
+    parents: Vec<Option<ScopeId>>,
+}
+
+/// A blueprint alongside the scope it is associated with.
+pub(crate) struct ScopedBlueprint<'a> {
+    pub(crate) scope_id: ScopeId,
+    /// `None` for the root blueprint.
+    pub(crate) parent_scope_id: Option<ScopeId>,
+    pub(crate) blueprint: &'a AppBlueprint,
+    /// The prefix to prepend to the path of all the routes registered against `blueprint`,
+    /// including the prefixes of all its ancestors.
+    pub(crate) path_prefix: String,
+}
+
+impl ScopedBlueprint<'_> {
+    /// Prepend the path prefix of this blueprint to `path`.
+    pub(crate) fn full_path(&self, path: &str) -> String {
+        format!("{}{path}", self.path_prefix)
+    }
+}
+
+impl ScopeGraph {
+    /// Walk the tree of blueprints, assigning a scope to each of them.
+    ///
+    /// Blueprints are returned in pre-order: a blueprint always comes before the blueprints
+    /// nested into it.
+    pub fn build<'a>(
+        bp: &'a AppBlueprint,
+        package_graph: &PackageGraph,
+        diagnostics: &mut Vec<miette::Error>,
+    ) -> (Self, Vec<ScopedBlueprint<'a>>) {
+        let mut parents = vec![None];
+        let mut scoped_blueprints = vec![];
+        let mut to_be_visited = vec![ScopedBlueprint {
+            scope_id: ScopeId(0),
+            parent_scope_id: None,
+            blueprint: bp,
+            path_prefix: String::new(),
+        }];
+        while let Some(scoped_blueprint) = to_be_visited.pop() {
+            if scoped_blueprint.parent_scope_id.is_some() {
+                let bp = scoped_blueprint.blueprint;
+                for (component_kind, location) in [
+                    (
+                        "fallback request handler",
+                        &bp.fallback_request_handler_location,
+                    ),
+                    ("shutdown hook", &bp.shutdown_hook_location),
+                ] {
+                    if let Some(location) = location {
+                        nested_top_level_component(
+                            component_kind,
+                            location,
+                            package_graph,
+                            diagnostics,
+                        );
+                    }
+                }
+            }
+            // Reversed, so that nested blueprints are visited in registration order.
+            for nested in scoped_blueprint.blueprint.nested_blueprints.iter().rev() {
+                let prefix = &nested.path_prefix;
+                if !prefix.starts_with('/') || prefix.ends_with('/') {
+                    invalid_path_prefix(
+                        prefix,
+                        &nested.nesting_location,
+                        package_graph,
+                        diagnostics,
+                    );
+                }
+                let scope_id = ScopeId(parents.len());
+                parents.push(Some(scoped_blueprint.scope_id));
+                to_be_visited.push(ScopedBlueprint {
+                    scope_id,
+                    parent_scope_id: Some(scoped_blueprint.scope_id),
+                    blueprint: &nested.blueprint,
+                    path_prefix: scoped_blueprint.full_path(prefix),
+                });
+            }
+            scoped_blueprints.push(scoped_blueprint);
+        }
+        // The detached scope for the application state.
+        parents.push(None);
+        (Self { parents }, scoped_blueprints)
+    }
+
+    /// The scope associated with the root blueprint.
+    pub fn root_scope_id(&self) -> ScopeId {
+        ScopeId(0)
+    }
+
+    /// The scope used to build the application state.
+    /// It is not part of the tree of blueprint scopes.
+    pub fn application_state_scope_id(&self) -> ScopeId {
+        ScopeId(self.parents.len() - 1)
+    }
+
+    /// Iterate over `scope_id` and its ancestors, from the innermost to the outermost.
+    pub fn ancestors(&self, scope_id: ScopeId) -> impl Iterator<Item = ScopeId> + '_ {
+        std::iter::successors(Some(scope_id), |id| self.parents[id.0])
+    }
+}
+
+fn invalid_path_prefix(
+    prefix: &str,
+    location: &Location,
+    package_graph: &PackageGraph,
+    diagnostics: &mut Vec<miette::Error>,
+) {
+    let source = match location.source_file(package_graph) {
+        Ok(s) => s,
+        Err(e) => {
+            diagnostics.push(e.into());
+            return;
+        }
+    };
+    let label = diagnostic::get_f_macro_invocation_span(&source, location)
+        .labeled("The path prefix was registered here".into());
+    let error = anyhow::anyhow!(
+        "The path prefix for a nested blueprint must start with a `/` and it must not end \
+        with a `/`.\n`{prefix}` doesn't."
+    );
+    let diagnostic = CompilerDiagnostic::builder(source, error)
+        .optional_label(label)
+        .build();
+    diagnostics.push(diagnostic.into());
+}
+
+/// Some components (e.g. the fallback request handler) are application-wide: they can only be
+/// registered against the top-level blueprint.
+fn nested_top_level_component(
+    component_kind: &str,
+    location: &Location,
+    package_graph: &PackageGraph,
+    diagnostics: &mut Vec<miette::Error>,
+) {
+    let source = match location.source_file(package_graph) {
+        Ok(s) => s,
+        Err(e) => {
+            diagnostics.push(e.into());
+            return;
+        }
+    };
+    let label = diagnostic::get_f_macro_invocation_span(&source, location)
+        .labeled(format!("The {component_kind} was registered here"));
+    let error = anyhow::anyhow!(
+        "A {component_kind} can only be registered against the top-level blueprint, \
+        but this one was registered against a nested blueprint."
+    );
+    let diagnostic = CompilerDiagnostic::builder(source, error)
+        .optional_label(label)
+        .help(format!(
+            "Register the {component_kind} against the blueprint that you pass to `pavex_cli`."
+        ))
+        .build();
+    diagnostics.push(diagnostic.into());
+}
@@ -1,21 +1,58 @@
-use pavex_builder::AppBlueprint;
+use ahash::{HashMap, HashMapExt};
+
+use pavex_builder::router::RouterKey;
 
 use crate::web::analyses::raw_identifiers::{RawCallableIdentifierId, RawCallableIdentifiersDb};
+use crate::web::analyses::scopes::{ScopeId, ScopedBlueprint};
 use crate::web::interner::Interner;
 use crate::web::resolvers::CallableType;
 
+/// A component registered by the user against one of the blueprints of the application.
+///
+/// Each component is tagged with the scope of the blueprint it was registered against.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub(crate) enum UserComponent {
     RequestHandler {
         raw_callable_identifiers_id: RawCallableIdentifierId,
-        route: String,
+        /// `None` for the fallback request handler, invoked when the path of an incoming
+        /// request does not match any of the registered routes.
+        ///
+        /// The path includes the prefixes of all the blueprints the request handler was
+        /// nested into.
+        router_key: Option<RouterKey>,
+        scope_id: ScopeId,
     },
     ErrorHandler {
         raw_callable_identifiers_id: RawCallableIdentifierId,
         fallible_callable_identifiers_id: UserComponentId,
+        scope_id: ScopeId,
+    },
+    /// An error handler registered via `AppBlueprint::error_handler_for`: it is used for all the
+    /// fallible components, in its scope or in any of its descendants, whose error type matches
+    /// `error_type` and that don't have a dedicated error handler.
+    DefaultErrorHandler {
+        /// The name of the error type, as returned by `std::any::type_name`.
+        error_type: String,
+        raw_callable_identifiers_id: RawCallableIdentifierId,
+        scope_id: ScopeId,
+    },
+    /// An error observer: it is invoked for all the fallible components, in its scope or in any
+    /// of its descendants, whose error type matches the type of its first input parameter.
+    ErrorObserver {
+        raw_callable_identifiers_id: RawCallableIdentifierId,
+        scope_id: ScopeId,
     },
     Constructor {
         raw_callable_identifiers_id: RawCallableIdentifierId,
+        scope_id: ScopeId,
+    },
+    Middleware {
+        raw_callable_identifiers_id: RawCallableIdentifierId,
+        scope_id: ScopeId,
+    },
+    ShutdownHook {
+        raw_callable_identifiers_id: RawCallableIdentifierId,
+        scope_id: ScopeId,
     },
 }
 
@@ -23,72 +60,203 @@ impl UserComponent {
     pub fn callable_type(&self) -> CallableType {
         match self {
             UserComponent::RequestHandler { .. } => CallableType::RequestHandler,
-            UserComponent::ErrorHandler { .. } => CallableType::ErrorHandler,
+            UserComponent::ErrorHandler { .. } | UserComponent::DefaultErrorHandler { .. } => {
+                CallableType::ErrorHandler
+            }
+            UserComponent::ErrorObserver { .. } => CallableType::ErrorObserver,
             UserComponent::Constructor { .. } => CallableType::Constructor,
+            UserComponent::Middleware { .. } => CallableType::Middleware,
+            UserComponent::ShutdownHook { .. } => CallableType::ShutdownHook,
         }
     }
+
     pub fn raw_callable_identifiers_id(&self) -> RawCallableIdentifierId {
         match self {
             UserComponent::RequestHandler {
                 raw_callable_identifiers_id,
                 ..
-            } => *raw_callable_identifiers_id,
-            UserComponent::ErrorHandler {
+            }
+            | UserComponent::ErrorHandler {
                 raw_callable_identifiers_id,
                 ..
-            } => *raw_callable_identifiers_id,
-            UserComponent::Constructor {
+            }
+            | UserComponent::DefaultErrorHandler {
+                raw_callable_identifiers_id,
+                ..
+            }
+            | UserComponent::ErrorObserver {
+                raw_callable_identifiers_id,
+                ..
+            }
+            | UserComponent::Constructor {
+                raw_callable_identifiers_id,
+                ..
+            }
+            | UserComponent::Middleware {
+                raw_callable_identifiers_id,
+                ..
+            }
+            | UserComponent::ShutdownHook {
                 raw_callable_identifiers_id,
+                ..
             } => *raw_callable_identifiers_id,
         }
     }
+
+    /// The scope of the blueprint that this component was registered against.
+    pub fn scope_id(&self) -> ScopeId {
+        match self {
+            UserComponent::RequestHandler { scope_id, .. }
+            | UserComponent::ErrorHandler { scope_id, .. }
+            | UserComponent::DefaultErrorHandler { scope_id, .. }
+            | UserComponent::ErrorObserver { scope_id, .. }
+            | UserComponent::Constructor { scope_id, .. }
+            | UserComponent::Middleware { scope_id, .. }
+            | UserComponent::ShutdownHook { scope_id, .. } => *scope_id,
+        }
+    }
 }
 
 pub(crate) type UserComponentId = la_arena::Idx<UserComponent>;
 
 pub(crate) struct UserComponentDb {
     interner: Interner<UserComponent>,
+    /// - Keys: the id of a request handler.
+    /// - Values: the ids of the middlewares that wrap it, from the outermost to the innermost.
+    handler_id2middleware_ids: HashMap<UserComponentId, Vec<UserComponentId>>,
 }
 
 impl UserComponentDb {
     pub fn build(
-        bp: &AppBlueprint,
+        scoped_blueprints: &[ScopedBlueprint],
         raw_callable_identifiers_db: &RawCallableIdentifiersDb,
     ) -> Self {
         let mut interner = Interner::new();
-        for (route, request_handler) in &bp.router {
-            let raw_callable_identifiers_id = raw_callable_identifiers_db[request_handler];
-            let component = UserComponent::RequestHandler {
-                raw_callable_identifiers_id,
-                route: route.to_owned(),
+        let mut handler_id2middleware_ids = HashMap::new();
+        // The middlewares that wrap all the request handlers registered against the blueprint
+        // associated with a scope, including the ones inherited from its ancestors.
+        let mut scope_id2middleware_ids = HashMap::<ScopeId, Vec<UserComponentId>>::new();
+
+        for scoped_blueprint in scoped_blueprints {
+            let ScopedBlueprint {
+                scope_id,
+                blueprint: bp,
+                ..
+            } = scoped_blueprint;
+            let scope_id = *scope_id;
+            let raw_id = |identifiers| raw_callable_identifiers_db.get_id(identifiers, scope_id);
+            let intern_middleware = |interner: &mut Interner<UserComponent>, middleware| {
+                interner.get_or_intern(UserComponent::Middleware {
+                    raw_callable_identifiers_id: raw_id(middleware),
+                    scope_id,
+                })
             };
-            let request_handler_id = interner.get_or_intern(component);
-            if let Some(error_handler) = bp.request_handlers_error_handlers.get(route) {
-                let raw_callable_identifiers_id = raw_callable_identifiers_db[error_handler];
-                let component = UserComponent::ErrorHandler {
-                    raw_callable_identifiers_id,
-                    fallible_callable_identifiers_id: request_handler_id,
+            // Scopes are visited in pre-order, therefore the parent scope has already been
+            // processed.
+            let mut scope_middleware_ids = scoped_blueprint
+                .parent_scope_id
+                .map(|parent_scope_id| scope_id2middleware_ids[&parent_scope_id].clone())
+                .unwrap_or_default();
+            for middleware in &bp.middlewares {
+                scope_middleware_ids.push(intern_middleware(&mut interner, middleware));
+            }
+
+            for (router_key, request_handler) in &bp.router {
+                let component = UserComponent::RequestHandler {
+                    raw_callable_identifiers_id: raw_id(request_handler),
+                    router_key: Some(RouterKey {
+                        path: scoped_blueprint.full_path(&router_key.path),
+                        method_guard: router_key.method_guard,
+                    }),
+                    scope_id,
                 };
-                interner.get_or_intern(component);
+                let request_handler_id = interner.get_or_intern(component);
+                let mut middleware_ids = scope_middleware_ids.clone();
+                for middleware in bp.route_middlewares.get(router_key).into_iter().flatten() {
+                    middleware_ids.push(intern_middleware(&mut interner, middleware));
+                }
+                handler_id2middleware_ids.insert(request_handler_id, middleware_ids);
+                if let Some(error_handler) = bp.request_handlers_error_handlers.get(router_key) {
+                    let component = UserComponent::ErrorHandler {
+                        raw_callable_identifiers_id: raw_id(error_handler),
+                        fallible_callable_identifiers_id: request_handler_id,
+                        scope_id,
+                    };
+                    interner.get_or_intern(component);
+                }
             }
-        }
 
-        for constructor in &bp.constructors {
-            let raw_callable_identifiers_id = raw_callable_identifiers_db[constructor];
-            let component = UserComponent::Constructor {
-                raw_callable_identifiers_id,
-            };
-            let constructor_id = interner.get_or_intern(component);
-            if let Some(error_handler) = bp.constructors_error_handlers.get(constructor) {
-                let raw_callable_identifiers_id = raw_callable_identifiers_db[error_handler];
-                let component = UserComponent::ErrorHandler {
-                    raw_callable_identifiers_id,
-                    fallible_callable_identifiers_id: constructor_id,
+            if let Some(fallback) = &bp.fallback_request_handler {
+                let component = UserComponent::RequestHandler {
+                    raw_callable_identifiers_id: raw_id(fallback),
+                    router_key: None,
+                    scope_id,
                 };
-                interner.get_or_intern(component);
+                let fallback_id = interner.get_or_intern(component);
+                handler_id2middleware_ids.insert(fallback_id, scope_middleware_ids.clone());
+                if let Some(error_handler) = &bp.fallback_error_handler {
+                    let component = UserComponent::ErrorHandler {
+                        raw_callable_identifiers_id: raw_id(error_handler),
+                        fallible_callable_identifiers_id: fallback_id,
+                        scope_id,
+                    };
+                    interner.get_or_intern(component);
+                }
             }
+
+            if let Some(shutdown_hook) = &bp.shutdown_hook {
+                interner.get_or_intern(UserComponent::ShutdownHook {
+                    raw_callable_identifiers_id: raw_id(shutdown_hook),
+                    scope_id,
+                });
+            }
+
+            for constructor in &bp.constructors {
+                let component = UserComponent::Constructor {
+                    raw_callable_identifiers_id: raw_id(constructor),
+                    scope_id,
+                };
+                let constructor_id = interner.get_or_intern(component);
+                if let Some(error_handler) = bp.constructors_error_handlers.get(constructor) {
+                    let component = UserComponent::ErrorHandler {
+                        raw_callable_identifiers_id: raw_id(error_handler),
+                        fallible_callable_identifiers_id: constructor_id,
+                        scope_id,
+                    };
+                    interner.get_or_intern(component);
+                }
+            }
+
+            for default_error_handler in &bp.default_error_handlers {
+                interner.get_or_intern(UserComponent::DefaultErrorHandler {
+                    error_type: default_error_handler.error_type.clone(),
+                    raw_callable_identifiers_id: raw_id(&default_error_handler.error_handler),
+                    scope_id,
+                });
+            }
+
+            for error_observer in &bp.error_observers {
+                interner.get_or_intern(UserComponent::ErrorObserver {
+                    raw_callable_identifiers_id: raw_id(error_observer),
+                    scope_id,
+                });
+            }
+
+            scope_id2middleware_ids.insert(scope_id, scope_middleware_ids);
         }
-        Self { interner }
+        Self {
+            interner,
+            handler_id2middleware_ids,
+        }
+    }
+
+    /// The ids of the middlewares that wrap a request handler, from the outermost to the
+    /// innermost.
+    pub fn middleware_ids(&self, request_handler_id: UserComponentId) -> &[UserComponentId] {
+        self.handler_id2middleware_ids
+            .get(&request_handler_id)
+            .map(|ids| ids.as_slice())
+            .unwrap_or_default()
     }
 
     pub fn iter(
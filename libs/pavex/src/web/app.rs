@@ -12,32 +12,42 @@ use miette::miette;
 use proc_macro2::Ident;
 use quote::format_ident;
 
+use pavex_builder::router::RouterKey;
 use pavex_builder::{AppBlueprint, Lifecycle};
 
 use crate::diagnostic;
 use crate::diagnostic::{CompilerDiagnostic, LocationExt, SourceSpanExt};
 use crate::language::ResolvedType;
-use crate::rustdoc::{CrateCollection, TOOLCHAIN_CRATES};
+use crate::rustdoc::{CrateCollection, RustdocConfig, TOOLCHAIN_CRATES};
 use crate::web::analyses::call_graph::{
-    application_state_call_graph, handler_call_graph, ApplicationStateCallGraph, CallGraph,
+    application_state_call_graph, handler_call_graph, pipeline_call_graph, server_state_type,
+    ApplicationStateCallGraph, CallGraph, RequestProcessingCallGraphs,
+};
+use crate::web::analyses::components::{
+    Component, ComponentDb, ComponentDbInputs, ComponentId, HydratedComponent,
 };
-use crate::web::analyses::components::ComponentDb;
 use crate::web::analyses::computations::ComputationDb;
 use crate::web::analyses::constructibles::ConstructibleDb;
 use crate::web::analyses::raw_identifiers::RawCallableIdentifiersDb;
 use crate::web::analyses::resolved_paths::ResolvedPathDb;
-use crate::web::analyses::user_components::UserComponentDb;
+use crate::web::analyses::scopes::ScopeGraph;
+use crate::web::analyses::user_components::{UserComponentDb, UserComponentId};
 use crate::web::codegen;
+use crate::web::computation::Computation;
+use crate::web::constructors::Constructor;
 use crate::web::generated_app::GeneratedApp;
 use crate::web::resolvers::CallableResolutionError;
-use crate::web::traits::{assert_trait_is_implemented, MissingTraitImplementationError};
+use crate::web::shutdown_hooks::ShutdownHook;
+use crate::web::traits::{
+    assert_trait_is_implemented, implements_trait, MissingTraitImplementationError,
+};
 use crate::web::utils::process_framework_path;
 
 pub(crate) const GENERATED_APP_PACKAGE_ID: &str = "crate";
 
 pub struct App {
     package_graph: PackageGraph,
-    handler_call_graphs: IndexMap<String, CallGraph>,
+    request_call_graphs: RequestProcessingCallGraphs,
     application_state_call_graph: ApplicationStateCallGraph,
     runtime_singleton_bindings: BiHashMap<Ident, ResolvedType>,
     request_scoped_framework_bindings: BiHashMap<Ident, ResolvedType>,
@@ -46,8 +56,9 @@ pub struct App {
     computation_db: ComputationDb,
 }
 
+/// Retrieve the package graph for the current `cargo` workspace.
 #[tracing::instrument]
-fn compute_package_graph() -> Result<PackageGraph, miette::Error> {
+pub fn compute_package_graph() -> Result<PackageGraph, miette::Error> {
     // `cargo metadata` seems to be the only reliable way of retrieving the path to
     // the root manifest of the current workspace for a Rust project.
     guppy::MetadataCommand::new()
@@ -69,11 +80,31 @@ macro_rules! exit_on_errors {
 impl App {
     #[tracing::instrument(skip_all)]
     pub fn build(bp: AppBlueprint) -> Result<Self, Vec<miette::Error>> {
-        let raw_identifiers_db = RawCallableIdentifiersDb::build(&bp);
-        let user_component_db = UserComponentDb::build(&bp, &raw_identifiers_db);
         let package_graph = compute_package_graph().map_err(|e| vec![e])?;
+        let krate_collection = CrateCollection::new(package_graph, RustdocConfig::default());
+        Self::build_with_crate_collection(bp, &krate_collection)
+    }
+
+    /// Same as [`App::build`], but the crate documentation computed by previous builds
+    /// is reused if it's available in `krate_collection`.
+    ///
+    /// The documentation computed during this build is added to `krate_collection`, ready
+    /// to be reused by the next one.
+    #[tracing::instrument(skip_all)]
+    pub fn build_with_crate_collection(
+        bp: AppBlueprint,
+        krate_collection: &CrateCollection,
+    ) -> Result<Self, Vec<miette::Error>> {
+        krate_collection
+            .preflight_checks()
+            .map_err(|e| vec![e.into()])?;
+        let package_graph = krate_collection.package_graph().clone();
         let mut diagnostics = vec![];
-        let krate_collection = CrateCollection::new(package_graph.clone());
+        let (scope_graph, scoped_blueprints) =
+            ScopeGraph::build(&bp, &package_graph, &mut diagnostics);
+        exit_on_errors!(diagnostics);
+        let raw_identifiers_db = RawCallableIdentifiersDb::build(&scoped_blueprints);
+        let user_component_db = UserComponentDb::build(&scoped_blueprints, &raw_identifiers_db);
         let resolved_path_db = ResolvedPathDb::build(
             &user_component_db,
             &raw_identifiers_db,
@@ -81,67 +112,212 @@ impl App {
             &mut diagnostics,
         );
         exit_on_errors!(diagnostics);
+        // We know up front which crates we need to look at: we compute their docs
+        // in one go rather than one at a time.
+        krate_collection.batch_compute_crates(resolved_path_db.package_ids());
         let mut computation_db = ComputationDb::build(
             &user_component_db,
             &resolved_path_db,
             &package_graph,
-            &krate_collection,
+            krate_collection,
             &raw_identifiers_db,
             &mut diagnostics,
         );
         exit_on_errors!(diagnostics);
+        let request_scoped_framework_bindings =
+            framework_bindings(&package_graph, krate_collection);
         let mut component_db = ComponentDb::build(
-            &user_component_db,
+            ComponentDbInputs {
+                user_component_db: &user_component_db,
+                package_graph: &package_graph,
+                raw_identifiers_db: &raw_identifiers_db,
+                krate_collection,
+                request_scoped_framework_bindings: &request_scoped_framework_bindings,
+                scope_graph: &scope_graph,
+            },
             &mut computation_db,
-            &package_graph,
-            &raw_identifiers_db,
-            &krate_collection,
             &mut diagnostics,
         );
         exit_on_errors!(diagnostics);
-        let request_scoped_framework_bindings =
-            framework_bindings(&package_graph, &krate_collection);
         let mut constructible_db = ConstructibleDb::build(
-            &component_db,
-            &computation_db,
+            &mut component_db,
+            &mut computation_db,
             &package_graph,
-            &krate_collection,
+            krate_collection,
             &user_component_db,
             &raw_identifiers_db,
             &request_scoped_framework_bindings.right_values().collect(),
+            &scope_graph,
+            &mut diagnostics,
+        );
+        verify_error_observers(
+            krate_collection,
+            &DiagnosticContext {
+                package_graph: &package_graph,
+                user_component_db: &user_component_db,
+                raw_identifiers_db: &raw_identifiers_db,
+                component_db: &component_db,
+                computation_db: &computation_db,
+            },
             &mut diagnostics,
         );
         exit_on_errors!(diagnostics);
         let handler_call_graphs = {
             let router = component_db.router();
             let mut handler_call_graphs = IndexMap::with_capacity(router.len());
-            for (route, handler_id) in router {
+            for (router_key, handler_id) in router {
                 let call_graph = handler_call_graph(
                     *handler_id,
+                    &IndexSet::new(),
                     &computation_db,
                     &component_db,
                     &constructible_db,
                 );
-                handler_call_graphs.insert(route.to_owned(), call_graph);
+                handler_call_graphs.insert(router_key.to_owned(), call_graph);
             }
             handler_call_graphs
         };
-
-        let runtime_singletons: IndexSet<ResolvedType> = get_required_singleton_types(
-            handler_call_graphs.iter(),
+        let fallback_call_graph = component_db.fallback().map(|fallback_id| {
+            handler_call_graph(
+                fallback_id,
+                &IndexSet::new(),
+                &computation_db,
+                &component_db,
+                &constructible_db,
+            )
+        });
+        let middleware_call_graphs: IndexMap<_, _> = component_db
+            .iter()
+            .filter(|(_, c)| matches!(c, Component::Middleware { .. }))
+            .map(|(middleware_id, _)| {
+                let call_graph = handler_call_graph(
+                    middleware_id,
+                    &IndexSet::new(),
+                    &computation_db,
+                    &component_db,
+                    &constructible_db,
+                );
+                (middleware_id, call_graph)
+            })
+            .collect();
+        let mut request_call_graphs = RequestProcessingCallGraphs {
+            handlers: handler_call_graphs,
+            fallback: fallback_call_graph,
+            middlewares: middleware_call_graphs,
+            pipelines: IndexMap::new(),
+        };
+        verify_middlewares(
+            &request_call_graphs,
             &request_scoped_framework_bindings,
+            &DiagnosticContext {
+                package_graph: &package_graph,
+                user_component_db: &user_component_db,
+                raw_identifiers_db: &raw_identifiers_db,
+                component_db: &component_db,
+                computation_db: &computation_db,
+            },
+            &mut diagnostics,
+        );
+        exit_on_errors!(diagnostics);
+
+        // Request-scoped types needed by more than one layer of a pipeline must be built
+        // ahead of the middlewares, in order to build them at most once per request.
+        let handler_ids: Vec<_> = request_call_graphs
+            .handlers
+            .keys()
+            .map(|router_key| {
+                (
+                    Some(router_key.to_owned()),
+                    component_db.router()[router_key],
+                )
+            })
+            .chain(
+                component_db
+                    .fallback()
+                    .map(|fallback_id| (None, fallback_id)),
+            )
+            .collect();
+        for (router_key, handler_id) in handler_ids {
+            let Some((handler_call_graph, pipeline_call_graph)) = pipeline_call_graph(
+                request_call_graphs.pipelines.len(),
+                handler_id,
+                &request_scoped_framework_bindings,
+                &mut computation_db,
+                &mut component_db,
+                &constructible_db,
+            ) else {
+                continue;
+            };
+            match router_key {
+                Some(router_key) => {
+                    request_call_graphs.handlers[&router_key] = handler_call_graph;
+                }
+                None => {
+                    request_call_graphs.fallback = Some(handler_call_graph);
+                }
+            }
+            request_call_graphs
+                .pipelines
+                .insert(handler_id, pipeline_call_graph);
+        }
+        verify_shared_request_scoped_types(
+            &request_call_graphs,
+            &constructible_db,
+            krate_collection,
+            &DiagnosticContext {
+                package_graph: &package_graph,
+                user_component_db: &user_component_db,
+                raw_identifiers_db: &raw_identifiers_db,
+                component_db: &component_db,
+                computation_db: &computation_db,
+            },
+            &mut diagnostics,
+        );
+
+        let types_provided_at_runtime = request_scoped_framework_bindings
+            .right_values()
+            .cloned()
+            .chain(std::iter::once(server_state_type()))
+            .chain(
+                request_call_graphs
+                    .pipelines
+                    .values()
+                    .flat_map(|p| p.shared_types.iter().cloned()),
+            )
+            .collect();
+        let mut runtime_singletons: IndexSet<ResolvedType> = get_required_singleton_types(
+            request_call_graphs.iter(),
+            &types_provided_at_runtime,
             &constructible_db,
             &component_db,
+            &scope_graph,
         );
+        runtime_singletons.extend(verify_shutdown_hook(
+            &constructible_db,
+            &component_db,
+            &scope_graph,
+            &package_graph,
+            &user_component_db,
+            &raw_identifiers_db,
+            &computation_db,
+            &mut diagnostics,
+        ));
 
+        let cloned_singletons = get_cloned_singleton_types(
+            request_call_graphs.iter(),
+            component_db.shutdown_hook(&computation_db),
+            &runtime_singletons,
+        );
         verify_singletons(
             &runtime_singletons,
+            &cloned_singletons,
             &constructible_db,
             &component_db,
+            &scope_graph,
             &package_graph,
             &user_component_db,
             &raw_identifiers_db,
-            &krate_collection,
+            krate_collection,
             &mut diagnostics,
         );
         let runtime_singleton_bindings = runtime_singletons
@@ -150,17 +326,29 @@ impl App {
             // Assign a unique name to each singleton
             .map(|(i, type_)| (format_ident!("s{}", i), type_.to_owned()))
             .collect();
-        let application_state_call_graph = application_state_call_graph(
+        let mut application_state_call_graph = application_state_call_graph(
             &runtime_singleton_bindings,
             &mut computation_db,
             &mut component_db,
             &mut constructible_db,
+            &scope_graph,
+            &user_component_db,
+            &raw_identifiers_db,
         );
-        let codegen_types = codegen_types(&package_graph, &krate_collection);
+        let error_trait =
+            process_framework_path("std::error::Error", &package_graph, krate_collection);
+        let ResolvedType::ResolvedPath(error_trait) = &error_trait else {
+            unreachable!()
+        };
+        for error_variant in application_state_call_graph.error_variants.values_mut() {
+            error_variant.implements_error =
+                implements_trait(krate_collection, &error_variant.type_, error_trait);
+        }
+        let codegen_types = codegen_types(&package_graph, krate_collection);
         exit_on_errors!(diagnostics);
         Ok(Self {
             package_graph,
-            handler_call_graphs,
+            request_call_graphs,
             component_db,
             computation_db,
             application_state_call_graph,
@@ -176,7 +364,7 @@ impl App {
     pub fn codegen(&self) -> Result<GeneratedApp, anyhow::Error> {
         let (cargo_toml, mut package_ids2deps) = codegen::codegen_manifest(
             &self.package_graph,
-            &self.handler_call_graphs,
+            &self.request_call_graphs,
             &self.application_state_call_graph.call_graph,
             &self.request_scoped_framework_bindings,
             &self.codegen_types,
@@ -194,7 +382,7 @@ impl App {
         package_ids2deps.insert(generated_app_package_id, "crate".into());
 
         let lib_rs = codegen::codegen_app(
-            &self.handler_call_graphs,
+            &self.request_call_graphs,
             &self.application_state_call_graph,
             &self.request_scoped_framework_bindings,
             &package_ids2deps,
@@ -210,7 +398,7 @@ impl App {
         let mut handler_graphs = IndexMap::new();
         let (_, mut package_ids2deps) = codegen::codegen_manifest(
             &self.package_graph,
-            &self.handler_call_graphs,
+            &self.request_call_graphs,
             &self.application_state_call_graph.call_graph,
             &self.request_scoped_framework_bindings,
             &self.codegen_types,
@@ -228,14 +416,56 @@ impl App {
         }
         package_ids2deps.insert(generated_app_package_id, "crate".into());
 
-        for (route, handler_call_graph) in &self.handler_call_graphs {
+        for (router_key, handler_call_graph) in &self.request_call_graphs.handlers {
             handler_graphs.insert(
-                route.to_owned(),
+                router_key.to_owned(),
                 handler_call_graph
                     .dot(&package_ids2deps, &self.component_db, &self.computation_db)
-                    .replace("digraph", &format!("digraph \"{route}\"")),
+                    .replace("digraph", &format!("digraph \"{router_key}\"")),
             );
         }
+        let fallback_graph = self
+            .request_call_graphs
+            .fallback
+            .as_ref()
+            .map(|call_graph| {
+                call_graph
+                    .dot(&package_ids2deps, &self.component_db, &self.computation_db)
+                    .replace("digraph", "digraph fallback")
+            });
+        let middleware_graphs = self
+            .request_call_graphs
+            .middlewares
+            .values()
+            .enumerate()
+            .map(|(i, call_graph)| {
+                call_graph
+                    .dot(&package_ids2deps, &self.component_db, &self.computation_db)
+                    .replace("digraph", &format!("digraph middleware_{i}"))
+            })
+            .collect();
+        let pipeline_graphs = self
+            .request_call_graphs
+            .pipelines
+            .values()
+            .enumerate()
+            .map(|(n, pipeline)| {
+                let prelude = pipeline
+                    .prelude
+                    .dot(&package_ids2deps, &self.component_db, &self.computation_db)
+                    .replace("digraph", &format!("digraph pipeline_{n}_prelude"));
+                let middlewares = pipeline
+                    .middlewares
+                    .iter()
+                    .enumerate()
+                    .map(|(i, call_graph)| {
+                        call_graph
+                            .dot(&package_ids2deps, &self.component_db, &self.computation_db)
+                            .replace("digraph", &format!("digraph pipeline_{n}_middleware_{i}"))
+                    });
+                std::iter::once(prelude).chain(middlewares).collect()
+            })
+            .collect();
         let application_state_graph = self
             .application_state_call_graph
             .call_graph
@@ -243,6 +473,9 @@ impl App {
             .replace("digraph", "digraph app_state");
         AppDiagnostics {
             handlers: handler_graphs,
+            fallback: fallback_graph,
+            middlewares: middleware_graphs,
+            pipelines: pipeline_graphs,
             application_state: application_state_graph,
         }
     }
@@ -261,18 +494,32 @@ pub(crate) enum BuildError {
 /// It contains the DOT representation of all the call graphs underpinning the originating `App`.
 /// The DOT representation can be used for snapshot testing and/or troubleshooting.
 pub struct AppDiagnostics {
-    pub handlers: IndexMap<String, String>,
+    pub handlers: IndexMap<RouterKey, String>,
+    /// The call graph of the fallback request handler, if one was registered.
+    pub fallback: Option<String>,
+    /// The call graphs of the registered middlewares, in the order they were registered.
+    pub middlewares: Vec<String>,
+    /// The call graphs of the pipelines that share request-scoped types across their layers:
+    /// the prelude first, followed by the middlewares.
+    pub pipelines: Vec<String>,
     pub application_state: String,
 }
 
 impl AppDiagnostics {
     /// Persist the diagnostic information to disk, using one file per handler within the specified
     /// directory.
+    ///
+    /// Handlers are grouped by path: the handler for `GET /home` is saved to
+    /// `handlers/home/GET.dot`.  
+    /// Middlewares are saved to `middlewares/middleware_<i>.dot`, where `i` is their
+    /// registration index.
+    /// Pipelines that share request-scoped types are saved to `pipelines/pipeline_<n>.dot`.
     pub fn persist(&self, directory: &Path) -> Result<(), anyhow::Error> {
         let handler_directory = directory.join("handlers");
-        fs_err::create_dir_all(&handler_directory)?;
-        for (route, handler) in &self.handlers {
-            let path = handler_directory.join(format!("{route}.dot").trim_start_matches('/'));
+        for (router_key, handler) in &self.handlers {
+            let route_directory = handler_directory.join(router_key.path.trim_start_matches('/'));
+            fs_err::create_dir_all(&route_directory)?;
+            let path = route_directory.join(format!("{}.dot", router_key.method_guard));
             let mut file = fs_err::OpenOptions::new()
                 .write(true)
                 .create(true)
@@ -280,6 +527,39 @@ impl AppDiagnostics {
                 .open(path)?;
             file.write_all(handler.as_bytes())?;
         }
+        if let Some(fallback) = &self.fallback {
+            fs_err::create_dir_all(&handler_directory)?;
+            let mut file = fs_err::OpenOptions::new()
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(handler_directory.join("fallback.dot"))?;
+            file.write_all(fallback.as_bytes())?;
+        }
+        if !self.middlewares.is_empty() {
+            let middleware_directory = directory.join("middlewares");
+            fs_err::create_dir_all(&middleware_directory)?;
+            for (i, middleware) in self.middlewares.iter().enumerate() {
+                let mut file = fs_err::OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(middleware_directory.join(format!("middleware_{i}.dot")))?;
+                file.write_all(middleware.as_bytes())?;
+            }
+        }
+        if !self.pipelines.is_empty() {
+            let pipeline_directory = directory.join("pipelines");
+            fs_err::create_dir_all(&pipeline_directory)?;
+            for (n, pipeline) in self.pipelines.iter().enumerate() {
+                let mut file = fs_err::OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(pipeline_directory.join(format!("pipeline_{n}.dot")))?;
+                file.write_all(pipeline.as_bytes())?;
+            }
+        }
         let mut file = fs_err::OpenOptions::new()
             .write(true)
             .create(true)
@@ -301,6 +581,15 @@ impl AppDiagnostics {
         for handler in self.handlers.values() {
             file.write_all(handler.as_bytes())?;
         }
+        if let Some(fallback) = &self.fallback {
+            file.write_all(fallback.as_bytes())?;
+        }
+        for middleware in &self.middlewares {
+            file.write_all(middleware.as_bytes())?;
+        }
+        for pipeline in &self.pipelines {
+            file.write_all(pipeline.as_bytes())?;
+        }
         file.write_all(self.application_state.as_bytes())?;
         file.flush()?;
         Ok(())
@@ -310,14 +599,20 @@ impl AppDiagnostics {
 /// Determine the set of singleton types that are required to execute the constructors and handlers
 /// registered by the application.
 /// These singletons will be attached to the overall application state.
+///
+/// The types in `types_provided_at_runtime` (e.g. the incoming request or the request-scoped
+/// types shared across the layers of a pipeline) are not singletons, even though they are
+/// taken as inputs.
 fn get_required_singleton_types<'a>(
-    handler_call_graphs: impl Iterator<Item = (&'a String, &'a CallGraph)>,
-    types_provided_by_the_framework: &BiHashMap<Ident, ResolvedType>,
+    handler_call_graphs: impl Iterator<Item = &'a CallGraph>,
+    types_provided_at_runtime: &HashSet<ResolvedType>,
     constructibles_db: &ConstructibleDb,
     component_db: &ComponentDb,
+    scope_graph: &ScopeGraph,
 ) -> IndexSet<ResolvedType> {
+    let application_state_scope_id = scope_graph.application_state_scope_id();
     let mut singletons_to_be_built = IndexSet::new();
-    for (_, handler_call_graph) in handler_call_graphs {
+    for handler_call_graph in handler_call_graphs {
         for required_input in handler_call_graph.required_input_types() {
             let required_input = if let ResolvedType::Reference(t) = &required_input {
                 if !t.is_static {
@@ -330,8 +625,10 @@ fn get_required_singleton_types<'a>(
             } else {
                 &required_input
             };
-            if !types_provided_by_the_framework.contains_right(required_input) {
-                let component_id = constructibles_db[required_input];
+            if !types_provided_at_runtime.contains(required_input) {
+                let component_id = constructibles_db
+                    .get(application_state_scope_id, required_input)
+                    .unwrap();
                 assert_eq!(
                     component_db.lifecycle(component_id),
                     Some(&Lifecycle::Singleton)
@@ -343,6 +640,34 @@ fn get_required_singleton_types<'a>(
     singletons_to_be_built
 }
 
+/// Return the subset of runtime singletons that are taken by value by at least one request
+/// handler, middleware or by the shutdown hook.
+///
+/// Those singletons are cloned out of the application state every time they are needed.
+/// Singletons that are only ever borrowed are passed by reference instead.
+fn get_cloned_singleton_types<'a>(
+    handler_call_graphs: impl Iterator<Item = &'a CallGraph>,
+    shutdown_hook: Option<ShutdownHook>,
+    runtime_singletons: &IndexSet<ResolvedType>,
+) -> IndexSet<ResolvedType> {
+    let mut cloned_singletons = IndexSet::new();
+    for handler_call_graph in handler_call_graphs {
+        for required_input in handler_call_graph.required_input_types() {
+            if runtime_singletons.contains(&required_input) {
+                cloned_singletons.insert(required_input);
+            }
+        }
+    }
+    if let Some(shutdown_hook) = shutdown_hook {
+        for input_type in shutdown_hook.input_types() {
+            if runtime_singletons.contains(input_type) {
+                cloned_singletons.insert(input_type.to_owned());
+            }
+        }
+    }
+    cloned_singletons
+}
+
 /// Return the set of name bindings injected by `pavex` into the processing context for
 /// an incoming request (e.g. the incoming request itself!).  
 /// The types injected here can be used by constructors and handlers even though no constructor
@@ -353,7 +678,18 @@ fn framework_bindings(
 ) -> BiHashMap<Ident, ResolvedType> {
     let http_request = "pavex_runtime::http::Request::<pavex_runtime::hyper::Body>";
     let http_request = process_framework_path(http_request, package_graph, krate_collection);
-    BiHashMap::from_iter([(format_ident!("request"), http_request)].into_iter())
+    let route_params = "pavex_runtime::extract::RouteParams";
+    let route_params = process_framework_path(route_params, package_graph, krate_collection);
+    let next = "pavex_runtime::middleware::Next";
+    let next = process_framework_path(next, package_graph, krate_collection);
+    BiHashMap::from_iter(
+        [
+            (format_ident!("request"), http_request),
+            (format_ident!("route_params"), route_params),
+            (format_ident!("next"), next),
+        ]
+        .into_iter(),
+    )
 }
 
 /// Return the set of types that will be used in the generated code to build a functional
@@ -366,12 +702,16 @@ fn codegen_types(
     HashSet::from_iter([error])
 }
 
-/// Verify that all singletons needed at runtime implement `Send`, `Sync` and `Clone`.
+/// Verify that all singletons needed at runtime implement `Send` and `Sync`.
 /// This is required since `pavex` runs on a multi-threaded `tokio` runtime.
+///
+/// Singletons that are taken by value must also implement `Clone`.
 fn verify_singletons(
     runtime_singletons: &IndexSet<ResolvedType>,
+    cloned_singletons: &IndexSet<ResolvedType>,
     constructible_db: &ConstructibleDb,
     component_db: &ComponentDb,
+    scope_graph: &ScopeGraph,
     package_graph: &PackageGraph,
     user_component_db: &UserComponentDb,
     raw_identifiers_db: &RawCallableIdentifiersDb,
@@ -380,9 +720,11 @@ fn verify_singletons(
 ) {
     fn missing_trait_implementation(
         e: MissingTraitImplementationError,
+        help: String,
         package_graph: &PackageGraph,
         constructible_db: &ConstructibleDb,
         component_db: &ComponentDb,
+        scope_graph: &ScopeGraph,
         user_component_db: &UserComponentDb,
         raw_identifiers_db: &RawCallableIdentifiersDb,
         diagnostics: &mut Vec<miette::Error>,
@@ -396,7 +738,9 @@ fn verify_singletons(
         } else {
             e.type_.clone()
         };
-        let component_id = constructible_db[&t];
+        let component_id = constructible_db
+            .get(scope_graph.application_state_scope_id(), &t)
+            .unwrap();
         let user_component_id = component_db.user_component_id(component_id).unwrap();
         let user_component = &user_component_db[user_component_id];
         let raw_identifier_id = user_component.raw_callable_identifiers_id();
@@ -412,10 +756,6 @@ fn verify_singletons(
         };
         let label = diagnostic::get_f_macro_invocation_span(&source, location)
             .map(|s| s.labeled(format!("The {component_kind} was registered here")));
-        let help = "All singletons must implement the `Send`, `Sync` and `Clone` traits.\n \
-                `pavex` runs on a multi-threaded HTTP server and singletons must be shared \
-                 across all worker threads."
-            .into();
         let diagnostic = CompilerDiagnostic::builder(source, e)
             .optional_label(label)
             .help(help)
@@ -427,14 +767,40 @@ fn verify_singletons(
     let sync = process_framework_path("core::marker::Sync", package_graph, krate_collection);
     let clone = process_framework_path("core::clone::Clone", package_graph, krate_collection);
     for singleton_type in runtime_singletons {
-        for trait_ in [&send, &sync, &clone] {
-            let ResolvedType::ResolvedPath(trait_) = trait_ else { unreachable!() };
-            if let Err(e) = assert_trait_is_implemented(krate_collection, singleton_type, trait_) {
+        let mut traits = vec![&send, &sync];
+        // Singletons that are only ever borrowed are passed by reference, they don't need to
+        // be cloned.
+        if cloned_singletons.contains(singleton_type) {
+            traits.push(&clone);
+        }
+        for trait_ in traits {
+            let ResolvedType::ResolvedPath(trait_path) = trait_ else {
+                unreachable!()
+            };
+            if let Err(e) =
+                assert_trait_is_implemented(krate_collection, singleton_type, trait_path)
+            {
+                let help = if trait_ == &clone {
+                    format!(
+                        "Singletons that are taken by value are cloned out of the application \
+                        state every time they are needed, therefore they must implement the \
+                        `Clone` trait.\n\
+                        Take a shared reference as input, `&{singleton_type:?}`, if you don't \
+                        need ownership."
+                    )
+                } else {
+                    "All singletons must implement the `Send` and `Sync` traits.\n \
+                    `pavex` runs on a multi-threaded HTTP server and singletons must be shared \
+                    across all worker threads."
+                        .into()
+                };
                 missing_trait_implementation(
                     e,
+                    help,
                     package_graph,
                     constructible_db,
                     component_db,
+                    scope_graph,
                     user_component_db,
                     raw_identifiers_db,
                     diagnostics,
@@ -443,3 +809,341 @@ fn verify_singletons(
         }
     }
 }
+
+/// Verify that the shutdown hook, if one was registered, only takes singletons as input.
+///
+/// It returns the singleton types that must be stored in the application state
+/// to invoke the shutdown hook.
+fn verify_shutdown_hook(
+    constructible_db: &ConstructibleDb,
+    component_db: &ComponentDb,
+    scope_graph: &ScopeGraph,
+    package_graph: &PackageGraph,
+    user_component_db: &UserComponentDb,
+    raw_identifiers_db: &RawCallableIdentifiersDb,
+    computation_db: &ComputationDb,
+    diagnostics: &mut Vec<miette::Error>,
+) -> IndexSet<ResolvedType> {
+    fn not_a_singleton(
+        input_type: &ResolvedType,
+        inner_type: &ResolvedType,
+        shutdown_hook: &ShutdownHook,
+        component_db: &ComponentDb,
+        package_graph: &PackageGraph,
+        user_component_db: &UserComponentDb,
+        raw_identifiers_db: &RawCallableIdentifiersDb,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let user_component_id = component_db.shutdown_hook_id().unwrap();
+        let location = raw_identifiers_db
+            .get_location(user_component_db[user_component_id].raw_callable_identifiers_id());
+        let source = match location.source_file(package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled("The shutdown hook was registered here".into()));
+        let error = anyhow::anyhow!(
+            "I cannot invoke your shutdown hook, `{}`, because it needs an instance of \
+            `{input_type:?}` as input, but shutdown hooks can only take singletons as input.",
+            shutdown_hook.callable.path
+        );
+        let help = format!(
+            "Register a constructor for `{inner_type:?}` with a singleton lifecycle, \
+            `Lifecycle::Singleton`."
+        );
+        let diagnostic = CompilerDiagnostic::builder(source, error)
+            .optional_label(label)
+            .help(help)
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+
+    let mut singletons = IndexSet::new();
+    let Some(shutdown_hook) = component_db.shutdown_hook(computation_db) else {
+        return singletons;
+    };
+    let application_state_scope_id = scope_graph.application_state_scope_id();
+    for input_type in shutdown_hook.input_types() {
+        let inner_type = match input_type {
+            ResolvedType::Reference(t) if !t.is_static => t.inner.deref(),
+            _ => input_type,
+        };
+        let is_singleton = constructible_db
+            .get(application_state_scope_id, inner_type)
+            .is_some_and(|id| component_db.lifecycle(id) == Some(&Lifecycle::Singleton));
+        if is_singleton {
+            singletons.insert(inner_type.to_owned());
+        } else {
+            not_a_singleton(
+                input_type,
+                inner_type,
+                &shutdown_hook,
+                component_db,
+                package_graph,
+                user_component_db,
+                raw_identifiers_db,
+                diagnostics,
+            );
+        }
+    }
+    singletons
+}
+
+/// The databases needed to point a diagnostic at the place where a component was registered.
+struct DiagnosticContext<'a> {
+    package_graph: &'a PackageGraph,
+    user_component_db: &'a UserComponentDb,
+    raw_identifiers_db: &'a RawCallableIdentifiersDb,
+    component_db: &'a ComponentDb,
+    computation_db: &'a ComputationDb,
+}
+
+impl<'a> DiagnosticContext<'a> {
+    /// Report `error`, pointing at the place where `user_component_id` was registered.
+    fn report(
+        &self,
+        user_component_id: UserComponentId,
+        error: impl Into<anyhow::Error>,
+        help: String,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let user_component = &self.user_component_db[user_component_id];
+        let component_kind = user_component.callable_type();
+        let location = self
+            .raw_identifiers_db
+            .get_location(user_component.raw_callable_identifiers_id());
+        let source = match location.source_file(self.package_graph) {
+            Ok(s) => s,
+            Err(e) => {
+                diagnostics.push(e.into());
+                return;
+            }
+        };
+        let label = diagnostic::get_f_macro_invocation_span(&source, location)
+            .map(|s| s.labeled(format!("The {component_kind} was registered here")));
+        let diagnostic = CompilerDiagnostic::builder(source, error)
+            .optional_label(label)
+            .help(help)
+            .build();
+        diagnostics.push(diagnostic.into());
+    }
+}
+
+/// Verify that `Next` and the incoming request are used where they are available:
+///
+/// - `Next` can only be injected into middlewares, it is meaningless for request handlers;
+/// - middlewares cannot take the incoming request as input, since it is owned by `Next`.
+fn verify_middlewares(
+    call_graphs: &RequestProcessingCallGraphs,
+    request_scoped_framework_bindings: &BiHashMap<Ident, ResolvedType>,
+    ctx: &DiagnosticContext,
+    diagnostics: &mut Vec<miette::Error>,
+) {
+    fn unavailable_framework_type(
+        component_id: ComponentId,
+        framework_type: &ResolvedType,
+        help: String,
+        ctx: &DiagnosticContext,
+        diagnostics: &mut Vec<miette::Error>,
+    ) {
+        let user_component_id = ctx.component_db.user_component_id(component_id).unwrap();
+        let callable = &ctx.computation_db[user_component_id];
+        let component_kind = ctx.user_component_db[user_component_id].callable_type();
+        let error = anyhow::anyhow!(
+            "I cannot invoke your {component_kind}, `{}`, because it needs an instance of \
+            `{framework_type:?}` as input (either directly or via one of its dependencies), \
+            but that type is not available to {component_kind}s.",
+            callable.path
+        );
+        ctx.report(user_component_id, error, help, diagnostics);
+    }
+
+    let next_type = request_scoped_framework_bindings
+        .get_by_left(&format_ident!("next"))
+        .unwrap();
+    let request_type = request_scoped_framework_bindings
+        .get_by_left(&format_ident!("request"))
+        .unwrap();
+    for (handler_id, call_graph) in call_graphs.request_handlers(ctx.component_db) {
+        if call_graph.required_input_types().contains(next_type) {
+            unavailable_framework_type(
+                handler_id,
+                next_type,
+                format!(
+                    "`{next_type:?}` can only be taken as input by middlewares. \
+                    Register a middleware via `.wrap` if you want to run logic around \
+                    your request handler."
+                ),
+                ctx,
+                diagnostics,
+            );
+        }
+    }
+    for (middleware_id, call_graph) in &call_graphs.middlewares {
+        if call_graph.required_input_types().contains(request_type) {
+            unavailable_framework_type(
+                *middleware_id,
+                request_type,
+                format!(
+                    "The incoming request is owned by `{next_type:?}`. \
+                    Use its `request` method to inspect it."
+                ),
+                ctx,
+                diagnostics,
+            );
+        }
+    }
+}
+
+/// Verify that the request-scoped types shared across the layers of a pipeline can be shared.
+///
+/// Shared types are built once per request and kept behind an `Arc` while the middlewares
+/// and the request handler are running, therefore they must implement `Send` and `Sync`.
+/// Layers that take a shared type by value get a clone: it must also implement `Clone`.
+fn verify_shared_request_scoped_types(
+    call_graphs: &RequestProcessingCallGraphs,
+    constructible_db: &ConstructibleDb,
+    krate_collection: &CrateCollection,
+    ctx: &DiagnosticContext,
+    diagnostics: &mut Vec<miette::Error>,
+) {
+    // For each shared type, the request handler of the first pipeline that shares it and
+    // whether it must be cloned.
+    let mut shared_types = IndexMap::<ResolvedType, (ComponentId, bool)>::new();
+    for (handler_id, call_graph) in call_graphs.request_handlers(ctx.component_db) {
+        let Some(pipeline) = call_graphs.pipelines.get(&handler_id) else {
+            continue;
+        };
+        let layer_input_types: IndexSet<_> = pipeline
+            .middlewares
+            .iter()
+            .chain(std::iter::once(call_graph))
+            .flat_map(|call_graph| call_graph.required_input_types())
+            .collect();
+        for shared_type in &pipeline.shared_types {
+            let is_cloned = layer_input_types.contains(shared_type);
+            let (_, must_be_cloned) = shared_types
+                .entry(shared_type.to_owned())
+                .or_insert((handler_id, false));
+            *must_be_cloned |= is_cloned;
+        }
+    }
+
+    let send = process_framework_path("core::marker::Send", ctx.package_graph, krate_collection);
+    let sync = process_framework_path("core::marker::Sync", ctx.package_graph, krate_collection);
+    let clone = process_framework_path("core::clone::Clone", ctx.package_graph, krate_collection);
+    for (shared_type, (handler_id, must_be_cloned)) in shared_types {
+        let mut traits = vec![&send, &sync];
+        if must_be_cloned {
+            traits.push(&clone);
+        }
+        for trait_ in traits {
+            let ResolvedType::ResolvedPath(trait_path) = trait_ else {
+                unreachable!()
+            };
+            let Err(e) = assert_trait_is_implemented(krate_collection, &shared_type, trait_path)
+            else {
+                continue;
+            };
+            let help = if trait_ == &clone {
+                format!(
+                    "Request-scoped types that are needed by more than one middleware or \
+                    request handler are built once per request and cloned when taken \
+                    by value, therefore they must implement the `Clone` trait.\n\
+                    Take a shared reference as input, `&{shared_type:?}`, if you don't \
+                    need ownership."
+                )
+            } else {
+                "Request-scoped types that are needed by more than one middleware or \
+                request handler are built once per request and shared across them, \
+                therefore they must implement the `Send` and `Sync` traits."
+                    .into()
+            };
+            let constructor_id = constructible_db
+                .get(ctx.component_db.scope_id(handler_id), &shared_type)
+                .unwrap();
+            // The constructor registered by the user returns a `Result`: the matcher for
+            // its `Ok` variant is what gets invoked in the call graph.
+            let constructor_id = match ctx
+                .component_db
+                .hydrated_component(constructor_id, ctx.computation_db)
+            {
+                HydratedComponent::Constructor(Constructor(Computation::MatchResult(_))) => {
+                    ctx.component_db.fallible_id(constructor_id)
+                }
+                _ => constructor_id,
+            };
+            // Constructors obtained by specializing a generic constructor are not
+            // user components: we point at the request handler instead.
+            let user_component_id = ctx
+                .component_db
+                .user_component_id(constructor_id)
+                .or_else(|| ctx.component_db.user_component_id(handler_id))
+                .unwrap();
+            ctx.report(user_component_id, e, help, diagnostics);
+        }
+    }
+}
+
+/// Verify that the errors observed by error observers that take `&pavex_runtime::Error` as input
+/// can be converted into a `pavex_runtime::Error`, i.e. that they implement `std::error::Error`.
+fn verify_error_observers(
+    krate_collection: &CrateCollection,
+    ctx: &DiagnosticContext,
+    diagnostics: &mut Vec<miette::Error>,
+) {
+    let error_trait =
+        process_framework_path("std::error::Error", ctx.package_graph, krate_collection);
+    let ResolvedType::ResolvedPath(error_trait) = &error_trait else {
+        unreachable!()
+    };
+    let mut missing_implementations = HashSet::default();
+    for (err_ref_id, error_observer_ids) in ctx.component_db.error_conversions() {
+        let err_ref = ctx
+            .component_db
+            .hydrated_component(err_ref_id, ctx.computation_db);
+        let ResolvedType::Reference(err_ref) = err_ref.output_type() else {
+            unreachable!()
+        };
+        let error_type = err_ref.inner.as_ref();
+        if implements_trait(krate_collection, error_type, error_trait) {
+            continue;
+        }
+        for error_observer_id in error_observer_ids {
+            // Error observers are always registered by the user.
+            let user_component_id = ctx
+                .component_db
+                .user_component_id(error_observer_id)
+                .unwrap();
+            missing_implementations.insert((user_component_id, error_type.to_owned()));
+        }
+    }
+    // Error references are not visited in a deterministic order.
+    let mut missing_implementations: Vec<_> = missing_implementations.into_iter().collect();
+    missing_implementations.sort_by_cached_key(|(user_component_id, error_type)| {
+        (
+            ctx.computation_db[*user_component_id].path.to_string(),
+            format!("{error_type:?}"),
+        )
+    });
+    for (user_component_id, error_type) in missing_implementations {
+        let callable = &ctx.computation_db[user_component_id];
+        let error = anyhow::anyhow!(
+            "Your error observer, `{}`, takes `&pavex_runtime::Error` as input: I must convert \
+            the errors it observes into a `pavex_runtime::Error` before invoking it.\n\
+            `{error_type:?}` is one of them, but it does not implement `std::error::Error`: \
+            I can't convert it.",
+            callable.path
+        );
+        let help = format!(
+            "Implement `std::error::Error` for `{error_type:?}`, or stop observing errors \
+            of that type by taking a reference to a more specific error type as the first input \
+            parameter of the error observer."
+        );
+        ctx.report(user_component_id, error, help, diagnostics);
+    }
+}
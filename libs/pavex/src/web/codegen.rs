@@ -8,20 +8,26 @@ use guppy::graph::PackageSource;
 use guppy::{PackageId, Version};
 use indexmap::{IndexMap, IndexSet};
 use proc_macro2::{Ident, TokenStream};
-use quote::{format_ident, quote};
-use syn::{ItemEnum, ItemFn, ItemStruct};
+use quote::{format_ident, quote, ToTokens};
+use syn::{ItemFn, ItemStruct};
+
+use pavex_builder::router::MethodGuard;
 
 use crate::language::{Callable, GenericArgument, ResolvedType};
 use crate::rustdoc::{ALLOC_PACKAGE_ID, TOOLCHAIN_CRATES};
-use crate::web::analyses::call_graph::{ApplicationStateCallGraph, CallGraph, CallGraphNode};
-use crate::web::analyses::components::{ComponentDb, HydratedComponent};
+use crate::web::analyses::call_graph::{
+    server_state_type, ApplicationStateCallGraph, ApplicationStateErrorVariant, CallGraph,
+    CallGraphNode, RequestProcessingCallGraphs,
+};
+use crate::web::analyses::components::{ComponentDb, ComponentId, HydratedComponent};
 use crate::web::analyses::computations::ComputationDb;
 use crate::web::app::GENERATED_APP_PACKAGE_ID;
+use crate::web::codegen_utils;
 use crate::web::computation::Computation;
 use crate::web::constructors::Constructor;
 
 pub(crate) fn codegen_app(
-    handler_call_graphs: &IndexMap<String, CallGraph>,
+    request_call_graphs: &RequestProcessingCallGraphs,
     application_state_call_graph: &ApplicationStateCallGraph,
     request_scoped_framework_bindings: &BiHashMap<Ident, ResolvedType>,
     package_id2name: &BiHashMap<PackageId, String>,
@@ -42,41 +48,171 @@ pub(crate) fn codegen_app(
         computation_db,
     )?;
     let define_server_state = define_server_state();
+    // Pipelines that share request-scoped types across their layers take a reference to
+    // the server state as input.
+    let mut request_scoped_bindings = request_scoped_framework_bindings.to_owned();
+    request_scoped_bindings.insert(format_ident!("server_state"), server_state_type());
 
-    let handler_functions: IndexMap<_, _> = handler_call_graphs
-        .into_iter()
-        .map(|(path, call_graph)| {
+    let handler_functions: IndexMap<_, _> = request_call_graphs
+        .handlers
+        .iter()
+        .map(|(router_key, call_graph)| {
             let code = call_graph.codegen(package_id2name, component_db, computation_db)?;
-            Ok::<_, anyhow::Error>((path, (code, call_graph.required_input_types())))
+            Ok::<_, anyhow::Error>((router_key, (code, call_graph.required_input_types())))
         })
         // TODO: wasteful
         .collect::<Result<IndexMap<_, _>, _>>()?
         .into_iter()
         .enumerate()
-        .map(|(i, (path, (mut function, parameter_bindings)))| {
+        .map(|(i, (router_key, (mut function, parameter_bindings)))| {
             // Ensure that all handler functions have a unique name.
             function.sig.ident = format_ident!("route_handler_{}", i);
-            (path, (function, parameter_bindings))
+            (router_key, (function, parameter_bindings))
         })
         .collect();
+    let fallback_function = request_call_graphs
+        .fallback
+        .as_ref()
+        .map(|call_graph| {
+            let mut function = call_graph.codegen(package_id2name, component_db, computation_db)?;
+            function.sig.ident = format_ident!("fallback_handler");
+            Ok::<_, anyhow::Error>((function, call_graph.required_input_types()))
+        })
+        .transpose()?;
+    // Middlewares that only wrap pipelines with shared request-scoped types are invoked
+    // via the functions generated for those pipelines.
+    let mut standalone_middleware_ids = HashSet::default();
+    let mut shared_pipeline_middleware_ids = HashSet::default();
+    for (handler_id, _) in request_call_graphs.request_handlers(component_db) {
+        let middleware_ids = if request_call_graphs.pipelines.contains_key(&handler_id) {
+            &mut shared_pipeline_middleware_ids
+        } else {
+            &mut standalone_middleware_ids
+        };
+        middleware_ids.extend(component_db.middleware_ids(handler_id).iter().copied());
+    }
+    let is_superseded = |middleware_id: &ComponentId| {
+        shared_pipeline_middleware_ids.contains(middleware_id)
+            && !standalone_middleware_ids.contains(middleware_id)
+    };
+    let middleware_functions = request_call_graphs
+        .middlewares
+        .iter()
+        .enumerate()
+        .filter(|(_, (middleware_id, _))| !is_superseded(middleware_id))
+        .map(|(i, (middleware_id, call_graph))| {
+            let mut function = call_graph.codegen(package_id2name, component_db, computation_db)?;
+            // Ensure that all middleware functions have a unique name.
+            function.sig.ident = format_ident!("middleware_{}", i);
+            Ok::<_, anyhow::Error>((
+                *middleware_id,
+                (function, call_graph.required_input_types()),
+            ))
+        })
+        .collect::<Result<IndexMap<_, _>, _>>()?;
+    let mut pipeline_preludes = vec![];
+    let mut pipeline_middleware_functions = vec![];
+    for (n, pipeline) in request_call_graphs.pipelines.values().enumerate() {
+        let prelude_call_graph = &pipeline.prelude;
+        let mut prelude =
+            prelude_call_graph.codegen(package_id2name, component_db, computation_db)?;
+        prelude.sig.ident = format_ident!("pipeline_{}_prelude", n);
+        // It may take the server state as input, which is a private type.
+        prelude.vis = syn::Visibility::Inherited;
+        pipeline_preludes.push((prelude, prelude_call_graph.required_input_types()));
+        let functions = pipeline
+            .middlewares
+            .iter()
+            .enumerate()
+            .map(|(i, call_graph)| {
+                let mut function =
+                    call_graph.codegen(package_id2name, component_db, computation_db)?;
+                function.sig.ident = format_ident!("pipeline_{}_middleware_{}", n, i);
+                Ok::<_, anyhow::Error>((function, call_graph.required_input_types()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        pipeline_middleware_functions.push(functions);
+    }
+    let pipeline = |handler_id: ComponentId, handler| match request_call_graphs
+        .pipelines
+        .get_index_of(&handler_id)
+    {
+        Some(n) => HandlerPipeline {
+            handler,
+            middlewares: pipeline_middleware_functions[n].iter().collect(),
+            prelude: Some(&pipeline_preludes[n]),
+        },
+        None => HandlerPipeline {
+            handler,
+            middlewares: component_db
+                .middleware_ids(handler_id)
+                .iter()
+                .map(|middleware_id| &middleware_functions[middleware_id])
+                .collect(),
+            prelude: None,
+        },
+    };
+    let fallback_pipeline = fallback_function
+        .as_ref()
+        .map(|handler| pipeline(component_db.fallback().unwrap(), handler));
+    let mut pipeline_functions = BTreeMap::new();
+    for (handler_id, handler) in handler_functions
+        .iter()
+        .map(|(router_key, handler)| (component_db.router()[*router_key], handler))
+        .chain(component_db.fallback().zip(fallback_function.as_ref()))
+    {
+        let Some(n) = request_call_graphs.pipelines.get_index_of(&handler_id) else {
+            continue;
+        };
+        let function = get_pipeline_function(
+            format_ident!("pipeline_{}", n),
+            &pipeline(handler_id, handler),
+            &request_call_graphs.pipelines[n].input_types,
+            runtime_singleton_bindings,
+            &request_scoped_bindings,
+            package_id2name,
+        );
+        pipeline_functions.insert(n, function);
+    }
 
     // TODO: enforce that handlers have the right signature
     // TODO: enforce that the only required input is a Request type of some kind
+    let mut path2handlers: BTreeMap<&str, BTreeMap<MethodGuard, _>> = BTreeMap::new();
+    for (&router_key, handler) in handler_functions.iter() {
+        let handler_id = component_db.router()[router_key];
+        path2handlers
+            .entry(router_key.path.as_str())
+            .or_default()
+            .insert(router_key.method_guard, pipeline(handler_id, handler));
+    }
+    // Each path gets a route id. Requests are then dispatched to the appropriate handler
+    // based on their HTTP method.
     let mut route_id2path = BiBTreeMap::new();
-    let mut route_id2handler = BTreeMap::new();
-    for (route_id, (&route, handler)) in handler_functions.iter().enumerate() {
-        route_id2path.insert(route_id as u32, route.to_owned());
-        route_id2handler.insert(route_id as u32, handler.to_owned());
+    let mut route_id2handlers = BTreeMap::new();
+    for (route_id, (path, handlers)) in path2handlers.into_iter().enumerate() {
+        route_id2path.insert(route_id as u32, path.to_owned());
+        route_id2handlers.insert(route_id as u32, handlers);
     }
 
     let router_init = get_router_init(&route_id2path);
     let route_request = get_request_dispatcher(
-        &route_id2handler,
+        &route_id2handlers,
+        fallback_pipeline.as_ref(),
         runtime_singleton_bindings,
-        request_scoped_framework_bindings,
+        &request_scoped_bindings,
     );
-    let handlers = handler_functions.values().map(|(function, _)| function);
-    let entrypoint = server_startup();
+    let handlers = handler_functions
+        .values()
+        .chain(fallback_function.iter())
+        .chain(middleware_functions.values())
+        .chain(pipeline_preludes.iter())
+        .chain(pipeline_middleware_functions.iter().flatten())
+        .map(|(function, _)| function)
+        .chain(pipeline_functions.values());
+    let shutdown_hook_invocation = component_db.shutdown_hook(computation_db).map(|hook| {
+        get_shutdown_hook_invocation(&hook.callable, runtime_singleton_bindings, package_id2name)
+    });
+    let entrypoint = server_startup(shutdown_hook_invocation);
     let alloc_rename = if package_id2name.contains_right(ALLOC_PACKAGE_ID) {
         quote! { use std as alloc; }
     } else {
@@ -99,28 +235,110 @@ pub(crate) fn codegen_app(
     Ok(code)
 }
 
-fn server_startup() -> ItemFn {
-    syn::parse2(quote! {
+/// Generate the entrypoints of the application:
+///
+/// - `run`, which serves incoming requests until the server fails;
+/// - `run_with_graceful_shutdown`, which stops accepting new connections when the shutdown
+///   signal completes and waits for in-flight requests to be processed before returning.
+///   The shutdown hook, if one was registered, is invoked right before returning.
+/// - `into_service`, which processes requests in memory, without binding a TCP port.
+///   It's primarily meant for testing, via `pavex_runtime::testing::TestClient`.
+fn server_startup(shutdown_hook_invocation: Option<TokenStream>) -> TokenStream {
+    let serve = quote! {
+        let make_service = pavex_runtime::hyper::service::make_service_fn(move |_| {
+            let service = service.clone();
+            async move { Ok::<_, std::convert::Infallible>(service) }
+        });
+        server_builder
+            .serve(make_service)
+            .with_graceful_shutdown(shutdown_signal)
+            .await
+            .map_err(pavex_runtime::Error::new)
+    };
+    let serve = match shutdown_hook_invocation {
+        None => quote! {
+            let service = build_service(server_state);
+            #serve
+        },
+        // The shutdown hook needs access to the application state, therefore we can't move
+        // our handle to the server state into the service.
+        Some(shutdown_hook_invocation) => quote! {
+            let service = build_service(server_state.clone());
+            #serve?;
+            #shutdown_hook_invocation;
+            Ok(())
+        },
+    };
+    quote! {
         pub async fn run(
             server_builder: pavex_runtime::hyper::server::Builder<pavex_runtime::hyper::server::conn::AddrIncoming>,
             application_state: ApplicationState
+        ) -> Result<(), pavex_runtime::Error> {
+            run_with_graceful_shutdown(server_builder, application_state, std::future::pending()).await
+        }
+        pub async fn run_with_graceful_shutdown(
+            server_builder: pavex_runtime::hyper::server::Builder<pavex_runtime::hyper::server::conn::AddrIncoming>,
+            application_state: ApplicationState,
+            shutdown_signal: impl std::future::Future<Output = ()>
         ) -> Result<(), pavex_runtime::Error> {
             let server_state = std::sync::Arc::new(ServerState {
                 router: build_router().map_err(pavex_runtime::Error::new)?,
                 application_state
             });
-            let make_service = pavex_runtime::hyper::service::make_service_fn(move |_| {
-                let server_state = server_state.clone();
-                async move {
-                    Ok::<_, pavex_runtime::hyper::Error>(pavex_runtime::hyper::service::service_fn(move |request| {
-                        let server_state = server_state.clone();
-                        async move { Ok::<_, pavex_runtime::hyper::Error>(route_request(request, server_state).await) }
-                    }))
-                }
+            #serve
+        }
+        pub fn into_service(
+            application_state: ApplicationState
+        ) -> Result<
+            impl pavex_runtime::hyper::service::Service<
+                pavex_runtime::http::Request<pavex_runtime::hyper::body::Body>,
+                Response = pavex_runtime::response::Response,
+                Error = std::convert::Infallible
+            > + Clone,
+            pavex_runtime::Error
+        > {
+            let server_state = std::sync::Arc::new(ServerState {
+                router: build_router().map_err(pavex_runtime::Error::new)?,
+                application_state
             });
-            server_builder.serve(make_service).await.map_err(pavex_runtime::Error::new)
+            Ok(build_service(server_state))
         }
-    }).unwrap()
+        fn build_service(
+            server_state: std::sync::Arc<ServerState>
+        ) -> impl pavex_runtime::hyper::service::Service<
+            pavex_runtime::http::Request<pavex_runtime::hyper::body::Body>,
+            Response = pavex_runtime::response::Response,
+            Error = std::convert::Infallible,
+            Future = impl std::future::Future<
+                Output = Result<pavex_runtime::response::Response, std::convert::Infallible>
+            > + Send
+        > + Clone {
+            pavex_runtime::hyper::service::service_fn(move |request| {
+                let server_state = server_state.clone();
+                async move { Ok::<_, std::convert::Infallible>(route_request(request, server_state).await) }
+            })
+        }
+    }
+}
+
+/// Invoke the shutdown hook, binding its input parameters to the singletons stored in
+/// the application state.
+fn get_shutdown_hook_invocation(
+    shutdown_hook: &Callable,
+    singleton_bindings: &BiHashMap<Ident, ResolvedType>,
+    package_id2name: &BiHashMap<PackageId, String>,
+) -> TokenStream {
+    let variable_bindings = shutdown_hook
+        .inputs
+        .iter()
+        .map(|type_| {
+            // Shutdown hooks have already been verified to take only singletons as input.
+            let binding: Box<dyn ToTokens> =
+                Box::new(get_singleton_binding(type_, singleton_bindings).unwrap());
+            (type_.to_owned(), binding)
+        })
+        .collect();
+    codegen_utils::codegen_call(shutdown_hook, &variable_bindings, package_id2name)
 }
 
 fn define_application_state(
@@ -140,27 +358,59 @@ fn define_application_state(
 }
 
 fn define_application_state_error(
-    error_types: &IndexMap<String, ResolvedType>,
+    error_variants: &IndexMap<String, ApplicationStateErrorVariant>,
     package_id2name: &BiHashMap<PackageId, String>,
-) -> Option<ItemEnum> {
-    if error_types.is_empty() {
+) -> Option<TokenStream> {
+    if error_variants.is_empty() {
         return None;
     }
-    let singleton_fields = error_types.iter().map(|(variant_name, type_)| {
-        let variant_type = type_.syn_type(package_id2name);
-        let variant_name = format_ident!("{}", variant_name);
-        quote! { #variant_name(#variant_type) }
+    let variant_names: Vec<_> = error_variants
+        .keys()
+        .map(|variant_name| format_ident!("{}", variant_name))
+        .collect();
+    let variant_types = error_variants
+        .values()
+        .map(|variant| variant.type_.syn_type(package_id2name));
+    // Error types that don't implement `std::error::Error` can't be exposed as a source.
+    let error_sources =
+        variant_names
+            .iter()
+            .zip(error_variants.values())
+            .map(|(variant_name, variant)| {
+                if variant.implements_error {
+                    quote! { ApplicationStateError::#variant_name(e) => Some(e) }
+                } else {
+                    quote! { ApplicationStateError::#variant_name(_) => None }
+                }
+            });
+    let error_messages = error_variants.values().map(|variant| {
+        format!(
+            "Failed to build the application state: `{}` returned an error",
+            variant.constructor_path
+        )
     });
-    // TODO: implement `Display` + `Error` for `ApplicationStateError`
-    Some(
-        syn::parse2(quote! {
-            #[derive(Debug)]
-            pub enum ApplicationStateError {
-                #(#singleton_fields),*
+    Some(quote! {
+        #[derive(Debug)]
+        pub enum ApplicationStateError {
+            #(#variant_names(#variant_types)),*
+        }
+
+        impl std::fmt::Display for ApplicationStateError {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    #(ApplicationStateError::#variant_names(_) => f.write_str(#error_messages)),*
+                }
             }
-        })
-        .unwrap(),
-    )
+        }
+
+        impl std::error::Error for ApplicationStateError {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                match self {
+                    #(#error_sources),*
+                }
+            }
+        }
+    })
 }
 
 fn define_server_state() -> ItemStruct {
@@ -215,76 +465,381 @@ fn get_router_init(route_id2path: &BiBTreeMap<u32, String>) -> ItemFn {
 }
 
 fn get_request_dispatcher(
-    route_id2handler: &BTreeMap<u32, (ItemFn, IndexSet<ResolvedType>)>,
+    route_id2handlers: &BTreeMap<u32, BTreeMap<MethodGuard, HandlerPipeline>>,
+    fallback: Option<&HandlerPipeline>,
     singleton_bindings: &BiHashMap<Ident, ResolvedType>,
     request_scoped_bindings: &BiHashMap<Ident, ResolvedType>,
 ) -> ItemFn {
-    let mut route_dispatch_table = quote! {};
+    let route_params_type = request_scoped_bindings.get_by_left(&format_ident!("route_params"));
+    let requires_route_params = |pipeline: &HandlerPipeline| {
+        route_params_type.is_some_and(|t| pipeline.input_types().any(|i| strip_reference(i) == t))
+    };
+    let invoke = |pipeline: &HandlerPipeline| match pipeline.prelude {
+        // The prelude builds the shared request-scoped types before invoking the pipeline.
+        Some((prelude, prelude_input_types)) => get_handler_invocation(
+            prelude,
+            prelude_input_types,
+            singleton_bindings,
+            request_scoped_bindings,
+            &BiHashMap::new(),
+        ),
+        None => get_pipeline_invocation(
+            pipeline,
+            singleton_bindings,
+            request_scoped_bindings,
+            &BiHashMap::new(),
+        ),
+    };
 
-    for (route_id, (handler, handler_input_types)) in route_id2handler {
-        let is_handler_async = handler.sig.asyncness.is_some();
-        let handler_function_name = &handler.sig.ident;
-        let input_parameters = handler_input_types.iter().map(|type_| {
-            let mut is_shared_reference = false;
-            let inner_type = match type_ {
-                ResolvedType::Reference(r) => {
-                    if !r.is_static {
-                        is_shared_reference = true;
-                        &r.inner
-                    } else {
-                        type_
+    // Requests that do not match any route are handed over to the fallback handler, if
+    // one was registered, or get a `404 Not Found` response otherwise.
+    let fallback_invocation = match fallback {
+        Some(pipeline) => {
+            let invocation = invoke(pipeline);
+            if requires_route_params(pipeline) {
+                // No route was matched, therefore there are no route parameters to speak of.
+                quote! {
+                    {
+                        let route_params = pavex_runtime::extract::RouteParams::default();
+                        #invocation
                     }
                 }
-                ResolvedType::Slice(_)
-                | ResolvedType::ResolvedPath(_)
-                | ResolvedType::Tuple(_)
-                | ResolvedType::ScalarPrimitive(_) => type_,
-            };
-            if let Some(field_name) = singleton_bindings.get_by_right(inner_type) {
-                if is_shared_reference {
-                    quote! {
-                        &server_state.application_state.#field_name
-                    }
-                } else {
-                    quote! {
-                        server_state.application_state.#field_name.clone()
-                    }
+            } else {
+                invocation
+            }
+        }
+        None => quote! {
+            <pavex_runtime::http::StatusCode as pavex_runtime::response::IntoResponse>::into_response(pavex_runtime::http::StatusCode::NOT_FOUND)
+        },
+    };
+    let mut route_dispatch_table = quote! {};
+
+    for (route_id, handlers) in route_id2handlers {
+        let mut method_dispatch_table = quote! {};
+        let mut allowed_methods = vec![];
+        let mut fallback_invocation = None;
+        for (method_guard, pipeline) in handlers {
+            let handler_invocation = invoke(pipeline);
+            match method_guard.method() {
+                Some(method) => {
+                    let method = format_ident!("{}", method);
+                    method_dispatch_table = quote! {
+                        #method_dispatch_table
+                        &pavex_runtime::http::Method::#method => #handler_invocation,
+                    };
+                    allowed_methods.push(method.to_string());
                 }
-            } else if let Some(field_name) = request_scoped_bindings.get_by_right(type_) {
-                quote! {
-                    #field_name
+                None => {
+                    fallback_invocation = Some(handler_invocation);
                 }
-            } else {
-                let field_name = request_scoped_bindings.get_by_right(&inner_type).unwrap();
+            }
+        }
+        let mut route_dispatch = if allowed_methods.is_empty() {
+            // There is a single handler and it accepts requests with any method.
+            fallback_invocation.expect("There must be at least one handler for each route")
+        } else {
+            let fallback_invocation = fallback_invocation.unwrap_or_else(|| {
+                let allow_header_value = allowed_methods.join(", ");
                 quote! {
-                    #field_name
+                    {
+                        let header_value = pavex_runtime::http::HeaderValue::from_static(#allow_header_value);
+                        let mut response = <pavex_runtime::http::StatusCode as pavex_runtime::response::IntoResponse>::into_response(pavex_runtime::http::StatusCode::METHOD_NOT_ALLOWED);
+                        response.headers_mut().insert(pavex_runtime::http::header::ALLOW, header_value);
+                        response
+                    }
+                }
+            });
+            quote! {
+                match request.method() {
+                    #method_dispatch_table
+                    _ => #fallback_invocation,
                 }
             }
-        });
-        let mut handler_invocation = quote! { #handler_function_name(#(#input_parameters),*) };
-        if is_handler_async {
-            handler_invocation = quote! { #handler_invocation.await };
+        };
+        if handlers.values().any(requires_route_params) {
+            // The route parameters borrow from the request path: we convert them into an
+            // owned type before the request is handed over to the request handler.
+            route_dispatch = quote! {
+                {
+                    let route_params = pavex_runtime::extract::RouteParams::from(route_id.params);
+                    #route_dispatch
+                }
+            };
         }
         route_dispatch_table = quote! {
             #route_dispatch_table
-            #route_id => #handler_invocation,
+            #route_id => #route_dispatch,
         }
     }
 
     syn::parse2(quote! {
         async fn route_request(request: pavex_runtime::http::Request<pavex_runtime::hyper::body::Body>, server_state: std::sync::Arc<ServerState>) -> pavex_runtime::response::Response {
-            let route_id = server_state.router.at(request.uri().path()).expect("Failed to match incoming request path");
+            let route_id = match server_state.router.at(request.uri().path()) {
+                Ok(route_id) => route_id,
+                Err(_) => return #fallback_invocation,
+            };
             match route_id.value {
                 #route_dispatch_table
-                _ => panic!("This is a bug, no route registered for a route id"),
+                _ => #fallback_invocation,
             }
         }
     }).unwrap()
 }
 
+/// A request handler alongside the middlewares that wrap it, from the outermost to the
+/// innermost.
+///
+/// If its layers share request-scoped types, the pipeline is invoked via its `prelude`,
+/// which builds them.
+struct HandlerPipeline<'a> {
+    handler: &'a (ItemFn, IndexSet<ResolvedType>),
+    middlewares: Vec<&'a (ItemFn, IndexSet<ResolvedType>)>,
+    prelude: Option<&'a (ItemFn, IndexSet<ResolvedType>)>,
+}
+
+impl<'a> HandlerPipeline<'a> {
+    /// The middlewares and the request handler, in invocation order.
+    fn layers(&self) -> Vec<&'a (ItemFn, IndexSet<ResolvedType>)> {
+        let mut layers = self.middlewares.clone();
+        layers.push(self.handler);
+        layers
+    }
+
+    /// The types required as input to invoke the pipeline.
+    fn input_types(&self) -> impl Iterator<Item = &'a ResolvedType> {
+        match self.prelude {
+            Some(prelude) => vec![prelude],
+            None => self.layers(),
+        }
+        .into_iter()
+        .flat_map(|(_, input_types)| input_types)
+    }
+}
+
+/// Generate the function that invokes a pipeline whose layers share request-scoped types.
+///
+/// The shared types are built by the prelude and passed as inputs, after the server state,
+/// the incoming request and the route parameters (when needed).
+/// They are kept behind an `Arc` to make them available to the layers invoked via `Next`.
+fn get_pipeline_function(
+    name: Ident,
+    pipeline: &HandlerPipeline,
+    input_types: &[ResolvedType],
+    singleton_bindings: &BiHashMap<Ident, ResolvedType>,
+    request_scoped_bindings: &BiHashMap<Ident, ResolvedType>,
+    package_id2name: &BiHashMap<PackageId, String>,
+) -> ItemFn {
+    let mut shared_bindings = BiHashMap::new();
+    let mut parameters = vec![];
+    let mut shared_values = quote! {};
+    for type_ in input_types {
+        let parameter_name = match request_scoped_bindings.get_by_right(strip_reference(type_)) {
+            Some(parameter_name) => parameter_name.to_owned(),
+            None => {
+                let parameter_name = format_ident!("r{}", shared_bindings.len());
+                shared_values = quote! {
+                    #shared_values
+                    let #parameter_name = std::sync::Arc::new(#parameter_name);
+                };
+                shared_bindings.insert(parameter_name.clone(), type_.to_owned());
+                parameter_name
+            }
+        };
+        let parameter_type = type_.syn_type(package_id2name);
+        parameters.push(quote! { #parameter_name: #parameter_type });
+    }
+    let output = &pipeline.prelude.unwrap().0.sig.output;
+    let invocation = get_pipeline_invocation(
+        pipeline,
+        singleton_bindings,
+        request_scoped_bindings,
+        &shared_bindings,
+    );
+    syn::parse2(quote! {
+        async fn #name(#(#parameters),*) #output {
+            #shared_values
+            #invocation
+        }
+    })
+    .unwrap()
+}
+
+/// Invoke a request handler, nested inside the middlewares that wrap it.
+///
+/// Each middleware receives a `pavex_runtime::middleware::Next` instance that, when run,
+/// invokes the next middleware in the pipeline or, for the innermost one, the request handler.
+///
+/// Shared request-scoped types, if any, are bound to the `Arc`s in `shared_bindings`.
+fn get_pipeline_invocation(
+    pipeline: &HandlerPipeline,
+    singleton_bindings: &BiHashMap<Ident, ResolvedType>,
+    request_scoped_bindings: &BiHashMap<Ident, ResolvedType>,
+    shared_bindings: &BiHashMap<Ident, ResolvedType>,
+) -> TokenStream {
+    let request_type = request_scoped_bindings.get_by_left(&format_ident!("request"));
+    let route_params_type = request_scoped_bindings.get_by_left(&format_ident!("route_params"));
+    let requires = |layers: &[&(ItemFn, IndexSet<ResolvedType>)], t: Option<&ResolvedType>| {
+        t.is_some_and(|t| {
+            layers
+                .iter()
+                .flat_map(|(_, input_types)| input_types)
+                .any(|i| strip_reference(i) == t)
+        })
+    };
+    let requires_singletons = |layers: &[&(ItemFn, IndexSet<ResolvedType>)]| {
+        layers
+            .iter()
+            .flat_map(|(_, input_types)| input_types)
+            .any(|t| singleton_bindings.contains_right(strip_reference(t)))
+    };
+
+    let layers = pipeline.layers();
+    let (handler, handler_input_types) = pipeline.handler;
+    let mut invocation = get_handler_invocation(
+        handler,
+        handler_input_types,
+        singleton_bindings,
+        request_scoped_bindings,
+        shared_bindings,
+    );
+    for (i, (middleware, middleware_input_types)) in pipeline.middlewares.iter().enumerate().rev() {
+        let (outer_layers, inner_layers) = layers.split_at(i + 1);
+        // The next middleware in the pipeline needs the request to build its own `Next`.
+        let is_innermost = i + 1 == pipeline.middlewares.len();
+        let request_parameter = if !is_innermost || requires(inner_layers, request_type) {
+            quote! { request }
+        } else {
+            quote! { _request }
+        };
+        let mut captures = quote! {};
+        if requires_singletons(inner_layers) {
+            captures = quote! {
+                let server_state = server_state.clone();
+            };
+        }
+        // The route parameters can be moved into the closure, unless they are also needed
+        // by the outer layers of the pipeline.
+        if requires(inner_layers, route_params_type) && requires(outer_layers, route_params_type) {
+            captures = quote! {
+                #captures
+                let route_params = route_params.clone();
+            };
+        }
+        for (shared_binding, shared_type) in shared_bindings {
+            if requires(inner_layers, Some(shared_type)) {
+                captures = quote! {
+                    #captures
+                    let #shared_binding = #shared_binding.clone();
+                };
+            }
+        }
+        let mut continuation = quote! {
+            move |#request_parameter| async move { #invocation }
+        };
+        if !captures.is_empty() {
+            continuation = quote! {
+                {
+                    #captures
+                    #continuation
+                }
+            };
+        }
+        let middleware_invocation = get_handler_invocation(
+            middleware,
+            middleware_input_types,
+            singleton_bindings,
+            request_scoped_bindings,
+            shared_bindings,
+        );
+        invocation = quote! {
+            {
+                let next = pavex_runtime::middleware::Next::new(request, #continuation);
+                #middleware_invocation
+            }
+        };
+    }
+    invocation
+}
+
+/// Invoke a request handler, binding its input parameters to the singletons stored in
+/// the application state, to the request-scoped types provided by the framework or to the
+/// request-scoped types shared across the layers of its pipeline.
+///
+/// Shared types are cloned out of their `Arc` if they are taken by value.
+fn get_handler_invocation(
+    handler: &ItemFn,
+    handler_input_types: &IndexSet<ResolvedType>,
+    singleton_bindings: &BiHashMap<Ident, ResolvedType>,
+    request_scoped_bindings: &BiHashMap<Ident, ResolvedType>,
+    shared_bindings: &BiHashMap<Ident, ResolvedType>,
+) -> TokenStream {
+    let is_handler_async = handler.sig.asyncness.is_some();
+    let handler_function_name = &handler.sig.ident;
+    let input_parameters = handler_input_types.iter().map(|type_| {
+        let inner_type = strip_reference(type_);
+        if let Some(binding) = get_singleton_binding(type_, singleton_bindings) {
+            binding
+        } else if let Some(field_name) = request_scoped_bindings.get_by_right(type_) {
+            quote! {
+                #field_name
+            }
+        } else if let Some(field_name) = request_scoped_bindings.get_by_right(inner_type) {
+            quote! {
+                &#field_name
+            }
+        } else if let Some(shared_binding) = shared_bindings.get_by_right(type_) {
+            quote! {
+                (*#shared_binding).clone()
+            }
+        } else {
+            let shared_binding = shared_bindings.get_by_right(inner_type).unwrap();
+            quote! {
+                &*#shared_binding
+            }
+        }
+    });
+    let mut handler_invocation = quote! { #handler_function_name(#(#input_parameters),*) };
+    if is_handler_async {
+        handler_invocation = quote! { #handler_invocation.await };
+    }
+    handler_invocation
+}
+
+/// `T` for `&T`, the type itself otherwise.
+fn strip_reference(type_: &ResolvedType) -> &ResolvedType {
+    match type_ {
+        ResolvedType::Reference(r) if !r.is_static => &r.inner,
+        _ => type_,
+    }
+}
+
+/// Bind an input parameter to the corresponding singleton stored in the application state,
+/// if there is one.
+///
+/// Singletons are cloned if they are taken by value.
+fn get_singleton_binding(
+    type_: &ResolvedType,
+    singleton_bindings: &BiHashMap<Ident, ResolvedType>,
+) -> Option<TokenStream> {
+    match type_ {
+        ResolvedType::Reference(r) if !r.is_static => {
+            let field_name = singleton_bindings.get_by_right(&r.inner)?;
+            Some(quote! {
+                &server_state.application_state.#field_name
+            })
+        }
+        _ => {
+            let field_name = singleton_bindings.get_by_right(type_)?;
+            Some(quote! {
+                server_state.application_state.#field_name.clone()
+            })
+        }
+    }
+}
+
 pub(crate) fn codegen_manifest<'a>(
     package_graph: &guppy::graph::PackageGraph,
-    handler_call_graphs: &'a IndexMap<String, CallGraph>,
+    request_call_graphs: &'a RequestProcessingCallGraphs,
     application_state_call_graph: &'a CallGraph,
     request_scoped_framework_bindings: &'a BiHashMap<Ident, ResolvedType>,
     codegen_types: &'a HashSet<ResolvedType>,
@@ -293,7 +848,7 @@ pub(crate) fn codegen_manifest<'a>(
 ) -> (cargo_manifest::Manifest, BiHashMap<PackageId, String>) {
     let (dependencies, package_ids2deps) = compute_dependencies(
         package_graph,
-        handler_call_graphs,
+        request_call_graphs,
         application_state_call_graph,
         request_scoped_framework_bindings,
         codegen_types,
@@ -352,7 +907,7 @@ pub(crate) fn codegen_manifest<'a>(
 
 fn compute_dependencies<'a>(
     package_graph: &guppy::graph::PackageGraph,
-    handler_call_graphs: &'a IndexMap<String, CallGraph>,
+    request_call_graphs: &'a RequestProcessingCallGraphs,
     application_state_call_graph: &'a CallGraph,
     request_scoped_framework_bindings: &'a BiHashMap<Ident, ResolvedType>,
     codegen_types: &'a HashSet<ResolvedType>,
@@ -360,7 +915,7 @@ fn compute_dependencies<'a>(
     computation_db: &'a ComputationDb,
 ) -> (BTreeMap<String, Dependency>, BiHashMap<PackageId, String>) {
     let package_ids = collect_package_ids(
-        handler_call_graphs,
+        request_call_graphs,
         application_state_call_graph,
         request_scoped_framework_bindings,
         codegen_types,
@@ -430,7 +985,7 @@ fn compute_dependencies<'a>(
 }
 
 fn collect_package_ids<'a>(
-    handler_call_graphs: &'a IndexMap<String, CallGraph>,
+    request_call_graphs: &'a RequestProcessingCallGraphs,
     application_state_call_graph: &'a CallGraph,
     request_scoped_framework_bindings: &'a BiHashMap<Ident, ResolvedType>,
     codegen_types: &'a HashSet<ResolvedType>,
@@ -450,13 +1005,11 @@ fn collect_package_ids<'a>(
         computation_db,
         application_state_call_graph,
     );
-    for handler_call_graph in handler_call_graphs.values() {
-        collect_call_graph_package_ids(
-            &mut package_ids,
-            component_db,
-            computation_db,
-            handler_call_graph,
-        );
+    for call_graph in request_call_graphs.iter() {
+        collect_call_graph_package_ids(&mut package_ids, component_db, computation_db, call_graph);
+    }
+    if let Some(shutdown_hook) = component_db.shutdown_hook(computation_db) {
+        collect_callable_package_ids(&mut package_ids, &shutdown_hook.callable);
     }
     package_ids
 }
@@ -488,9 +1041,15 @@ fn collect_call_graph_package_ids<'a>(
                     HydratedComponent::RequestHandler(r) => {
                         collect_callable_package_ids(package_ids, &r.callable);
                     }
+                    HydratedComponent::Middleware(m) => {
+                        collect_callable_package_ids(package_ids, &m.callable);
+                    }
                     HydratedComponent::ErrorHandler(e) => {
                         collect_callable_package_ids(package_ids, &e.callable)
                     }
+                    HydratedComponent::ErrorObserver(o) => {
+                        collect_callable_package_ids(package_ids, &o.callable)
+                    }
                 }
             }
             CallGraphNode::InputParameter(t) => collect_type_package_ids(package_ids, t),
@@ -530,5 +1089,6 @@ fn collect_type_package_ids(package_ids: &mut IndexSet<PackageId>, t: &ResolvedT
         ResolvedType::Slice(s) => {
             collect_type_package_ids(package_ids, &s.element_type);
         }
+        ResolvedType::Generic(_) => {}
     }
 }
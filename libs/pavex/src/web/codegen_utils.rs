@@ -137,9 +137,53 @@ pub(crate) fn codegen_call(
                 }
             }
         }
+        InvocationStyle::StartupGuard {
+            policy,
+            is_async,
+            is_fallible,
+        } => {
+            let parameters = callable.inputs.iter().map(|i| {
+                let binding = &variable_bindings[i];
+                // Each attempt needs its own copy of the inputs that are taken by value.
+                if policy.max_retries > 0 && !matches!(i, ResolvedType::Reference(_)) {
+                    quote! { #binding.clone() }
+                } else {
+                    quote! { #binding }
+                }
+            });
+            let mut attempt = quote! { #callable_path(#(#parameters),*) };
+            if !is_async {
+                attempt = quote! { std::future::ready(#attempt) };
+            }
+            let timeout = policy.timeout.map(codegen_duration);
+            if policy.max_retries > 0 {
+                let max_retries = policy.max_retries;
+                let backoff = codegen_duration(policy.backoff);
+                let timeout = match timeout {
+                    Some(timeout) => quote! { Some(#timeout) },
+                    None => quote! { None },
+                };
+                quote! {
+                    pavex_runtime::startup::retry(#max_retries, #backoff, #timeout, || #attempt)
+                }
+            } else {
+                let timeout = timeout.expect("A startup policy without retries must set a timeout");
+                if *is_fallible {
+                    quote! { pavex_runtime::startup::try_with_timeout(#timeout, #attempt) }
+                } else {
+                    quote! { pavex_runtime::startup::with_timeout(#timeout, #attempt) }
+                }
+            }
+        }
     };
     if callable.is_async {
         invocation = quote! { #invocation.await };
     }
     invocation
 }
+
+fn codegen_duration(duration: std::time::Duration) -> TokenStream {
+    let seconds = duration.as_secs();
+    let nanoseconds = duration.subsec_nanos();
+    quote! { std::time::Duration::new(#seconds, #nanoseconds) }
+}
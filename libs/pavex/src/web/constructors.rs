@@ -1,5 +1,7 @@
 use std::borrow::Cow;
 
+use indexmap::IndexSet;
+
 use crate::language::ResolvedType;
 use crate::web::computation::{Computation, MatchResult};
 use crate::web::utils::is_result;
@@ -32,6 +34,24 @@ impl<'a> TryFrom<Computation<'a>> for Constructor<'a> {
                 return Err(ConstructorValidationError::CannotFalliblyReturnTheUnitType);
             }
         }
+        // We infer the generic parameters of a constructor using the type it is asked to build,
+        // therefore all of them must appear in its output type.
+        let output_parameters = output_type.unassigned_generic_type_parameters();
+        let mut underconstrained_parameters = IndexSet::new();
+        for input_type in c.input_types().iter() {
+            for parameter in input_type.unassigned_generic_type_parameters() {
+                if !output_parameters.contains(&parameter) {
+                    underconstrained_parameters.insert(parameter);
+                }
+            }
+        }
+        if !underconstrained_parameters.is_empty() {
+            return Err(
+                ConstructorValidationError::UnderconstrainedGenericParameters {
+                    parameters: underconstrained_parameters,
+                },
+            );
+        }
         Ok(Constructor(c))
     }
 }
@@ -101,6 +121,8 @@ pub(crate) enum ConstructorValidationError {
     CannotReturnTheUnitType,
     #[error("All fallible constructors must return *something* when successful.\nThis fallible constructor doesn't: it returns the unit type when successful, `Ok(())`.")]
     CannotFalliblyReturnTheUnitType,
+    #[error("I can only infer the generic parameters of a constructor from its output type.\nThe generic parameters of this constructor that don't appear in its output type cannot be inferred: {}.", parameters.iter().map(|p| format!("`{p}`")).collect::<Vec<_>>().join(", "))]
+    UnderconstrainedGenericParameters { parameters: IndexSet<String> },
 }
 
 #[derive(thiserror::Error, Debug, Clone)]
@@ -0,0 +1,63 @@
+use std::borrow::Cow;
+
+use crate::language::{Callable, ResolvedType};
+
+/// The output type of all error observers.
+static UNIT_TYPE: ResolvedType = ResolvedType::UNIT_TYPE;
+
+/// A callable invoked every time a fallible component fails, before the error is converted into
+/// a response by the corresponding error handler.
+/// It takes a reference to the error as its first input parameter and it must return the
+/// unit type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ErrorObserver<'a> {
+    pub(crate) callable: Cow<'a, Callable>,
+}
+
+impl<'a> ErrorObserver<'a> {
+    pub fn new(c: Cow<'a, Callable>) -> Result<Self, ErrorObserverValidationError> {
+        if let Some(output_type) = c.output.as_ref() {
+            return Err(ErrorObserverValidationError::MustReturnTheUnitType {
+                output_type: output_type.to_owned(),
+            });
+        }
+        match c.inputs.first() {
+            Some(ResolvedType::Reference(r)) if !r.is_mutable => {}
+            _ => return Err(ErrorObserverValidationError::MustTakeErrorReferenceAsFirstInput),
+        }
+        Ok(Self { callable: c })
+    }
+
+    /// Return the type of the errors that this error observer can be invoked with.
+    ///
+    /// The error observer takes a **reference** to it as its first input parameter.
+    pub fn error_type(&self) -> &ResolvedType {
+        let ResolvedType::Reference(r) = &self.callable.inputs[0] else {
+            unreachable!()
+        };
+        &r.inner
+    }
+
+    pub fn output_type(&self) -> &ResolvedType {
+        &UNIT_TYPE
+    }
+
+    pub fn input_types(&self) -> &[ResolvedType] {
+        self.callable.inputs.as_slice()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub(crate) enum ErrorObserverValidationError {
+    #[error(
+        "Error observers must return the unit type, `()`.\n\
+        This error observer doesn't: it returns `{output_type:?}`."
+    )]
+    MustReturnTheUnitType { output_type: ResolvedType },
+    #[error(
+        "Error observers must take a reference to the error they observe as their first \
+        input parameter.\n\
+        This error observer doesn't."
+    )]
+    MustTakeErrorReferenceAsFirstInput,
+}
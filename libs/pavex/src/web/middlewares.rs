@@ -0,0 +1,64 @@
+use std::borrow::Cow;
+
+use crate::language::{Callable, ResolvedType};
+use crate::web::utils::is_result;
+
+/// A callable that wraps the invocation of a request handler.
+/// It takes the rest of the request processing pipeline as input (`pavex_runtime::middleware::Next`)
+/// and it must return a type that implements `pavex_runtime::response::IntoResponse`.
+/// Middlewares cannot be fallible.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct Middleware<'a> {
+    pub(crate) callable: Cow<'a, Callable>,
+}
+
+impl<'a> Middleware<'a> {
+    pub fn new(
+        c: Cow<'a, Callable>,
+        next_type: &ResolvedType,
+    ) -> Result<Self, MiddlewareValidationError> {
+        let Some(output) = c.output.as_ref() else {
+            return Err(MiddlewareValidationError::CannotReturnTheUnitType);
+        };
+        if is_result(output) {
+            return Err(MiddlewareValidationError::CannotBeFallible);
+        }
+        let n_next_inputs = c.inputs.iter().filter(|&i| i == next_type).count();
+        if n_next_inputs != 1 {
+            return Err(MiddlewareValidationError::MustTakeNextAsInputOnce {
+                next_type: next_type.to_owned(),
+            });
+        }
+        Ok(Self { callable: c })
+    }
+
+    pub fn output_type(&self) -> &ResolvedType {
+        self.callable.output.as_ref().unwrap()
+    }
+
+    pub fn input_types(&self) -> &[ResolvedType] {
+        self.callable.inputs.as_slice()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub(crate) enum MiddlewareValidationError {
+    #[error(
+        "All middlewares must return a type that can be converted into a \
+        `pavex_runtime::response::Response`.\n\
+        This middleware doesn't: it returns the unit type, `()`."
+    )]
+    CannotReturnTheUnitType,
+    #[error(
+        "Middlewares cannot be fallible: they must return a type that can be converted into a \
+        `pavex_runtime::response::Response`.\n\
+        This middleware returns a `Result`."
+    )]
+    CannotBeFallible,
+    #[error(
+        "Middlewares must take the rest of the request processing pipeline, `{next_type:?}`, \
+        as input, exactly once.\n\
+        This middleware doesn't."
+    )]
+    MustTakeNextAsInputOnce { next_type: ResolvedType },
+}
@@ -1,4 +1,4 @@
-pub use app::App;
+pub use app::{compute_package_graph, App};
 
 mod analyses;
 mod app;
@@ -7,9 +7,13 @@ mod codegen_utils;
 mod computation;
 mod constructors;
 mod error_handlers;
+mod error_observers;
 mod generated_app;
 mod interner;
+mod middlewares;
 mod request_handlers;
 mod resolvers;
+mod shutdown_hooks;
+mod startup_policies;
 mod traits;
 mod utils;
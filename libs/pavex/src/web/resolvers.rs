@@ -7,15 +7,17 @@ use std::sync::Arc;
 use ahash::{HashMap, HashMapExt};
 use anyhow::anyhow;
 use guppy::PackageId;
-use rustdoc_types::{GenericArg, GenericArgs, GenericParamDefKind, ItemEnum, Type};
+use indexmap::IndexSet;
+use rustdoc_types::{GenericArg, GenericArgs, GenericParamDefKind, Generics, Impl, ItemEnum, Type};
 
 use crate::language::{
-    Callable, GenericArgument, InvocationStyle, Lifetime, ResolvedPath,
+    Callable, Generic, GenericArgument, InvocationStyle, Lifetime, ResolvedPath,
     ResolvedPathGenericArgument, ResolvedPathLifetime, ResolvedPathType, ResolvedType, Slice,
-    Tuple, TypeReference, UnknownPath,
+    Tuple, TypeReference, UnassignedGenericParameter, UnknownPath,
 };
 use crate::rustdoc::{CannotGetCrateData, RustdocKindExt};
-use crate::rustdoc::{CrateCollection, ResolvedItem};
+use crate::rustdoc::{CrateCollection, GlobalItemId, ResolvedItem, ResolvedItemWithParent};
+use crate::web::traits::{implements_trait, resolve_trait_bound, trait_bounds};
 
 pub(crate) fn resolve_type(
     type_: &Type,
@@ -184,8 +186,13 @@ pub(crate) fn resolve_callable(
     let (callable_type, qualified_self_type) =
         callable_path.find_rustdoc_items(krate_collection)?;
     let used_by_package_id = &callable_path.package_id;
-    let (header, decl, invocation_style) = match &callable_type.item.item.inner {
-        ItemEnum::Function(f) => (&f.header, &f.decl, InvocationStyle::FunctionCall),
+    let (header, decl, generics, invocation_style) = match &callable_type.item.item.inner {
+        ItemEnum::Function(f) => (
+            &f.header,
+            &f.decl,
+            &f.generics,
+            InvocationStyle::FunctionCall,
+        ),
         kind => {
             let item_kind = kind.kind().to_owned();
             return Err(UnsupportedCallableKind {
@@ -200,6 +207,25 @@ pub(crate) fn resolve_callable(
     if let Some(qself) = qualified_self_type {
         generic_bindings.insert("Self".to_string(), qself);
     }
+    bind_impl_generic_parameters(
+        krate_collection,
+        callable_path,
+        &callable_type,
+        &mut generic_bindings,
+    )?;
+    bind_callable_generic_parameters(
+        krate_collection,
+        callable_path,
+        generics,
+        &mut generic_bindings,
+    )
+    .map_err(|e| *e)?;
+    bind_unassigned_generic_parameters(
+        krate_collection,
+        &callable_type,
+        generics,
+        &mut generic_bindings,
+    );
 
     let mut parameter_paths = Vec::with_capacity(decl.inputs.len());
     for (parameter_index, (_, parameter_type)) in decl.inputs.iter().enumerate() {
@@ -255,6 +281,406 @@ pub(crate) fn resolve_callable(
     Ok(callable)
 }
 
+/// If `callable_path` points at a method defined in an inherent `impl` block of a generic
+/// struct or enum (e.g. `impl<T> QueryParams<T> { .. }`), bind the generic parameters of
+/// the `impl` block to the generic arguments that were specified by the user on the type
+/// segment of the path (e.g. `QueryParams::<crate::MyQuery>::extract`).
+///
+/// Generic parameters for which the user did not specify a concrete type are left unbound.
+fn bind_impl_generic_parameters(
+    krate_collection: &CrateCollection,
+    callable_path: &ResolvedPath,
+    callable_type: &ResolvedItemWithParent,
+    generic_bindings: &mut HashMap<String, ResolvedType>,
+) -> Result<(), GenericArgumentResolutionError> {
+    let Some(parent_segment) = callable_path
+        .segments
+        .len()
+        .checked_sub(2)
+        .map(|i| &callable_path.segments[i])
+    else {
+        return Ok(());
+    };
+    let Some(impl_block) = find_impl_block(krate_collection, callable_type) else {
+        return Ok(());
+    };
+    let Type::ResolvedPath(rustdoc_types::Path {
+        args: Some(args), ..
+    }) = &impl_block.for_
+    else {
+        return Ok(());
+    };
+    let GenericArgs::AngleBracketed { args, .. } = &**args else {
+        return Ok(());
+    };
+    let mut user_arguments = parent_segment
+        .generic_arguments
+        .iter()
+        .filter(|arg| matches!(arg, ResolvedPathGenericArgument::Type(_)));
+    for arg in args {
+        let GenericArg::Type(impl_arg) = arg else {
+            continue;
+        };
+        let Some(ResolvedPathGenericArgument::Type(user_arg)) = user_arguments.next() else {
+            return Ok(());
+        };
+        if let Type::Generic(parameter_name) = impl_arg {
+            let resolved_arg =
+                user_arg
+                    .resolve(krate_collection)
+                    .map_err(|e| GenericArgumentResolutionError {
+                        callable_path: callable_path.to_owned(),
+                        generic_argument: user_arg.to_string(),
+                        source: Arc::new(e),
+                    })?;
+            generic_bindings.insert(parameter_name.to_owned(), resolved_arg);
+        }
+    }
+    Ok(())
+}
+
+/// Bind the generic parameters of the callable itself to the generic arguments that were
+/// specified by the user, via turbofish, on the last segment of the path
+/// (e.g. `crate::stream_file::<std::path::PathBuf>`).
+///
+/// If no generic argument has been specified, all generic parameters are left unbound.
+/// Otherwise, the user must specify a concrete type for each of them.
+fn bind_callable_generic_parameters(
+    krate_collection: &CrateCollection,
+    callable_path: &ResolvedPath,
+    generics: &Generics,
+    generic_bindings: &mut HashMap<String, ResolvedType>,
+) -> Result<(), Box<CallableResolutionError>> {
+    let Some(last_segment) = callable_path.segments.last() else {
+        return Ok(());
+    };
+    // Generic parameters introduced by the compiler (e.g. for `impl Trait` in argument
+    // position) cannot be specified via turbofish.
+    let parameters: Vec<_> = generics
+        .params
+        .iter()
+        .filter(|p| {
+            matches!(
+                p.kind,
+                GenericParamDefKind::Type {
+                    synthetic: false,
+                    ..
+                }
+            )
+        })
+        .collect();
+    let user_arguments: Vec<_> = last_segment
+        .generic_arguments
+        .iter()
+        .filter_map(|arg| match arg {
+            ResolvedPathGenericArgument::Type(t) => Some(t),
+            ResolvedPathGenericArgument::Lifetime(_) => None,
+        })
+        .collect();
+    if user_arguments.is_empty() {
+        return Ok(());
+    }
+    if user_arguments.len() != parameters.len() {
+        return Err(Box::new(
+            GenericArgumentCountMismatch {
+                callable_path: callable_path.to_owned(),
+                n_parameters: parameters.len(),
+                n_arguments: user_arguments.len(),
+            }
+            .into(),
+        ));
+    }
+    for (parameter, user_arg) in parameters.into_iter().zip(user_arguments) {
+        let resolved_arg = user_arg.resolve(krate_collection).map_err(|e| {
+            Box::new(
+                GenericArgumentResolutionError {
+                    callable_path: callable_path.to_owned(),
+                    generic_argument: user_arg.to_string(),
+                    source: Arc::new(e),
+                }
+                .into(),
+            )
+        })?;
+        generic_bindings.insert(parameter.name.to_owned(), resolved_arg);
+    }
+    Ok(())
+}
+
+/// Bind the generic type parameters of the callable (and of the inherent `impl` block it
+/// belongs to, if any) that have not been assigned a concrete type by the user to
+/// [`ResolvedType::Generic`] placeholders.
+///
+/// The resulting callable is a template: a concrete type for each placeholder must be
+/// inferred (see [`instantiate_callable_path`]) before it can be invoked.
+///
+/// Generic parameters introduced by the compiler (e.g. for `impl Trait` in argument position)
+/// and the generic parameters of trait `impl` blocks are left unbound.
+fn bind_unassigned_generic_parameters(
+    krate_collection: &CrateCollection,
+    callable_type: &ResolvedItemWithParent,
+    generics: &Generics,
+    generic_bindings: &mut HashMap<String, ResolvedType>,
+) {
+    let impl_generics = find_impl_block(krate_collection, callable_type)
+        .filter(|impl_block| impl_block.trait_.is_none())
+        .map(|impl_block| &impl_block.generics);
+    for parameter in generics
+        .params
+        .iter()
+        .chain(impl_generics.into_iter().flat_map(|g| g.params.iter()))
+    {
+        if let GenericParamDefKind::Type {
+            synthetic: false, ..
+        } = parameter.kind
+        {
+            generic_bindings
+                .entry(parameter.name.to_owned())
+                .or_insert_with(|| {
+                    ResolvedType::Generic(Generic {
+                        name: parameter.name.to_owned(),
+                    })
+                });
+        }
+    }
+}
+
+/// If `callable_type` is a method, find the `impl` block it was defined in.
+fn find_impl_block<'a>(
+    krate_collection: &'a CrateCollection,
+    callable_type: &ResolvedItemWithParent,
+) -> Option<&'a Impl> {
+    let parent = callable_type.parent.as_ref()?;
+    let impl_ids = match &parent.item.inner {
+        ItemEnum::Struct(s) => &s.impls,
+        ItemEnum::Enum(e) => &e.impls,
+        _ => return None,
+    };
+    let method_id = &callable_type.item.item_id.rustdoc_item_id;
+    impl_ids.iter().find_map(|impl_id| {
+        let impl_item = krate_collection.get_type_by_global_type_id(&GlobalItemId {
+            rustdoc_item_id: impl_id.to_owned(),
+            package_id: parent.item_id.package_id.clone(),
+        });
+        match &impl_item.inner {
+            ItemEnum::Impl(impl_block) if impl_block.items.contains(method_id) => Some(impl_block),
+            _ => None,
+        }
+    })
+}
+
+/// Assign a concrete type to the unassigned generic parameters of a template callable
+/// (see [`bind_unassigned_generic_parameters`]).
+///
+/// `inferred_bindings` maps the name of each unassigned generic parameter to the concrete
+/// type it should be replaced with. The trait bounds on the generic parameters are checked
+/// against the inferred types.
+///
+/// It returns the path to the instantiated callable, where the concrete type of every generic
+/// parameter is specified via turbofish (e.g. `crate::extract_json::<crate::MyBody>`).
+pub(crate) fn instantiate_callable_path(
+    krate_collection: &CrateCollection,
+    template_path: &ResolvedPath,
+    inferred_bindings: &HashMap<String, ResolvedType>,
+) -> Result<ResolvedPath, Box<InstantiationError>> {
+    let cannot_resolve_template = |e: CallableResolutionError| {
+        Box::new(InstantiationError::CannotResolveTemplate {
+            template_path: template_path.to_owned(),
+            source: Box::new(e),
+        })
+    };
+    let (callable_type, qualified_self_type) = template_path
+        .find_rustdoc_items(krate_collection)
+        .map_err(|e| cannot_resolve_template(e.into()))?;
+    let ItemEnum::Function(function) = &callable_type.item.item.inner else {
+        let item_kind = callable_type.item.item.inner.kind().to_owned();
+        return Err(cannot_resolve_template(
+            UnsupportedCallableKind {
+                import_path: template_path.to_owned(),
+                item_kind,
+            }
+            .into(),
+        ));
+    };
+    let mut generic_bindings = HashMap::new();
+    if let Some(qself) = qualified_self_type {
+        generic_bindings.insert("Self".to_string(), qself);
+    }
+    bind_impl_generic_parameters(
+        krate_collection,
+        template_path,
+        &callable_type,
+        &mut generic_bindings,
+    )
+    .map_err(|e| Box::new(e.into()))?;
+    bind_callable_generic_parameters(
+        krate_collection,
+        template_path,
+        &function.generics,
+        &mut generic_bindings,
+    )
+    .map_err(|e| cannot_resolve_template(*e))?;
+    generic_bindings.extend(
+        inferred_bindings
+            .iter()
+            .map(|(name, type_)| (name.to_owned(), type_.to_owned())),
+    );
+
+    let impl_block = find_impl_block(krate_collection, &callable_type)
+        .filter(|impl_block| impl_block.trait_.is_none());
+    let mut generics = vec![(&function.generics, &callable_type.item.item_id.package_id)];
+    if let (Some(impl_block), Some(parent)) = (impl_block, &callable_type.parent) {
+        generics.push((&impl_block.generics, &parent.item_id.package_id));
+    }
+
+    // Each generic type parameter must be assigned a concrete type, either explicitly or via
+    // inference, before the callable can be invoked.
+    let unassigned_parameters: IndexSet<String> = generics
+        .iter()
+        .flat_map(|(generics, _)| generics.params.iter())
+        .filter(|p| {
+            matches!(
+                p.kind,
+                GenericParamDefKind::Type {
+                    synthetic: false,
+                    ..
+                }
+            )
+        })
+        .filter(|p| match generic_bindings.get(&p.name) {
+            Some(t) => !t.unassigned_generic_type_parameters().is_empty(),
+            None => true,
+        })
+        .map(|p| p.name.to_owned())
+        .collect();
+    if !unassigned_parameters.is_empty() {
+        return Err(Box::new(
+            UnassignedGenericParameters {
+                template_path: template_path.to_owned(),
+                parameters: unassigned_parameters,
+            }
+            .into(),
+        ));
+    }
+
+    for (generics, used_by_package_id) in &generics {
+        for parameter in &generics.params {
+            let Some(type_) = inferred_bindings.get(&parameter.name) else {
+                continue;
+            };
+            for bound in trait_bounds(generics, &parameter.name) {
+                let trait_ = match resolve_type(
+                    &Type::ResolvedPath(bound.to_owned()),
+                    used_by_package_id,
+                    krate_collection,
+                    &generic_bindings,
+                ) {
+                    Ok(ResolvedType::ResolvedPath(trait_)) => trait_,
+                    // We might fail to resolve the generic arguments of the trait (e.g. `'de` in
+                    // `T: for<'de> serde::Deserialize<'de>`), but we only need its path.
+                    _ => match resolve_trait_bound(bound, used_by_package_id, krate_collection) {
+                        Ok(trait_) => trait_,
+                        Err(e) => {
+                            tracing::trace!(
+                                "Failed to resolve the trait bound {:?} on `{}`: {:?}",
+                                bound,
+                                parameter.name,
+                                e
+                            );
+                            continue;
+                        }
+                    },
+                };
+                if !implements_trait(krate_collection, type_, &trait_) {
+                    return Err(Box::new(
+                        UnsatisfiedTraitBound {
+                            template_path: template_path.to_owned(),
+                            parameter: parameter.name.to_owned(),
+                            type_: type_.to_owned(),
+                            trait_: trait_.into(),
+                        }
+                        .into(),
+                    ));
+                }
+            }
+        }
+    }
+
+    let unassigned = |e: UnassignedGenericParameter| {
+        Box::new(InstantiationError::from(UnassignedGenericParameters {
+            template_path: template_path.to_owned(),
+            parameters: IndexSet::from([e.name]),
+        }))
+    };
+    let mut instantiated_path = template_path.to_owned();
+    let n_segments = instantiated_path.segments.len();
+    if let (Some(impl_block), Some(parent), Some(parent_segment)) = (
+        impl_block,
+        &callable_type.parent,
+        n_segments
+            .checked_sub(2)
+            .map(|i| &mut instantiated_path.segments[i]),
+    ) {
+        if let Type::ResolvedPath(rustdoc_types::Path {
+            args: Some(args), ..
+        }) = &impl_block.for_
+        {
+            if let GenericArgs::AngleBracketed { args, .. } = &**args {
+                let used_by_package_id = &parent.item_id.package_id;
+                parent_segment.generic_arguments = args
+                    .iter()
+                    .filter_map(|arg| match arg {
+                        GenericArg::Type(t) => Some(t),
+                        _ => None,
+                    })
+                    .map(|t| {
+                        let resolved = resolve_type(
+                            t,
+                            used_by_package_id,
+                            krate_collection,
+                            &generic_bindings,
+                        )
+                        .map_err(|e| {
+                            InstantiationError::from(GenericArgumentResolutionError {
+                                callable_path: template_path.to_owned(),
+                                generic_argument: match t {
+                                    Type::Generic(name) => name.to_owned(),
+                                    Type::ResolvedPath(p) => p.name.to_owned(),
+                                    _ => "_".to_owned(),
+                                },
+                                source: Arc::new(e),
+                            })
+                        })?;
+                        let resolved = resolved.try_into().map_err(unassigned)?;
+                        Ok(ResolvedPathGenericArgument::Type(resolved))
+                    })
+                    .collect::<Result<_, Box<InstantiationError>>>()?;
+            }
+        }
+    }
+    if let Some(last_segment) = instantiated_path.segments.last_mut() {
+        last_segment.generic_arguments = function
+            .generics
+            .params
+            .iter()
+            .filter(|p| {
+                matches!(
+                    p.kind,
+                    GenericParamDefKind::Type {
+                        synthetic: false,
+                        ..
+                    }
+                )
+            })
+            .filter_map(|p| generic_bindings.get(&p.name))
+            .map(|t| {
+                let t = t.to_owned().try_into().map_err(unassigned)?;
+                Ok(ResolvedPathGenericArgument::Type(t))
+            })
+            .collect::<Result<_, Box<InstantiationError>>>()?;
+    }
+    Ok(instantiated_path)
+}
+
 pub(crate) fn resolve_type_path(
     path: &ResolvedPath,
     resolved_item: &ResolvedItem,
@@ -300,6 +726,10 @@ pub(crate) enum CallableResolutionError {
     OutputTypeResolutionError(#[from] OutputTypeResolutionError),
     #[error(transparent)]
     CannotGetCrateData(#[from] CannotGetCrateData),
+    #[error(transparent)]
+    GenericArgumentResolutionError(#[from] GenericArgumentResolutionError),
+    #[error(transparent)]
+    GenericArgumentCountMismatch(#[from] GenericArgumentCountMismatch),
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
@@ -307,6 +737,9 @@ pub(crate) enum CallableType {
     RequestHandler,
     Constructor,
     ErrorHandler,
+    ErrorObserver,
+    Middleware,
+    ShutdownHook,
 }
 
 impl Display for CallableType {
@@ -315,6 +748,9 @@ impl Display for CallableType {
             CallableType::RequestHandler => "request handler",
             CallableType::Constructor => "constructor",
             CallableType::ErrorHandler => "error handler",
+            CallableType::ErrorObserver => "error observer",
+            CallableType::Middleware => "middleware",
+            CallableType::ShutdownHook => "shutdown hook",
         };
         write!(f, "{s}")
     }
@@ -338,6 +774,109 @@ pub(crate) struct ParameterResolutionError {
     pub source: Arc<anyhow::Error>,
 }
 
+#[derive(Debug, thiserror::Error, Clone)]
+#[error(
+    "`{type_:?}` does not implement the `{trait_:?}` trait, which is required by the generic \
+    parameter `{parameter}` of `{template_path}`."
+)]
+pub(crate) struct UnsatisfiedTraitBound {
+    pub template_path: ResolvedPath,
+    pub parameter: String,
+    pub type_: ResolvedType,
+    pub trait_: ResolvedType,
+}
+
+/// The error returned by [`instantiate_callable_path`].
+#[derive(thiserror::Error, Debug, Clone)]
+pub(crate) enum InstantiationError {
+    #[error(transparent)]
+    UnsatisfiedTraitBound(#[from] UnsatisfiedTraitBound),
+    #[error(transparent)]
+    UnassignedGenericParameters(#[from] UnassignedGenericParameters),
+    #[error(transparent)]
+    GenericArgumentResolutionError(#[from] GenericArgumentResolutionError),
+    #[error("{source}")]
+    CannotResolveTemplate {
+        template_path: ResolvedPath,
+        #[source]
+        source: Box<CallableResolutionError>,
+    },
+}
+
+impl InstantiationError {
+    /// The path of the template callable that we failed to instantiate.
+    pub fn template_path(&self) -> &ResolvedPath {
+        match self {
+            InstantiationError::UnsatisfiedTraitBound(e) => &e.template_path,
+            InstantiationError::UnassignedGenericParameters(e) => &e.template_path,
+            InstantiationError::GenericArgumentResolutionError(e) => &e.callable_path,
+            InstantiationError::CannotResolveTemplate { template_path, .. } => template_path,
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error, Clone)]
+#[error(
+    "I cannot infer the concrete type of {} of `{template_path}` from the type it is asked \
+    to build.",
+    render_generic_parameters(parameters)
+)]
+pub(crate) struct UnassignedGenericParameters {
+    pub template_path: ResolvedPath,
+    pub parameters: IndexSet<String>,
+}
+
+fn render_generic_parameters(parameters: &IndexSet<String>) -> String {
+    let names = parameters
+        .iter()
+        .map(|p| format!("`{p}`"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if parameters.len() == 1 {
+        format!("the generic parameter {names}")
+    } else {
+        format!("the generic parameters {names}")
+    }
+}
+
+#[derive(Debug, thiserror::Error, Clone)]
+#[error(
+    "I cannot resolve `{generic_argument}`, one of the generic arguments of `{callable_path}`."
+)]
+pub(crate) struct GenericArgumentResolutionError {
+    pub callable_path: ResolvedPath,
+    pub generic_argument: String,
+    #[source]
+    pub source: Arc<anyhow::Error>,
+}
+
+#[derive(Debug, thiserror::Error, Clone)]
+pub(crate) struct GenericArgumentCountMismatch {
+    pub callable_path: ResolvedPath,
+    pub n_parameters: usize,
+    pub n_arguments: usize,
+}
+
+impl Display for GenericArgumentCountMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let parameters = if self.n_parameters == 1 {
+            "generic parameter"
+        } else {
+            "generic parameters"
+        };
+        let arguments = if self.n_arguments == 1 {
+            "generic argument was"
+        } else {
+            "generic arguments were"
+        };
+        write!(
+            f,
+            "`{}` has {} {parameters}, but {} {arguments} specified via turbofish.",
+            self.callable_path, self.n_parameters, self.n_arguments
+        )
+    }
+}
+
 #[derive(Debug, thiserror::Error, Clone)]
 #[error("I do not know how to handle the type returned by `{callable_path}`.")]
 pub(crate) struct OutputTypeResolutionError {
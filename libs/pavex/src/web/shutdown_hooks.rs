@@ -0,0 +1,35 @@
+use std::borrow::Cow;
+
+use crate::language::{Callable, ResolvedType};
+
+/// A callable invoked when the server shuts down gracefully, after all in-flight requests
+/// have been processed.
+/// It can only take singletons as input and it must return the unit type.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct ShutdownHook<'a> {
+    pub(crate) callable: Cow<'a, Callable>,
+}
+
+impl<'a> ShutdownHook<'a> {
+    pub fn new(c: Cow<'a, Callable>) -> Result<Self, ShutdownHookValidationError> {
+        if let Some(output_type) = c.output.as_ref() {
+            return Err(ShutdownHookValidationError::MustReturnTheUnitType {
+                output_type: output_type.to_owned(),
+            });
+        }
+        Ok(Self { callable: c })
+    }
+
+    pub fn input_types(&self) -> &[ResolvedType] {
+        self.callable.inputs.as_slice()
+    }
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub(crate) enum ShutdownHookValidationError {
+    #[error(
+        "Shutdown hooks must return the unit type, `()`.\n\
+        This shutdown hook doesn't: it returns `{output_type:?}`."
+    )]
+    MustReturnTheUnitType { output_type: ResolvedType },
+}
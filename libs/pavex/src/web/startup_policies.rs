@@ -0,0 +1,140 @@
+use guppy::graph::PackageGraph;
+use guppy::PackageId;
+
+use pavex_builder::{Lifecycle, StartupPolicy};
+
+use crate::language::{Callable, GenericArgument, InvocationStyle, ResolvedPathType, ResolvedType};
+use crate::rustdoc::{CrateCollection, CORE_PACKAGE_ID};
+use crate::web::traits::assert_trait_is_implemented;
+use crate::web::utils::{is_result, process_framework_path};
+
+/// Wrap the invocation of a singleton constructor to enforce its startup policy.
+///
+/// The guarded constructor is always fallible, since it can time out:
+///
+/// - it returns `Result<T, pavex_runtime::startup::TimeoutError>` if the constructor returns `T`;
+/// - it returns `Result<T, pavex_runtime::startup::StartupError<E>>` if the constructor returns
+///   `Result<T, E>`.
+///
+/// It returns `None` if the policy doesn't change how the constructor is invoked (i.e. no
+/// timeout and no retries).
+pub(crate) fn guard_constructor(
+    callable: &Callable,
+    lifecycle: &Lifecycle,
+    policy: &StartupPolicy,
+    package_graph: &PackageGraph,
+    krate_collection: &CrateCollection,
+) -> Result<Option<Callable>, StartupPolicyValidationError> {
+    if lifecycle != &Lifecycle::Singleton {
+        return Err(StartupPolicyValidationError::NotASingleton {
+            lifecycle: lifecycle.to_owned(),
+        });
+    }
+    if policy.timeout.is_none() && policy.max_retries == 0 {
+        return Ok(None);
+    }
+    if policy.timeout.is_some() && !callable.is_async {
+        return Err(StartupPolicyValidationError::CannotTimeOutASynchronousConstructor);
+    }
+    // Constructors are guaranteed to have an output type.
+    let output = callable.output.as_ref().unwrap();
+    let is_fallible = is_result(output);
+    if policy.max_retries > 0 && !is_fallible {
+        return Err(StartupPolicyValidationError::CannotRetryAnInfallibleConstructor);
+    }
+    if policy.max_retries > 0 {
+        // Each attempt needs its own copy of the inputs that are taken by value.
+        let ResolvedType::ResolvedPath(clone) =
+            process_framework_path("core::clone::Clone", package_graph, krate_collection)
+        else {
+            unreachable!()
+        };
+        for input in &callable.inputs {
+            // The inputs of a template are checked when it gets instantiated.
+            if matches!(input, ResolvedType::Reference(_))
+                || !input.unassigned_generic_type_parameters().is_empty()
+            {
+                continue;
+            }
+            if assert_trait_is_implemented(krate_collection, input, &clone).is_err() {
+                return Err(StartupPolicyValidationError::CannotCloneInputForRetries {
+                    input: input.to_owned(),
+                });
+            }
+        }
+    }
+
+    let guarded_output = if is_fallible {
+        let ResolvedType::ResolvedPath(mut result_type) = output.to_owned() else {
+            unreachable!()
+        };
+        let GenericArgument::Type(err_type) = result_type.generic_arguments[1].to_owned() else {
+            unreachable!()
+        };
+        let ResolvedType::ResolvedPath(mut startup_error) = process_framework_path(
+            "pavex_runtime::startup::StartupError",
+            package_graph,
+            krate_collection,
+        ) else {
+            unreachable!()
+        };
+        startup_error.generic_arguments = vec![GenericArgument::Type(err_type)];
+        result_type.generic_arguments[1] = GenericArgument::Type(startup_error.into());
+        result_type
+    } else {
+        let timeout_error = process_framework_path(
+            "pavex_runtime::startup::TimeoutError",
+            package_graph,
+            krate_collection,
+        );
+        ResolvedPathType {
+            package_id: PackageId::new(CORE_PACKAGE_ID),
+            rustdoc_id: None,
+            base_type: vec!["core".into(), "result".into(), "Result".into()],
+            generic_arguments: vec![
+                GenericArgument::Type(output.to_owned()),
+                GenericArgument::Type(timeout_error),
+            ],
+        }
+    };
+    Ok(Some(Callable {
+        // The guarded invocation always has to be awaited, to enforce the timeout or to wait
+        // between retries.
+        is_async: true,
+        output: Some(guarded_output.into()),
+        path: callable.path.clone(),
+        inputs: callable.inputs.clone(),
+        invocation_style: InvocationStyle::StartupGuard {
+            policy: policy.to_owned(),
+            is_async: callable.is_async,
+            is_fallible,
+        },
+    }))
+}
+
+#[derive(thiserror::Error, Debug, Clone)]
+pub(crate) enum StartupPolicyValidationError {
+    #[error(
+        "Startup policies (`startup_timeout` and `retry`) can only be set for singleton \
+        constructors.\n\
+        This constructor has a {lifecycle} lifecycle: it is not invoked when the application \
+        state is built."
+    )]
+    NotASingleton { lifecycle: Lifecycle },
+    #[error(
+        "I cannot enforce a startup timeout on a synchronous constructor: its invocation \
+        cannot be interrupted."
+    )]
+    CannotTimeOutASynchronousConstructor,
+    #[error(
+        "You asked me to retry an infallible constructor, but there is nothing to retry: \
+        it does not return a `Result`."
+    )]
+    CannotRetryAnInfallibleConstructor,
+    #[error(
+        "I cannot retry this constructor: it takes `{input:?}` as input, by value, but \
+        `{input:?}` does not implement the `Clone` trait.\n\
+        Each attempt must be given its own copy of the inputs that are taken by value."
+    )]
+    CannotCloneInputForRetries { input: ResolvedType },
+}
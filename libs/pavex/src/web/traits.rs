@@ -2,7 +2,10 @@ use std::fmt::Formatter;
 
 use ahash::{HashMap, HashMapExt};
 use guppy::PackageId;
-use rustdoc_types::{GenericParamDefKind, ItemEnum, Type};
+use rustdoc_types::{
+    GenericBound, GenericParamDefKind, Generics, Impl, ItemEnum, TraitBoundModifier, Type,
+    WherePredicate,
+};
 
 use crate::language::{ResolvedPathType, ResolvedType};
 use crate::rustdoc::CrateCollection;
@@ -193,6 +196,11 @@ pub(crate) fn implements_trait(
             }
             // TODO: handle Unpin + other traits
         }
+        // We can't say anything about a generic type parameter that hasn't been assigned
+        // a concrete type.
+        ResolvedType::Generic(_) => {
+            return false;
+        }
     }
 
     // We check if there is a trait implementation for this type in the crate where the trait
@@ -205,7 +213,7 @@ pub(crate) fn implements_trait(
     }
 
     for impl_id in &trait_item.implementations {
-        let implementer = match &trait_definition_crate
+        let impl_ = match &trait_definition_crate
             .get_type_by_local_type_id(impl_id)
             .inner
         {
@@ -213,15 +221,29 @@ pub(crate) fn implements_trait(
                 if impl_.negative {
                     continue;
                 }
-                &impl_.for_
+                impl_
             }
             n => {
                 dbg!(n);
                 unreachable!()
             }
         };
+        // A blanket implementation (e.g. `impl<T: Display> ToString for T`) only applies if
+        // our type satisfies the bounds on the generic parameter it is implemented for.
+        if let Some((parameter, bound_type)) = blanket_impl_binding(impl_, type_) {
+            if satisfies_trait_bounds(
+                krate_collection,
+                &impl_.generics,
+                parameter,
+                bound_type,
+                &trait_definition_crate.core.package_id,
+            ) {
+                return true;
+            }
+            continue;
+        }
         if is_equivalent(
-            implementer,
+            &impl_.for_,
             type_,
             krate_collection,
             &trait_definition_crate.core.package_id,
@@ -232,6 +254,123 @@ pub(crate) fn implements_trait(
     false
 }
 
+/// If `impl_` is a blanket implementation for one of its generic type parameters
+/// (e.g. `impl<T> MyTrait for T`) or for a reference to one (e.g. `impl<T> MyTrait for &T`),
+/// return the name of that parameter and the type it must be bound to for the implementation
+/// to apply to `type_`.
+fn blanket_impl_binding<'a>(
+    impl_: &'a Impl,
+    type_: &'a ResolvedType,
+) -> Option<(&'a str, &'a ResolvedType)> {
+    let (parameter, bound_type) = match (&impl_.for_, type_) {
+        (Type::Generic(parameter), _) => (parameter, type_),
+        (
+            Type::BorrowedRef {
+                mutable,
+                type_: inner,
+                ..
+            },
+            ResolvedType::Reference(r),
+        ) if *mutable == r.is_mutable => match &**inner {
+            Type::Generic(parameter) => (parameter, &*r.inner),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let is_impl_parameter = impl_
+        .generics
+        .params
+        .iter()
+        .any(|p| &p.name == parameter && matches!(p.kind, GenericParamDefKind::Type { .. }));
+    is_impl_parameter.then_some((parameter.as_str(), bound_type))
+}
+
+/// It returns `true` if `type_` implements all the traits that the generic type parameter
+/// named `parameter` is bound by.
+///
+/// A bound that we cannot resolve is considered unsatisfied.
+fn satisfies_trait_bounds(
+    krate_collection: &CrateCollection,
+    generics: &Generics,
+    parameter: &str,
+    type_: &ResolvedType,
+    used_by_package_id: &PackageId,
+) -> bool {
+    trait_bounds(generics, parameter).all(|bound| {
+        match resolve_trait_bound(bound, used_by_package_id, krate_collection) {
+            Ok(bound) => implements_trait(krate_collection, type_, &bound),
+            Err(e) => {
+                tracing::trace!(
+                    "Failed to resolve the trait bound {:?} on `{}`: {:?}",
+                    bound,
+                    parameter,
+                    e
+                );
+                false
+            }
+        }
+    })
+}
+
+/// The trait bounds on the generic type parameter named `parameter`, both inline
+/// (e.g. `<T: MyTrait>`) and in `where` clauses (e.g. `where T: MyTrait`).
+///
+/// `?Sized` bounds are skipped.
+pub(crate) fn trait_bounds<'a>(
+    generics: &'a Generics,
+    parameter: &'a str,
+) -> impl Iterator<Item = &'a rustdoc_types::Path> + 'a {
+    let inline_bounds = generics
+        .params
+        .iter()
+        .filter(move |p| p.name == parameter)
+        .filter_map(|p| match &p.kind {
+            GenericParamDefKind::Type { bounds, .. } => Some(bounds),
+            _ => None,
+        });
+    let where_bounds = generics
+        .where_predicates
+        .iter()
+        .filter_map(move |p| match p {
+            WherePredicate::BoundPredicate {
+                type_: Type::Generic(name),
+                bounds,
+                ..
+            } if name == parameter => Some(bounds),
+            _ => None,
+        });
+    inline_bounds
+        .chain(where_bounds)
+        .flatten()
+        .filter_map(|bound| match bound {
+            GenericBound::TraitBound {
+                trait_,
+                modifier: TraitBoundModifier::None,
+                ..
+            } => Some(trait_),
+            _ => None,
+        })
+}
+
+/// Resolve the trait referenced by a trait bound to its canonical path.
+///
+/// The generic arguments of the trait (e.g. `'de` in `serde::Deserialize<'de>`) are
+/// discarded: [`implements_trait`] only looks at the path of the trait.
+pub(crate) fn resolve_trait_bound(
+    trait_: &rustdoc_types::Path,
+    used_by_package_id: &PackageId,
+    krate_collection: &CrateCollection,
+) -> Result<ResolvedPathType, anyhow::Error> {
+    let (global_type_id, base_type) =
+        krate_collection.get_canonical_path_by_local_type_id(used_by_package_id, &trait_.id)?;
+    Ok(ResolvedPathType {
+        package_id: global_type_id.package_id().to_owned(),
+        rustdoc_id: Some(global_type_id.rustdoc_item_id),
+        base_type: base_type.to_vec(),
+        generic_arguments: vec![],
+    })
+}
+
 fn is_equivalent(
     rustdoc_type: &Type,
     our_type: &ResolvedType,
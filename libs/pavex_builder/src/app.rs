@@ -1,56 +1,131 @@
 use std::collections::BTreeMap;
 use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
 use indexmap::{IndexMap, IndexSet};
 
 use crate::callable::{RawCallable, RawCallableIdentifiers};
-use crate::Callable;
+use crate::router::{MethodGuard, RouterKey};
 
 #[derive(Default, serde::Serialize, serde::Deserialize)]
 /// A blueprint for the runtime behaviour of your application.
 ///
-/// `AppBlueprint` captures three types of information:
+/// `AppBlueprint` captures seven types of information:
 ///
-/// - route handlers, via [`AppBlueprint::route`].
+/// - route handlers, via [`AppBlueprint::route`] and [`AppBlueprint::fallback`].
 /// - constructors, via [`AppBlueprint::constructor`].
-/// - error handlers, via [`Constructor::error_handler`].
+/// - error handlers, via [`Constructor::error_handler`] and [`AppBlueprint::error_handler_for`].
+/// - error observers, via [`AppBlueprint::error_observer`].
+/// - startup policies for singletons, via [`Constructor::startup_timeout`] and
+///   [`Constructor::retry`].
+/// - middlewares, via [`AppBlueprint::wrap`] and [`Route::wrap`].
+/// - a shutdown hook, via [`AppBlueprint::on_shutdown`].
+///
+/// Blueprints can be composed: check out [`AppBlueprint::nest`] to merge the routes of another
+/// blueprint under a path prefix.
 ///
 /// This information is then serialized via [`AppBlueprint::persist`] and passed as input to
 /// `pavex_cli` to generate the application's source code.
 pub struct AppBlueprint {
     /// The set of registered constructors.
     pub constructors: IndexSet<RawCallableIdentifiers>,
-    /// - Keys: a path (e.g. `/homes/rooms`).
+    /// - Keys: a [`RouterKey`] (e.g. `GET /homes/rooms`).
     /// - Values: [`RawCallableIdentifiers`] of an error handler for the error type returned by
-    /// the request handler specified for that path.
-    pub request_handlers_error_handlers: IndexMap<String, RawCallableIdentifiers>,
+    ///   the request handler specified for that route.
+    pub request_handlers_error_handlers: IndexMap<RouterKey, RawCallableIdentifiers>,
     /// - Keys: [`RawCallableIdentifiers`] of a **fallible** constructor.
     /// - Values: [`RawCallableIdentifiers`] of an error handler for the error type returned by
-    /// the constructor.
+    ///   the constructor.
     pub constructors_error_handlers: IndexMap<RawCallableIdentifiers, RawCallableIdentifiers>,
+    /// The error handlers registered via [`AppBlueprint::error_handler_for`], in registration
+    /// order.
+    pub default_error_handlers: Vec<DefaultErrorHandler>,
+    /// [`RawCallableIdentifiers`] of the error observers, in registration order.
+    pub error_observers: Vec<RawCallableIdentifiers>,
+    /// - Keys: [`RawCallableIdentifiers`] of an error observer.
+    /// - Values: a [`Location`] pointing at the corresponding invocation of
+    ///   [`AppBlueprint::error_observer`].
+    pub error_observer_locations: IndexMap<RawCallableIdentifiers, Location>,
     /// - Keys: [`RawCallableIdentifiers`] of a constructor.
     /// - Values: the [`Lifecycle`] for the type returned by the constructor.
     pub component_lifecycles: IndexMap<RawCallableIdentifiers, Lifecycle>,
-    /// - Keys: a path (e.g. `/homes/rooms`).
+    /// - Keys: [`RawCallableIdentifiers`] of a singleton constructor.
+    /// - Values: the [`StartupPolicy`] that governs its invocation when the application state
+    ///   is built.
+    pub constructors_startup_policies: IndexMap<RawCallableIdentifiers, StartupPolicy>,
+    /// - Keys: a [`RouterKey`] (e.g. `GET /homes/rooms`).
     /// - Values: [`RawCallableIdentifiers`] of the request handler in charge of processing
-    /// incoming requests for that path.
-    pub router: BTreeMap<String, RawCallableIdentifiers>,
-    /// - Keys: a path (e.g. `/homes/rooms`).
+    ///   incoming requests for that route.
+    pub router: BTreeMap<RouterKey, RawCallableIdentifiers>,
+    /// - Keys: a [`RouterKey`] (e.g. `GET /homes/rooms`).
     /// - Values: a [`Location`] pointing at the corresponding invocation of
-    /// [`AppBlueprint::route`].
-    pub request_handler_locations: IndexMap<String, Location>,
+    ///   [`AppBlueprint::route`].
+    pub request_handler_locations: IndexMap<RouterKey, Location>,
     /// - Keys: [`RawCallableIdentifiers`] of the fallible constructor.
     /// - Values: a [`Location`] pointing at the corresponding invocation of
-    /// [`Constructor::error_handler`].
+    ///   [`Constructor::error_handler`].
     pub error_handler_locations: IndexMap<RawCallableIdentifiers, Location>,
-    /// - Keys: the path (e.g. `/homes/rooms`) of the corresponding request handler.
+    /// - Keys: the [`RouterKey`] (e.g. `GET /homes/rooms`) of the corresponding request handler.
     /// - Values: a [`Location`] pointing at the corresponding invocation of
-    /// [`Route::error_handler`].
-    pub request_error_handler_locations: IndexMap<String, Location>,
+    ///   [`Route::error_handler`].
+    pub request_error_handler_locations: IndexMap<RouterKey, Location>,
     /// - Keys: [`RawCallableIdentifiers`] of a constructor.
     /// - Values: a [`Location`] pointing at the corresponding invocation of
-    /// [`AppBlueprint::constructor`].
+    ///   [`AppBlueprint::constructor`].
     pub constructor_locations: IndexMap<RawCallableIdentifiers, Location>,
+    /// [`RawCallableIdentifiers`] of the request handler in charge of processing incoming
+    /// requests that do not match any of the registered routes.
+    pub fallback_request_handler: Option<RawCallableIdentifiers>,
+    /// A [`Location`] pointing at the invocation of [`AppBlueprint::fallback`].
+    pub fallback_request_handler_location: Option<Location>,
+    /// [`RawCallableIdentifiers`] of an error handler for the error type returned by the
+    /// fallback request handler.
+    pub fallback_error_handler: Option<RawCallableIdentifiers>,
+    /// A [`Location`] pointing at the invocation of [`Fallback::error_handler`].
+    pub fallback_error_handler_location: Option<Location>,
+    /// [`RawCallableIdentifiers`] of the middlewares that wrap all request handlers,
+    /// in registration order.
+    pub middlewares: Vec<RawCallableIdentifiers>,
+    /// - Keys: a [`RouterKey`] (e.g. `GET /homes/rooms`).
+    /// - Values: [`RawCallableIdentifiers`] of the middlewares that wrap the request handler
+    ///   for that route, in registration order.
+    pub route_middlewares: IndexMap<RouterKey, Vec<RawCallableIdentifiers>>,
+    /// - Keys: [`RawCallableIdentifiers`] of a middleware.
+    /// - Values: a [`Location`] pointing at the corresponding invocation of
+    ///   [`AppBlueprint::wrap`] or [`Route::wrap`].
+    pub middleware_locations: IndexMap<RawCallableIdentifiers, Location>,
+    /// The blueprints nested under this one via [`AppBlueprint::nest`], in registration order.
+    pub nested_blueprints: Vec<NestedBlueprint>,
+    /// [`RawCallableIdentifiers`] of the callable invoked when the server shuts down gracefully.
+    pub shutdown_hook: Option<RawCallableIdentifiers>,
+    /// A [`Location`] pointing at the invocation of [`AppBlueprint::on_shutdown`].
+    pub shutdown_hook_location: Option<Location>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+/// A blueprint that has been nested under another one via [`AppBlueprint::nest`].
+pub struct NestedBlueprint {
+    /// The nested blueprint.
+    pub blueprint: AppBlueprint,
+    /// The prefix prepended to the path of all the routes registered against `blueprint`.
+    pub path_prefix: String,
+    /// A [`Location`] pointing at the corresponding invocation of [`AppBlueprint::nest`].
+    pub nesting_location: Location,
+}
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+/// An error handler registered via [`AppBlueprint::error_handler_for`].
+///
+/// It is used for all the fallible components that return `error_type` as their error type,
+/// unless they have their own dedicated error handler.
+pub struct DefaultErrorHandler {
+    /// The name of the error type, as returned by [`std::any::type_name`].
+    pub error_type: String,
+    /// [`RawCallableIdentifiers`] of the error handler.
+    pub error_handler: RawCallableIdentifiers,
+    /// A [`Location`] pointing at the corresponding invocation of
+    /// [`AppBlueprint::error_handler_for`].
+    pub location: Location,
 }
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -85,6 +160,21 @@ impl Display for Lifecycle {
     }
 }
 
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq, serde::Serialize, serde::Deserialize)]
+/// How a singleton constructor should be invoked when the application state is built.
+///
+/// Configure it via [`Constructor::startup_timeout`] and [`Constructor::retry`].
+pub struct StartupPolicy {
+    /// The maximum amount of time each invocation of the constructor is allowed to take.
+    ///
+    /// `None` if the constructor is allowed to take as long as it needs.
+    pub timeout: Option<Duration>,
+    /// The number of times the constructor is invoked again if it fails (or times out).
+    pub max_retries: u32,
+    /// How long to wait before invoking the constructor again after a failure.
+    pub backoff: Duration,
+}
+
 impl AppBlueprint {
     /// Create a new [`AppBlueprint`].
     pub fn new() -> Self {
@@ -111,15 +201,8 @@ impl AppBlueprint {
     /// ```
     ///
     /// If a constructor for the same type has already been registered, it will be overwritten.
-    pub fn constructor<F, ConstructorInputs>(
-        &mut self,
-        callable: RawCallable<F>,
-        lifecycle: Lifecycle,
-    ) -> Constructor
-    where
-        F: Callable<ConstructorInputs>,
-    {
-        let callable_identifiers = RawCallableIdentifiers::new(callable.import_path);
+    pub fn constructor(&mut self, callable: RawCallable, lifecycle: Lifecycle) -> Constructor {
+        let callable_identifiers = RawCallableIdentifiers::from_raw_callable(callable);
         let location = std::panic::Location::caller();
         self.constructor_locations
             .entry(callable_identifiers.clone())
@@ -136,22 +219,373 @@ impl AppBlueprint {
     #[track_caller]
     /// Register a route and the corresponding request handler.
     ///
-    /// If a handler has already been registered for the same route, it will be overwritten.
-    pub fn route<F, HandlerInputs>(&mut self, callable: RawCallable<F>, path: &str) -> Route
-    where
-        F: Callable<HandlerInputs>,
-    {
-        let callable_identifiers = RawCallableIdentifiers::new(callable.import_path);
+    /// The request handler will be invoked for all incoming requests whose path matches `path`
+    /// and whose HTTP method is allowed by `method_guard`.
+    ///
+    /// ```rust
+    /// use pavex_builder::{AppBlueprint, f, router::{GET, POST}};
+    /// use pavex_runtime::response::Response;
+    ///
+    /// fn get_home() -> Response {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// fn update_home() -> Response {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut bp = AppBlueprint::new();
+    /// bp.route(GET, "/home", f!(crate::get_home));
+    /// bp.route(POST, "/home", f!(crate::update_home));
+    /// # }
+    /// ```
+    ///
+    /// If no request handler matches the method of an incoming request for a registered path,
+    /// the generated application returns a `405 Method Not Allowed` response, with an `Allow`
+    /// header listing the methods that are supported for that path.
+    ///
+    /// If a handler has already been registered for the same path and method guard, it will be
+    /// overwritten.
+    pub fn route(&mut self, method_guard: MethodGuard, path: &str, callable: RawCallable) -> Route {
+        let callable_identifiers = RawCallableIdentifiers::from_raw_callable(callable);
+        let router_key = RouterKey {
+            path: path.to_owned(),
+            method_guard,
+        };
         self.request_handler_locations
-            .insert(path.to_owned(), std::panic::Location::caller().into());
-        self.router
-            .insert(path.to_owned(), callable_identifiers.clone());
+            .insert(router_key.clone(), std::panic::Location::caller().into());
+        self.router.insert(router_key.clone(), callable_identifiers);
         Route {
             blueprint: self,
-            path: path.to_owned(),
+            router_key,
         }
     }
 
+    #[track_caller]
+    /// Register a fallback request handler.
+    ///
+    /// The fallback request handler will be invoked for all incoming requests whose path does
+    /// not match any of the routes registered via [`AppBlueprint::route`].
+    ///
+    /// ```rust
+    /// use pavex_builder::{AppBlueprint, f};
+    /// use pavex_runtime::{http::Request, hyper::Body, response::Response};
+    ///
+    /// fn not_found(request: Request<Body>) -> Response {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut bp = AppBlueprint::new();
+    /// bp.fallback(f!(crate::not_found));
+    /// # }
+    /// ```
+    ///
+    /// The fallback request handler can take advantage of dependency injection, just like any
+    /// other request handler.
+    ///
+    /// If no fallback request handler is registered, the generated application returns an empty
+    /// `404 Not Found` response for requests that do not match any route.  
+    /// If a fallback request handler has already been registered, it will be overwritten.
+    pub fn fallback(&mut self, callable: RawCallable) -> Fallback {
+        let callable_identifiers = RawCallableIdentifiers::from_raw_callable(callable);
+        self.fallback_request_handler_location = Some(std::panic::Location::caller().into());
+        self.fallback_request_handler = Some(callable_identifiers);
+        // An error handler registered for a previous fallback request handler is no longer
+        // relevant.
+        self.fallback_error_handler = None;
+        self.fallback_error_handler_location = None;
+        Fallback { blueprint: self }
+    }
+
+    #[track_caller]
+    /// Register a middleware that wraps all request handlers, including the fallback one.
+    ///
+    /// A middleware takes a [`Next`] instance as input, which represents the rest of the
+    /// request processing pipeline, and returns a type that can be converted into a response.
+    /// It can take additional input parameters, as long as there are constructors registered
+    /// for their types.
+    ///
+    /// ```rust
+    /// use pavex_builder::{AppBlueprint, f};
+    /// use pavex_runtime::{middleware::Next, response::Response};
+    /// # struct Logger;
+    ///
+    /// async fn log_requests(next: Next, logger: Logger) -> Response {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut bp = AppBlueprint::new();
+    /// bp.wrap(f!(crate::log_requests));
+    /// # }
+    /// ```
+    ///
+    /// Middlewares are invoked in the order they were registered: the first one is the
+    /// outermost, the closest to the caller.  
+    /// Middlewares registered via [`AppBlueprint::wrap`] are invoked before the ones registered
+    /// for a specific route via [`Route::wrap`].
+    ///
+    /// ## Common Errors
+    ///
+    /// `pavex_cli` will fail to generate the runtime code for your application if a middleware
+    /// does not take [`Next`] as input, if it returns a `Result` or if it takes the incoming
+    /// request as input (use [`Next::request`] instead).
+    ///
+    /// [`Next`]: pavex_runtime::middleware::Next
+    /// [`Next::request`]: pavex_runtime::middleware::Next::request
+    pub fn wrap(&mut self, callable: RawCallable) {
+        let callable_identifiers = RawCallableIdentifiers::from_raw_callable(callable);
+        self.middleware_locations
+            .entry(callable_identifiers.clone())
+            .or_insert_with(|| std::panic::Location::caller().into());
+        self.middlewares.push(callable_identifiers);
+    }
+
+    #[track_caller]
+    /// Register a callable to be invoked when the server shuts down gracefully, after all
+    /// in-flight requests have been processed.
+    ///
+    /// The shutdown hook can only take singletons as input, either by value or by reference.
+    /// Use it to release the resources held by your application state (e.g. flushing a
+    /// connection pool).
+    ///
+    /// ```rust
+    /// use pavex_builder::{AppBlueprint, f, Lifecycle};
+    /// # struct ConnectionPool;
+    /// # fn connection_pool() -> ConnectionPool { todo!() }
+    ///
+    /// async fn flush_pool(pool: &ConnectionPool) {
+    ///     // [...]
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut bp = AppBlueprint::new();
+    /// bp.constructor(f!(crate::connection_pool), Lifecycle::Singleton);
+    /// bp.on_shutdown(f!(crate::flush_pool));
+    /// # }
+    /// ```
+    ///
+    /// The shutdown hook is invoked by the `run_with_graceful_shutdown` function in the
+    /// generated application, once its shutdown signal has completed and all connections
+    /// have been drained.  
+    /// If a shutdown hook has already been registered, it will be overwritten.
+    ///
+    /// ## Common Errors
+    ///
+    /// `pavex_cli` will fail to generate the runtime code for your application if:
+    ///
+    /// - the shutdown hook takes as input a type that is not a singleton;
+    /// - the shutdown hook returns a value: it must return the unit type, `()`;
+    /// - the shutdown hook is registered against a nested blueprint: it can only be registered
+    ///   against the top-level blueprint.
+    pub fn on_shutdown(&mut self, callable: RawCallable) {
+        let callable_identifiers = RawCallableIdentifiers::from_raw_callable(callable);
+        self.shutdown_hook_location = Some(std::panic::Location::caller().into());
+        self.shutdown_hook = Some(callable_identifiers);
+    }
+
+    #[track_caller]
+    /// Register an error handler for all the fallible constructors and request handlers that
+    /// return `E` as their error type.
+    ///
+    /// It saves you from having to register the same error handler, via
+    /// [`Constructor::error_handler`] or [`Route::error_handler`], for every single component
+    /// that returns `E`.
+    ///
+    /// ```rust
+    /// use pavex_builder::{AppBlueprint, f, Lifecycle, router::GET};
+    /// use pavex_runtime::response::Response;
+    /// # struct User;
+    /// # struct Session;
+    /// # struct DatabaseError;
+    ///
+    /// fn session() -> Result<Session, DatabaseError> {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// fn get_user(session: Session) -> Result<Response, DatabaseError> {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// fn database_error_to_response(error: &DatabaseError) -> Response {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut bp = AppBlueprint::new();
+    /// bp.constructor(f!(crate::session), Lifecycle::RequestScoped);
+    /// bp.route(GET, "/user", f!(crate::get_user));
+    /// // Used for the errors returned by both `session` and `get_user`.
+    /// bp.error_handler_for::<DatabaseError>(f!(crate::database_error_to_response));
+    /// # }
+    /// ```
+    ///
+    /// The error handler must take `&E` as one of its input parameters. It can have additional
+    /// input parameters, as long as there are constructors registered for those parameter types.
+    ///
+    /// # Precedence
+    ///
+    /// An error handler registered for a specific component, via [`Constructor::error_handler`]
+    /// or [`Route::error_handler`], always takes precedence.  
+    /// Error handlers registered via `error_handler_for` against a nested blueprint take
+    /// precedence over the ones registered for the same error type against its parent
+    /// blueprints.
+    ///
+    /// ## Common Errors
+    ///
+    /// `pavex_cli` will fail to generate the runtime code for your application if:
+    ///
+    /// - the error handler does not take `&E` as one of its input parameters;
+    /// - more than one error handler is registered for `E` against the same blueprint.
+    pub fn error_handler_for<E: ?Sized>(&mut self, handler: RawCallable) {
+        self.default_error_handlers.push(DefaultErrorHandler {
+            error_type: std::any::type_name::<E>().to_owned(),
+            error_handler: RawCallableIdentifiers::from_raw_callable(handler),
+            location: std::panic::Location::caller().into(),
+        });
+    }
+
+    #[track_caller]
+    /// Register a callable to be invoked every time a fallible constructor or request handler
+    /// fails, before the error is converted into a response by its error handler.
+    ///
+    /// Error observers are the right place to log or trace errors: once an error has been
+    /// converted into a response, it's gone.
+    ///
+    /// ```rust
+    /// use pavex_builder::{AppBlueprint, f, Lifecycle, router::GET};
+    /// use pavex_runtime::response::Response;
+    /// # struct Logger;
+    /// # fn logger() -> Logger { todo!() }
+    /// # fn get_user() -> Result<Response, pavex_runtime::Error> { todo!() }
+    /// # fn error_to_response(error: &pavex_runtime::Error) -> Response { todo!() }
+    ///
+    /// fn log_error(error: &pavex_runtime::Error, logger: Logger) {
+    ///     // [...]
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut bp = AppBlueprint::new();
+    /// bp.constructor(f!(crate::logger), Lifecycle::Transient);
+    /// bp.route(GET, "/user", f!(crate::get_user))
+    ///     .error_handler(f!(crate::error_to_response));
+    /// // Invoked with a reference to the error if `get_user` fails.
+    /// bp.error_observer(f!(crate::log_error));
+    /// # }
+    /// ```
+    ///
+    /// An error observer must take a reference to the error it observes as its **first**
+    /// input parameter.
+    /// Take `&pavex_runtime::Error` to observe every error: errors of a different type are
+    /// converted into a `pavex_runtime::Error` (via `pavex_runtime::Error::from_ref`) before
+    /// the observer is invoked, therefore they must implement `std::error::Error`.
+    /// Take `&E` to only observe the components that fail with `E`.
+    /// It can have additional input parameters, as long as there are constructors registered
+    /// for those parameter types.
+    ///
+    /// Error observers are invoked in the order they were registered.
+    /// The ones registered against a nested blueprint only observe the errors of the components
+    /// registered against it (or against the blueprints nested under it). They are invoked
+    /// after the ones registered against the parent blueprint.
+    ///
+    /// Errors returned by singleton constructors are not observed: they are returned to the
+    /// caller of the function that builds the application state.
+    ///
+    /// ## Common Errors
+    ///
+    /// `pavex_cli` will fail to generate the runtime code for your application if:
+    ///
+    /// - the error observer does not take a shared reference as its first input parameter;
+    /// - the error observer returns a value: it must return the unit type, `()`;
+    /// - the error observer takes `&pavex_runtime::Error` as input and the error type of one
+    ///   of the components it observes does not implement `std::error::Error`.
+    pub fn error_observer(&mut self, callable: RawCallable) {
+        let callable_identifiers = RawCallableIdentifiers::from_raw_callable(callable);
+        self.error_observer_locations
+            .entry(callable_identifiers.clone())
+            .or_insert_with(|| std::panic::Location::caller().into());
+        self.error_observers.push(callable_identifiers);
+    }
+
+    #[track_caller]
+    /// Merge the routes of another blueprint into this one, under a path prefix.
+    ///
+    /// ```rust
+    /// use pavex_builder::{AppBlueprint, f, Lifecycle, router::GET};
+    /// use pavex_runtime::response::Response;
+    /// # struct AdminSession;
+    ///
+    /// fn admin_session() -> AdminSession {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// fn list_users(session: AdminSession) -> Response {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// fn admin_blueprint() -> AppBlueprint {
+    ///     let mut bp = AppBlueprint::new();
+    ///     bp.constructor(f!(crate::admin_session), Lifecycle::RequestScoped);
+    ///     bp.route(GET, "/users", f!(crate::list_users));
+    ///     bp
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut bp = AppBlueprint::new();
+    /// // `crate::list_users` will be invoked for `GET /admin/users` requests.
+    /// bp.nest("/admin", admin_blueprint());
+    /// # }
+    /// ```
+    ///
+    /// The nested blueprint can be built in a different crate: the locations of its
+    /// registrations are preserved, therefore `pavex_cli` will keep pointing at the right
+    /// source file if something goes wrong.
+    ///
+    /// # Scoping
+    ///
+    /// Constructors registered against the nested blueprint are only visible to its routes (and
+    /// to the routes of the blueprints nested under it).
+    /// If the nested blueprint registers a constructor for a type that can already be built by
+    /// one of the constructors registered against the parent blueprint, the nested constructor
+    /// takes precedence for the routes of the nested blueprint.  
+    /// Constructors registered against the parent blueprint are visible to the routes of the
+    /// nested blueprint.
+    ///
+    /// Middlewares registered against the nested blueprint via [`AppBlueprint::wrap`] only wrap
+    /// its routes. They are invoked after the middlewares registered against the parent
+    /// blueprint.
+    ///
+    /// ## Common Errors
+    ///
+    /// `pavex_cli` will fail to generate the runtime code for your application if:
+    ///
+    /// - `prefix` does not start with a `/` or if it ends with a `/`;
+    /// - the nested blueprint registers a fallback request handler or a shutdown hook: they
+    ///   can only be registered against the top-level blueprint;
+    /// - a route of the nested blueprint clashes with a route registered elsewhere (i.e. same
+    ///   path and method guard);
+    /// - the nested blueprint and another blueprint register different constructors for the same
+    ///   singleton type: there is a single instance of each singleton type for the whole
+    ///   application.
+    pub fn nest(&mut self, prefix: &str, blueprint: AppBlueprint) {
+        self.nested_blueprints.push(NestedBlueprint {
+            blueprint,
+            path_prefix: prefix.to_owned(),
+            nesting_location: std::panic::Location::caller().into(),
+        });
+    }
+
     /// Serialize the blueprint data to a file in RON format.
     pub fn persist(&self, filepath: &std::path::Path) -> Result<(), anyhow::Error> {
         let mut file = fs_err::OpenOptions::new()
@@ -214,9 +648,8 @@ impl<'a> From<&'a std::panic::Location<'a>> for Location {
 ///
 /// It allows you to further configure the behaviour of the registered route.
 pub struct Route<'a> {
-    #[allow(dead_code)]
     blueprint: &'a mut AppBlueprint,
-    path: String,
+    router_key: RouterKey,
 }
 
 impl<'a> Route<'a> {
@@ -231,7 +664,7 @@ impl<'a> Route<'a> {
     /// are constructors registered for those parameter types.
     ///
     /// ```rust
-    /// use pavex_builder::{AppBlueprint, f};
+    /// use pavex_builder::{AppBlueprint, f, router::GET};
     /// use pavex_runtime::{response::Response, hyper::body::Body};
     /// # struct LogLevel;
     /// # struct RuntimeError;
@@ -249,7 +682,7 @@ impl<'a> Route<'a> {
     ///
     /// # fn main() {
     /// let mut bp = AppBlueprint::new();
-    /// bp.route(f!(crate::request_handler), "/home")
+    /// bp.route(GET, "/home", f!(crate::request_handler))
     ///     .error_handler(f!(crate::error_to_response));
     /// # }
     /// ```
@@ -262,17 +695,115 @@ impl<'a> Route<'a> {
     /// `pavex_cli` will fail to generate the runtime code for your application if you register
     /// an error handler for an infallible request handler (i.e. a request handler that does not
     /// return a `Result`).
-    pub fn error_handler<F, HandlerInputs>(self, error_handler: RawCallable<F>) -> Self
-    where
-        F: Callable<HandlerInputs>,
-    {
-        let callable_identifiers = RawCallableIdentifiers::new(error_handler.import_path);
-        self.blueprint
-            .request_error_handler_locations
-            .insert(self.path.to_owned(), std::panic::Location::caller().into());
+    pub fn error_handler(self, error_handler: RawCallable) -> Self {
+        let callable_identifiers = RawCallableIdentifiers::from_raw_callable(error_handler);
+        self.blueprint.request_error_handler_locations.insert(
+            self.router_key.clone(),
+            std::panic::Location::caller().into(),
+        );
         self.blueprint
             .request_handlers_error_handlers
-            .insert(self.path.to_owned(), callable_identifiers);
+            .insert(self.router_key.clone(), callable_identifiers);
+        self
+    }
+
+    #[track_caller]
+    /// Register a middleware that wraps the request handler for this route.
+    ///
+    /// ```rust
+    /// use pavex_builder::{AppBlueprint, f, router::GET};
+    /// use pavex_runtime::{middleware::Next, response::Response};
+    ///
+    /// async fn require_auth(next: Next) -> Response {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// fn request_handler() -> Response {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut bp = AppBlueprint::new();
+    /// bp.route(GET, "/home", f!(crate::request_handler))
+    ///     .wrap(f!(crate::require_auth));
+    /// # }
+    /// ```
+    ///
+    /// Route middlewares are invoked after the ones registered via [`AppBlueprint::wrap`], in
+    /// the order they were registered.  
+    /// Check out [`AppBlueprint::wrap`] for more details on the requirements that middlewares
+    /// must satisfy.
+    pub fn wrap(self, middleware: RawCallable) -> Self {
+        let callable_identifiers = RawCallableIdentifiers::from_raw_callable(middleware);
+        self.blueprint
+            .middleware_locations
+            .entry(callable_identifiers.clone())
+            .or_insert_with(|| std::panic::Location::caller().into());
+        self.blueprint
+            .route_middlewares
+            .entry(self.router_key.clone())
+            .or_default()
+            .push(callable_identifiers);
+        self
+    }
+}
+
+/// The type returned by [`AppBlueprint::fallback`].
+///
+/// It allows you to further configure the behaviour of the fallback request handler.
+pub struct Fallback<'a> {
+    blueprint: &'a mut AppBlueprint,
+}
+
+impl<'a> Fallback<'a> {
+    #[track_caller]
+    /// Register an error handler.
+    ///
+    /// Error handlers convert the error type returned by your fallback request handler into an
+    /// HTTP response.
+    ///
+    /// Error handlers CANNOT consume the error type, they must take a reference to the
+    /// error as input.  
+    /// Error handlers can have additional input parameters alongside the error, as long as there
+    /// are constructors registered for those parameter types.
+    ///
+    /// ```rust
+    /// use pavex_builder::{AppBlueprint, f};
+    /// use pavex_runtime::response::Response;
+    /// # struct LogLevel;
+    /// # struct RuntimeError;
+    ///
+    /// fn not_found() -> Result<Response, RuntimeError> {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// fn error_to_response(error: &RuntimeError, log_level: LogLevel) -> Response {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut bp = AppBlueprint::new();
+    /// bp.fallback(f!(crate::not_found))
+    ///     .error_handler(f!(crate::error_to_response));
+    /// # }
+    /// ```
+    ///
+    /// If an error handler has already been registered, it will be overwritten.
+    ///
+    /// ## Common Errors
+    ///
+    /// `pavex_cli` will fail to generate the runtime code for your application if you register
+    /// an error handler for an infallible fallback request handler (i.e. a request handler that
+    /// does not return a `Result`).
+    pub fn error_handler(self, error_handler: RawCallable) -> Self {
+        let callable_identifiers = RawCallableIdentifiers::from_raw_callable(error_handler);
+        self.blueprint.fallback_error_handler_location =
+            Some(std::panic::Location::caller().into());
+        self.blueprint.fallback_error_handler = Some(callable_identifiers);
         self
     }
 }
@@ -328,11 +859,8 @@ impl<'a> Constructor<'a> {
     /// `pavex_cli` will fail to generate the runtime code for your application if you register
     /// an error handler for an infallible constructor (i.e. a constructor that does not return
     /// a `Result`).
-    pub fn error_handler<F, HandlerInputs>(self, handler: RawCallable<F>) -> Self
-    where
-        F: Callable<HandlerInputs>,
-    {
-        let callable_identifiers = RawCallableIdentifiers::new(handler.import_path);
+    pub fn error_handler(self, handler: RawCallable) -> Self {
+        let callable_identifiers = RawCallableIdentifiers::from_raw_callable(handler);
         self.blueprint.error_handler_locations.insert(
             self.constructor_identifiers.clone(),
             std::panic::Location::caller().into(),
@@ -342,4 +870,92 @@ impl<'a> Constructor<'a> {
             .insert(self.constructor_identifiers.clone(), callable_identifiers);
         self
     }
+
+    /// Set the maximum amount of time the constructor is allowed to take when the application
+    /// state is built.
+    ///
+    /// If the constructor does not complete in time, the function that builds the application
+    /// state returns an error.
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use pavex_builder::{AppBlueprint, f, Lifecycle};
+    /// # struct ConnectionPool;
+    /// # struct PoolError;
+    ///
+    /// async fn connection_pool() -> Result<ConnectionPool, PoolError> {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut bp = AppBlueprint::new();
+    /// bp.constructor(f!(crate::connection_pool), Lifecycle::Singleton)
+    ///     .startup_timeout(Duration::from_secs(5));
+    /// # }
+    /// ```
+    ///
+    /// If a timeout has already been set for this constructor, it will be overwritten.
+    ///
+    /// ## Common Errors
+    ///
+    /// `pavex_cli` will fail to generate the runtime code for your application if:
+    ///
+    /// - the constructor is not a singleton;
+    /// - the constructor is not `async`: a synchronous constructor cannot be interrupted.
+    pub fn startup_timeout(self, timeout: Duration) -> Self {
+        self.blueprint
+            .constructors_startup_policies
+            .entry(self.constructor_identifiers.clone())
+            .or_default()
+            .timeout = Some(timeout);
+        self
+    }
+
+    /// Invoke the constructor again, up to `max_retries` times, if it fails when the
+    /// application state is built.
+    ///
+    /// `backoff` is the amount of time to wait before each new attempt.  
+    /// If a timeout has been set via [`Constructor::startup_timeout`], it applies to each
+    /// attempt: an attempt that times out is retried as well.
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use pavex_builder::{AppBlueprint, f, Lifecycle};
+    /// # struct ConnectionPool;
+    /// # struct PoolError;
+    ///
+    /// async fn connection_pool() -> Result<ConnectionPool, PoolError> {
+    ///     // [...]
+    ///     # todo!()
+    /// }
+    ///
+    /// # fn main() {
+    /// let mut bp = AppBlueprint::new();
+    /// bp.constructor(f!(crate::connection_pool), Lifecycle::Singleton)
+    ///     .retry(3, Duration::from_millis(500));
+    /// # }
+    /// ```
+    ///
+    /// The input parameters of the constructor that are taken by value must implement `Clone`,
+    /// since each attempt needs its own copy.  
+    /// If a retry policy has already been set for this constructor, it will be overwritten.
+    ///
+    /// ## Common Errors
+    ///
+    /// `pavex_cli` will fail to generate the runtime code for your application if:
+    ///
+    /// - the constructor is not a singleton;
+    /// - the constructor is infallible (i.e. it does not return a `Result`): there is nothing
+    ///   to retry.
+    pub fn retry(self, max_retries: u32, backoff: Duration) -> Self {
+        let policy = self
+            .blueprint
+            .constructors_startup_policies
+            .entry(self.constructor_identifiers.clone())
+            .or_default();
+        policy.max_retries = max_retries;
+        policy.backoff = backoff;
+        self
+    }
 }
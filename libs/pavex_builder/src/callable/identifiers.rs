@@ -1,7 +1,11 @@
 #[derive(Debug, Hash, Eq, PartialEq, Clone, serde::Serialize, serde::Deserialize)]
-pub struct RawCallable<F> {
-    pub callable: F,
+pub struct RawCallable {
     pub import_path: &'static str,
+    /// The name of the crate where the callable was registered, captured at compile-time.
+    ///
+    /// It is used to resolve `crate::`-relative import paths for callables registered
+    /// against a blueprint that lives in a different crate (see `AppBlueprint::nest`).
+    pub registered_at: &'static str,
 }
 
 #[derive(Debug, Hash, Eq, PartialEq, Clone, serde::Serialize, serde::Deserialize)]
@@ -26,11 +30,10 @@ pub struct RawCallableIdentifiers {
 }
 
 impl RawCallableIdentifiers {
-    #[track_caller]
-    pub fn new(import_path: &'static str) -> Self {
+    pub fn from_raw_callable(callable: RawCallable) -> Self {
         Self {
-            registered_at: std::env::var("CARGO_PKG_NAME").expect("Failed to fetch the CARGO_CRATE_NAME environment variable. Are you using a custom build system?"),
-            import_path: import_path.to_string(),
+            registered_at: callable.registered_at.to_owned(),
+            import_path: callable.import_path.to_owned(),
         }
     }
 
@@ -1,5 +1,9 @@
-pub use app::{AppBlueprint, Constructor, Lifecycle, Location, Route};
-pub use callable::{Callable, RawCallable, RawCallableIdentifiers};
+pub use app::{
+    AppBlueprint, Constructor, DefaultErrorHandler, Fallback, Lifecycle, Location, NestedBlueprint,
+    Route, StartupPolicy,
+};
+pub use callable::{RawCallable, RawCallableIdentifiers};
 
 mod app;
 mod callable;
+pub mod router;
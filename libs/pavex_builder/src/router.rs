@@ -0,0 +1,97 @@
+//! Types to specify which requests should be routed to a request handler.
+//!
+//! Check out [`AppBlueprint::route`](crate::AppBlueprint::route) for more details.
+use std::fmt::{Display, Formatter};
+
+/// Only match requests with the `GET` method.
+pub const GET: MethodGuard = MethodGuard::Get;
+/// Only match requests with the `POST` method.
+pub const POST: MethodGuard = MethodGuard::Post;
+/// Only match requests with the `PUT` method.
+pub const PUT: MethodGuard = MethodGuard::Put;
+/// Only match requests with the `PATCH` method.
+pub const PATCH: MethodGuard = MethodGuard::Patch;
+/// Only match requests with the `DELETE` method.
+pub const DELETE: MethodGuard = MethodGuard::Delete;
+/// Only match requests with the `HEAD` method.
+pub const HEAD: MethodGuard = MethodGuard::Head;
+/// Only match requests with the `OPTIONS` method.
+pub const OPTIONS: MethodGuard = MethodGuard::Options;
+/// Match requests regardless of their HTTP method.
+pub const ANY: MethodGuard = MethodGuard::Any;
+
+#[derive(
+    Clone, Copy, Debug, Hash, Eq, PartialEq, Ord, PartialOrd, serde::Serialize, serde::Deserialize,
+)]
+/// Used by [`AppBlueprint::route`](crate::AppBlueprint::route) to specify which HTTP methods
+/// the request handler should be invoked for.
+///
+/// You will usually rely on the constants exposed by this module (e.g. [`GET`]) rather than
+/// using the variants directly.
+///
+/// # Precedence
+///
+/// If a request handler has been registered with [`MethodGuard::Any`] and another one has been
+/// registered with a specific method for the same path, the more specific guard wins: requests
+/// using that method will be routed to the second handler.
+pub enum MethodGuard {
+    /// Match requests using the `GET` method.
+    Get,
+    /// Match requests using the `POST` method.
+    Post,
+    /// Match requests using the `PUT` method.
+    Put,
+    /// Match requests using the `PATCH` method.
+    Patch,
+    /// Match requests using the `DELETE` method.
+    Delete,
+    /// Match requests using the `HEAD` method.
+    Head,
+    /// Match requests using the `OPTIONS` method.
+    Options,
+    /// Match all incoming requests, regardless of their HTTP method.
+    Any,
+}
+
+impl MethodGuard {
+    /// The name of the HTTP method matched by this guard (e.g. `GET`).
+    ///
+    /// It returns `None` for [`MethodGuard::Any`], since it matches all methods.
+    pub fn method(&self) -> Option<&'static str> {
+        let m = match self {
+            MethodGuard::Get => "GET",
+            MethodGuard::Post => "POST",
+            MethodGuard::Put => "PUT",
+            MethodGuard::Patch => "PATCH",
+            MethodGuard::Delete => "DELETE",
+            MethodGuard::Head => "HEAD",
+            MethodGuard::Options => "OPTIONS",
+            MethodGuard::Any => return None,
+        };
+        Some(m)
+    }
+}
+
+impl Display for MethodGuard {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.method().unwrap_or("ANY"))
+    }
+}
+
+#[derive(
+    Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd, serde::Serialize, serde::Deserialize,
+)]
+/// The key used to identify a route within an [`AppBlueprint`](crate::AppBlueprint): a path
+/// and the guard on the HTTP method of incoming requests.
+pub struct RouterKey {
+    /// The path of the route (e.g. `/homes/rooms`).
+    pub path: String,
+    /// The HTTP method(s) that the route matches.
+    pub method_guard: MethodGuard,
+}
+
+impl Display for RouterKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.method_guard, self.path)
+    }
+}
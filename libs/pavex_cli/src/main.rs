@@ -1,26 +1,40 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
 
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use tracing_subscriber::fmt::format::FmtSpan;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::util::SubscriberInitExt;
 use tracing_subscriber::EnvFilter;
 
 use owo_colors::OwoColorize;
-use pavex::App;
+use pavex::{compute_package_graph, App, CrateCollection, JsonDiagnostic, RustdocConfig};
 use pavex_builder::AppBlueprint;
 
+mod watch;
+
 #[derive(Parser)]
 #[clap(author, version, about, long_about = None)]
 struct Cli {
     /// Expose inner details in case of an error.
     #[clap(long, env = "PAVEX_DEBUG")]
     debug: bool,
+    /// The format used to report errors.
+    /// `json` emits one JSON object per diagnostic, on its own line, to `stdout`.
+    #[clap(long, value_enum, default_value_t = MessageFormat::Human, global = true)]
+    message_format: MessageFormat,
     #[clap(subcommand)]
     command: Commands,
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum MessageFormat {
+    /// Human-readable error reports.
+    Human,
+    /// Machine-readable JSON diagnostics, mirroring `cargo`'s `--message-format=json`.
+    Json,
+}
+
 #[derive(Subcommand)]
 enum Commands {
     /// Generate application runtime code according to an application blueprint.
@@ -36,7 +50,98 @@ enum Commands {
         /// The path is interpreted as relative to the root of the current workspace.
         #[clap(short, long, value_parser)]
         output: PathBuf,
+        #[clap(flatten)]
+        rustdoc: RustdocArgs,
+    },
+    /// Watch an application blueprint and regenerate the application runtime code
+    /// every time it changes.
+    ///
+    /// Pavex also keeps an eye on the source code of the local crates that the
+    /// blueprint relies on (e.g. to register constructors or request handlers).
+    Watch {
+        /// The source path for the serialized application blueprint.
+        #[clap(short, long, value_parser)]
+        blueprint: PathBuf,
+        /// Optional. If provided, pavex will serialize diagnostic information about
+        /// the application to the specified path.
+        #[clap(long, value_parser)]
+        diagnostics: Option<PathBuf>,
+        /// The target directory for the generated application crate.  
+        /// The path is interpreted as relative to the root of the current workspace.
+        #[clap(short, long, value_parser)]
+        output: PathBuf,
+        #[clap(flatten)]
+        rustdoc: RustdocArgs,
     },
+    /// Check an application blueprint for errors, without generating any code.
+    ///
+    /// All the analyses performed by `generate` are carried out, but no code is generated
+    /// and `pavex`'s on-disk cache for the JSON docs computed by `rustdoc` is left untouched.
+    /// Just like `cargo check`, `cargo` may still write to the target directory when the docs
+    /// of a crate have to be computed.
+    Check {
+        /// The source path for the serialized application blueprint.
+        #[clap(short, long, value_parser)]
+        blueprint: PathBuf,
+        #[clap(flatten)]
+        rustdoc: RustdocArgs,
+    },
+}
+
+/// Options that control how `rustdoc` is invoked to compute the documentation of your crates.
+#[derive(clap::Args)]
+struct RustdocArgs {
+    /// Optional. The path to a TOML file with the `rustdoc` configuration (toolchain,
+    /// target triple and features).
+    /// The options passed on the command line take precedence over its content.
+    #[clap(long, value_parser)]
+    rustdoc_config: Option<PathBuf>,
+    /// The `nightly` toolchain used to compute the documentation of your crates.
+    /// Defaults to `nightly`.
+    #[clap(long)]
+    toolchain: Option<String>,
+    /// The target triple that your crates are documented for.
+    /// Defaults to the host target.
+    #[clap(long)]
+    target: Option<String>,
+    /// Features to enable, on top of the default ones, in the `<package>/<feature>` format.
+    /// Multiple features can be separated by commas or specified with repeated flags.
+    #[clap(long, value_delimiter = ',')]
+    features: Vec<String>,
+    /// Enable all the features of every documented package.
+    #[clap(long)]
+    all_features: bool,
+}
+
+impl RustdocArgs {
+    /// Combine the configuration file (if any) with the options passed on the command line.
+    fn into_config(self) -> Result<RustdocConfig, Box<dyn std::error::Error>> {
+        let mut config = match &self.rustdoc_config {
+            Some(path) => RustdocConfig::load(path)?,
+            None => RustdocConfig::default(),
+        };
+        if let Some(toolchain) = self.toolchain {
+            config.toolchain = Some(toolchain);
+        }
+        if let Some(target) = self.target {
+            config.target = Some(target);
+        }
+        config.all_features |= self.all_features;
+        for feature in self.features {
+            let Some((package_name, feature_name)) = feature.split_once('/') else {
+                return Err(format!(
+                    "`{feature}` is not a valid feature: it must be in the `<package>/<feature>` format"
+                )
+                .into());
+            };
+            config
+                .features
+                .entry(package_name.to_owned())
+                .or_default()
+                .push(feature_name.to_owned());
+        }
+        Ok(config)
+    }
 }
 
 fn init_telemetry() {
@@ -55,7 +160,7 @@ fn init_telemetry() {
         .init();
 }
 
-fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
+fn main() -> ExitCode {
     let cli = Cli::parse();
     miette::set_hook(Box::new(move |_| {
         let mut config = miette::MietteHandlerOpts::new();
@@ -70,33 +175,136 @@ fn main() -> Result<ExitCode, Box<dyn std::error::Error>> {
     if cli.debug {
         init_telemetry();
     }
+    let message_format = cli.message_format;
+    match run(cli) {
+        Ok(exit_code) => exit_code,
+        // Errors that were not reported as diagnostics (e.g. an invalid command-line argument
+        // or a failure to write the generated code to disk) must honour `--message-format` too.
+        Err(e) => {
+            report_errors(vec![miette::miette!("{e}")], message_format);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<ExitCode, Box<dyn std::error::Error>> {
     match cli.command {
         Commands::Generate {
             blueprint,
             diagnostics,
             output,
+            rustdoc,
         } => {
-            let blueprint = AppBlueprint::load(&blueprint)?;
-            let app = match App::build(blueprint) {
-                Ok(a) => a,
-                Err(errors) => {
-                    for e in errors {
-                        eprintln!("{}: {:?}", "ERROR".bold().red(), e);
-                    }
-                    return Ok(ExitCode::FAILURE);
-                }
+            let Some(krate_collection) =
+                crate_collection(rustdoc.into_config()?, cli.message_format)?
+            else {
+                return Ok(ExitCode::FAILURE);
+            };
+            let Some(app) = build_app(&blueprint, &krate_collection, cli.message_format)? else {
+                return Ok(ExitCode::FAILURE);
+            };
+            persist_app(&app, diagnostics.as_deref(), &output)?;
+        }
+        Commands::Check { blueprint, rustdoc } => {
+            let Some(krate_collection) =
+                crate_collection(rustdoc.into_config()?, cli.message_format)?
+            else {
+                return Ok(ExitCode::FAILURE);
             };
-            if let Some(diagnostic_path) = diagnostics {
-                app.diagnostic_representation()
-                    .persist_flat(&diagnostic_path)?;
+            let krate_collection = krate_collection.with_read_only_disk_cache();
+            if build_app(&blueprint, &krate_collection, cli.message_format)?.is_none() {
+                return Ok(ExitCode::FAILURE);
             }
-            assert!(
-                output.is_relative(),
-                "The output path must be relative to the root of the current `cargo` workspace."
+        }
+        Commands::Watch {
+            blueprint,
+            diagnostics,
+            output,
+            rustdoc,
+        } => {
+            return watch::watch(
+                &blueprint,
+                diagnostics.as_deref(),
+                &output,
+                rustdoc.into_config()?,
+                cli.message_format,
             );
-            let generated_app = app.codegen()?;
-            generated_app.persist(&output)?;
         }
     }
     Ok(ExitCode::SUCCESS)
 }
+
+/// Initialise a [`CrateCollection`] for the current workspace.
+///
+/// Errors are reported according to the chosen `message_format`; `None` is returned if the
+/// package graph for the current workspace could not be computed.
+fn crate_collection(
+    config: RustdocConfig,
+    message_format: MessageFormat,
+) -> Result<Option<CrateCollection>, Box<dyn std::error::Error>> {
+    match compute_package_graph() {
+        Ok(package_graph) => Ok(Some(CrateCollection::new(package_graph, config))),
+        Err(e) => {
+            report_errors(vec![e], message_format);
+            Ok(None)
+        }
+    }
+}
+
+/// Load an application blueprint from disk and run all the analyses required to
+/// build an [`App`] out of it.
+///
+/// The crate documentation held by `krate_collection` is reused (and extended) instead of
+/// being computed from scratch.
+///
+/// Diagnostics are reported according to the chosen `message_format`; `None` is returned
+/// if there was at least one error.
+fn build_app(
+    blueprint: &Path,
+    krate_collection: &CrateCollection,
+    message_format: MessageFormat,
+) -> Result<Option<App>, Box<dyn std::error::Error>> {
+    let blueprint = AppBlueprint::load(blueprint)?;
+    match App::build_with_crate_collection(blueprint, krate_collection) {
+        Ok(app) => Ok(Some(app)),
+        Err(errors) => {
+            report_errors(errors, message_format);
+            Ok(None)
+        }
+    }
+}
+
+/// Print errors to the user, according to the chosen `message_format`.
+fn report_errors(errors: Vec<miette::Error>, message_format: MessageFormat) {
+    for e in errors {
+        match message_format {
+            MessageFormat::Human => eprintln!("{}: {:?}", "ERROR".bold().red(), e),
+            MessageFormat::Json => {
+                let diagnostic = JsonDiagnostic::new(e.as_ref());
+                println!(
+                    "{}",
+                    serde_json::to_string(&diagnostic).expect("Failed to serialize a diagnostic")
+                );
+            }
+        }
+    }
+}
+
+/// Generate the code for the application and write it to `output`.
+fn persist_app(
+    app: &App,
+    diagnostics: Option<&Path>,
+    output: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if let Some(diagnostic_path) = diagnostics {
+        app.diagnostic_representation()
+            .persist_flat(diagnostic_path)?;
+    }
+    assert!(
+        output.is_relative(),
+        "The output path must be relative to the root of the current `cargo` workspace."
+    );
+    let generated_app = app.codegen()?;
+    generated_app.persist(output)?;
+    Ok(())
+}
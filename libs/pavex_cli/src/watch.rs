@@ -0,0 +1,255 @@
+//! Regenerate the application runtime code every time the blueprint or the source code
+//! it relies on changes.
+//!
+//! We poll the filesystem for changes: it's portable and it doesn't require a
+//! notification backend for each platform.
+//! The crate documentation computed by `rustdoc` is kept in memory across runs (see
+//! [`CrateCollection::refresh`]), which is what makes a regeneration fast.
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
+
+use guppy::graph::PackageGraph;
+use guppy::PackageId;
+use owo_colors::OwoColorize;
+
+use pavex::{compute_package_graph, package_source_files, CrateCollection, RustdocConfig};
+
+use crate::{build_app, crate_collection, persist_app, report_errors, MessageFormat};
+
+/// How long we wait between two consecutive scans of the watched files.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The last modification time of each watched file.
+type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+pub(crate) fn watch(
+    blueprint: &Path,
+    diagnostics: Option<&Path>,
+    output: &Path,
+    rustdoc_config: RustdocConfig,
+    message_format: MessageFormat,
+) -> Result<ExitCode, Box<dyn std::error::Error>> {
+    let Some(mut krate_collection) = crate_collection(rustdoc_config, message_format)? else {
+        return Ok(ExitCode::FAILURE);
+    };
+    loop {
+        // Errors are reported, but they don't stop us from watching: the user is
+        // probably going to fix them soon.
+        match build_app(blueprint, &krate_collection, message_format) {
+            Ok(Some(app)) => match persist_app(&app, diagnostics, output) {
+                Ok(()) => eprintln!("{}: application code generated", "DONE".bold().green()),
+                Err(e) => report_errors(vec![miette::miette!("{e}")], message_format),
+            },
+            Ok(None) => {}
+            Err(e) => report_errors(vec![miette::miette!("{e}")], message_format),
+        }
+
+        let watched_directories = watched_directories(&mut krate_collection);
+        let mut snapshot = take_snapshot(blueprint, &watched_directories);
+        let mut changed_files = vec![];
+        let package_graph = loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let new_snapshot = take_snapshot(blueprint, &watched_directories);
+            changed_files.extend(diff_snapshots(&snapshot, &new_snapshot));
+            snapshot = new_snapshot;
+            if changed_files.is_empty() {
+                continue;
+            }
+            // The manifest of one of the watched crates might have changed: we need an
+            // up-to-date package graph.
+            match compute_package_graph() {
+                Ok(package_graph) => break package_graph,
+                Err(e) => report_errors(vec![e], message_format),
+            }
+        };
+        eprintln!(
+            "{}: changes detected, regenerating the application code",
+            "WATCH".bold().cyan()
+        );
+        let changed_packages = owning_packages(&changed_files, &local_packages(&package_graph));
+        krate_collection.refresh(package_graph, &changed_packages);
+    }
+}
+
+/// The root directories of the local packages whose documentation was needed to build
+/// the application.
+/// Third-party packages are not watched: their source code doesn't change.
+fn watched_directories(krate_collection: &mut CrateCollection) -> Vec<PathBuf> {
+    let package_ids = krate_collection.cached_package_ids();
+    let package_graph = krate_collection.package_graph();
+    package_ids
+        .iter()
+        // Toolchain crates do not appear in the package graph.
+        .filter_map(|package_id| package_graph.metadata(package_id).ok())
+        .filter(|metadata| metadata.source().is_local())
+        .filter_map(|metadata| metadata.manifest_path().parent())
+        .map(|directory| directory.as_std_path().to_owned())
+        .collect()
+}
+
+fn take_snapshot(blueprint: &Path, watched_directories: &[PathBuf]) -> Snapshot {
+    let mut snapshot = Snapshot::new();
+    record_modification_time(blueprint, &mut snapshot);
+    // We watch the same files that determine whether the docs of a package can be
+    // retrieved from the on-disk cache.
+    // A directory that can't be scanned contributes no files: its disappearance (or
+    // reappearance) is detected as a change.
+    for directory in watched_directories {
+        for path in package_source_files(directory).unwrap_or_default() {
+            record_modification_time(&path, &mut snapshot);
+        }
+    }
+    snapshot
+}
+
+fn record_modification_time(path: &Path, snapshot: &mut Snapshot) {
+    if let Ok(modified) = fs_err::metadata(path).and_then(|m| m.modified()) {
+        snapshot.insert(path.to_owned(), modified);
+    }
+}
+
+/// All the files that have been created, modified or deleted between two snapshots.
+fn diff_snapshots(old: &Snapshot, new: &Snapshot) -> Vec<PathBuf> {
+    let modified_or_created = new
+        .iter()
+        .filter(|(path, modified)| old.get(*path) != Some(modified))
+        .map(|(path, _)| path.to_owned());
+    let deleted = old.keys().filter(|path| !new.contains_key(*path)).cloned();
+    modified_or_created.chain(deleted).collect()
+}
+
+/// The root directory of each local package in the package graph.
+fn local_packages(package_graph: &PackageGraph) -> Vec<(PathBuf, PackageId)> {
+    package_graph
+        .packages()
+        .filter(|metadata| metadata.source().is_local())
+        .filter_map(|metadata| {
+            let directory = metadata.manifest_path().parent()?.as_std_path().to_owned();
+            Some((directory, metadata.id().to_owned()))
+        })
+        .collect()
+}
+
+/// Determine which local packages the changed files belong to.
+/// Each file is assigned to the package with the innermost root directory that contains it.
+fn owning_packages(
+    changed_files: &[PathBuf],
+    local_packages: &[(PathBuf, PackageId)],
+) -> Vec<PackageId> {
+    let mut changed_packages = vec![];
+    for file in changed_files {
+        let owner = local_packages
+            .iter()
+            .filter(|(directory, _)| file.starts_with(directory))
+            .max_by_key(|(directory, _)| directory.components().count());
+        if let Some((_, package_id)) = owner {
+            if !changed_packages.contains(package_id) {
+                changed_packages.push(package_id.to_owned());
+            }
+        }
+    }
+    changed_packages
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::time::{Duration, SystemTime};
+
+    use guppy::PackageId;
+
+    use super::{diff_snapshots, owning_packages, take_snapshot, Snapshot};
+
+    /// A fresh directory, unique to the calling test.
+    fn scratch_directory(test_name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("pavex-watch-{}-{test_name}", std::process::id()));
+        let _ = fs_err::remove_dir_all(&directory);
+        fs_err::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    #[test]
+    fn snapshots_track_the_blueprint_and_package_sources() {
+        let root = scratch_directory("snapshot");
+        let files = [
+            "blueprint.ron",
+            "app/Cargo.toml",
+            "app/src/lib.rs",
+            "app/src/routes/mod.rs",
+            "app/README.md",
+            "app/target/debug/build.rs",
+            "app/.git/hook.rs",
+            "app/nested/Cargo.toml",
+            "app/nested/src/lib.rs",
+        ];
+        for file in files {
+            let path = root.join(file);
+            fs_err::create_dir_all(path.parent().unwrap()).unwrap();
+            fs_err::write(path, "").unwrap();
+        }
+
+        let snapshot = take_snapshot(&root.join("blueprint.ron"), &[root.join("app")]);
+        let watched: Vec<_> = snapshot.keys().cloned().collect();
+        assert_eq!(
+            watched,
+            vec![
+                root.join("app/Cargo.toml"),
+                root.join("app/src/lib.rs"),
+                root.join("app/src/routes/mod.rs"),
+                root.join("blueprint.ron"),
+            ]
+        );
+    }
+
+    #[test]
+    fn diffs_include_modified_created_and_deleted_files() {
+        let t0 = SystemTime::UNIX_EPOCH;
+        let t1 = t0 + Duration::from_secs(1);
+        let old = Snapshot::from([
+            (PathBuf::from("unchanged.rs"), t0),
+            (PathBuf::from("modified.rs"), t0),
+            (PathBuf::from("deleted.rs"), t0),
+        ]);
+        let new = Snapshot::from([
+            (PathBuf::from("unchanged.rs"), t0),
+            (PathBuf::from("modified.rs"), t1),
+            (PathBuf::from("created.rs"), t1),
+        ]);
+
+        let mut changed = diff_snapshots(&old, &new);
+        changed.sort();
+        assert_eq!(
+            changed,
+            vec![
+                PathBuf::from("created.rs"),
+                PathBuf::from("deleted.rs"),
+                PathBuf::from("modified.rs"),
+            ]
+        );
+        assert!(diff_snapshots(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn changed_files_are_assigned_to_the_innermost_package() {
+        let outer = PackageId::new("outer");
+        let inner = PackageId::new("inner");
+        let local_packages = [
+            (PathBuf::from("/workspace"), outer.clone()),
+            (PathBuf::from("/workspace/libs/inner"), inner.clone()),
+        ];
+        let changed_files = [
+            PathBuf::from("/workspace/libs/inner/src/lib.rs"),
+            PathBuf::from("/workspace/libs/inner/Cargo.toml"),
+            PathBuf::from("/workspace/src/main.rs"),
+            PathBuf::from("/elsewhere/blueprint.ron"),
+        ];
+
+        assert_eq!(
+            owning_packages(&changed_files, &local_packages),
+            vec![inner, outer]
+        );
+    }
+}
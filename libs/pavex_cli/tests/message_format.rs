@@ -0,0 +1,40 @@
+//! `--message-format json` must be honoured by every error that `pavex` reports,
+//! including those that are not diagnostics about the blueprint.
+use std::process::Command;
+
+#[test]
+fn propagated_errors_are_reported_as_json_diagnostics() {
+    let output = Command::new(env!("CARGO_BIN_EXE_pavex_cli"))
+        .args([
+            "--message-format",
+            "json",
+            "check",
+            "--blueprint",
+            "blueprint.ron",
+            "--features",
+            "not-a-feature",
+        ])
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(
+        output.stderr.is_empty(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines.len(), 1, "{stdout}");
+    let diagnostic: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(
+        diagnostic,
+        serde_json::json!({
+            "severity": "error",
+            "message": "`not-a-feature` is not a valid feature: it must be in the `<package>/<feature>` format",
+            "help": null,
+            "labels": [],
+            "related": []
+        })
+    );
+}
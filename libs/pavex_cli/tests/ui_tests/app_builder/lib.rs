@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 use pavex_runtime::response::Response;
 
 pub struct Logger;
@@ -45,6 +45,6 @@ pub fn blueprint() -> AppBlueprint {
     bp.constructor(f!(crate::extract_path), Lifecycle::RequestScoped)
         .error_handler(f!(crate::handle_extract_path_error));
     bp.constructor(f!(crate::logger), Lifecycle::Transient);
-    bp.route(f!(crate::stream_file), "/home");
+    bp.route(GET, "/home", f!(crate::stream_file));
     bp
 }
@@ -0,0 +1,197 @@
+//! Do NOT edit this code.
+//! It was automatically generated by `pavex`.
+//! All manual edits will be lost next time the code is generated.
+use std as alloc;
+struct ServerState {
+    router: pavex_runtime::routing::Router<u32>,
+    application_state: ApplicationState,
+}
+pub struct ApplicationState {
+    s0: app::HttpClient,
+    s1: app::Cache,
+}
+#[derive(Debug)]
+pub enum ApplicationStateError {
+    HttpClient(app::HttpClientError),
+    Cache(app::CacheError),
+}
+impl std::fmt::Display for ApplicationStateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ApplicationStateError::HttpClient(_) => {
+                f
+                    .write_str(
+                        "Failed to build the application state: `app::http_client` returned an error",
+                    )
+            }
+            ApplicationStateError::Cache(_) => {
+                f
+                    .write_str(
+                        "Failed to build the application state: `app::cache` returned an error",
+                    )
+            }
+        }
+    }
+}
+impl std::error::Error for ApplicationStateError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ApplicationStateError::HttpClient(e) => Some(e),
+            ApplicationStateError::Cache(_) => None,
+        }
+    }
+}
+pub async fn build_application_state() -> Result<
+    crate::ApplicationState,
+    crate::ApplicationStateError,
+> {
+    let v0 = app::cache();
+    match v0 {
+        Ok(v1) => {
+            let v2 = app::http_client();
+            match v2 {
+                Ok(v3) => {
+                    let v4 = crate::ApplicationState {
+                        s0: v3,
+                        s1: v1,
+                    };
+                    core::result::Result::Ok(v4)
+                }
+                Err(v3) => {
+                    let v4 = crate::ApplicationStateError::HttpClient(v3);
+                    core::result::Result::Err(v4)
+                }
+            }
+        }
+        Err(v1) => {
+            let v2 = crate::ApplicationStateError::Cache(v1);
+            core::result::Result::Err(v2)
+        }
+    }
+}
+pub async fn run(
+    server_builder: pavex_runtime::hyper::server::Builder<
+        pavex_runtime::hyper::server::conn::AddrIncoming,
+    >,
+    application_state: ApplicationState,
+) -> Result<(), pavex_runtime::Error> {
+    run_with_graceful_shutdown(server_builder, application_state, std::future::pending())
+        .await
+}
+pub async fn run_with_graceful_shutdown(
+    server_builder: pavex_runtime::hyper::server::Builder<
+        pavex_runtime::hyper::server::conn::AddrIncoming,
+    >,
+    application_state: ApplicationState,
+    shutdown_signal: impl std::future::Future<Output = ()>,
+) -> Result<(), pavex_runtime::Error> {
+    let server_state = std::sync::Arc::new(ServerState {
+        router: build_router().map_err(pavex_runtime::Error::new)?,
+        application_state,
+    });
+    let service = build_service(server_state);
+    let make_service = pavex_runtime::hyper::service::make_service_fn(move |_| {
+        let service = service.clone();
+        async move { Ok::<_, std::convert::Infallible>(service) }
+    });
+    server_builder
+        .serve(make_service)
+        .with_graceful_shutdown(shutdown_signal)
+        .await
+        .map_err(pavex_runtime::Error::new)
+}
+pub fn into_service(
+    application_state: ApplicationState,
+) -> Result<
+    impl pavex_runtime::hyper::service::Service<
+        pavex_runtime::http::Request<pavex_runtime::hyper::body::Body>,
+        Response = pavex_runtime::response::Response,
+        Error = std::convert::Infallible,
+    > + Clone,
+    pavex_runtime::Error,
+> {
+    let server_state = std::sync::Arc::new(ServerState {
+        router: build_router().map_err(pavex_runtime::Error::new)?,
+        application_state,
+    });
+    Ok(build_service(server_state))
+}
+fn build_service(
+    server_state: std::sync::Arc<ServerState>,
+) -> impl pavex_runtime::hyper::service::Service<
+    pavex_runtime::http::Request<pavex_runtime::hyper::body::Body>,
+    Response = pavex_runtime::response::Response,
+    Error = std::convert::Infallible,
+    Future = impl std::future::Future<
+        Output = Result<pavex_runtime::response::Response, std::convert::Infallible>,
+    > + Send,
+> + Clone {
+    pavex_runtime::hyper::service::service_fn(move |request| {
+        let server_state = server_state.clone();
+        async move {
+            Ok::<_, std::convert::Infallible>(route_request(request, server_state).await)
+        }
+    })
+}
+fn build_router() -> Result<
+    pavex_runtime::routing::Router<u32>,
+    pavex_runtime::routing::InsertError,
+> {
+    let mut router = pavex_runtime::routing::Router::new();
+    router.insert("/home", 0u32)?;
+    Ok(router)
+}
+async fn route_request(
+    request: pavex_runtime::http::Request<pavex_runtime::hyper::body::Body>,
+    server_state: std::sync::Arc<ServerState>,
+) -> pavex_runtime::response::Response {
+    let route_id = match server_state.router.at(request.uri().path()) {
+        Ok(route_id) => route_id,
+        Err(_) => {
+            return <pavex_runtime::http::StatusCode as pavex_runtime::response::IntoResponse>::into_response(
+                pavex_runtime::http::StatusCode::NOT_FOUND,
+            );
+        }
+    };
+    match route_id.value {
+        0u32 => {
+            match request.method() {
+                &pavex_runtime::http::Method::GET => {
+                    route_handler_0(
+                            server_state.application_state.s0.clone(),
+                            server_state.application_state.s1.clone(),
+                        )
+                        .await
+                }
+                _ => {
+                    let header_value = pavex_runtime::http::HeaderValue::from_static(
+                        "GET",
+                    );
+                    let mut response = <pavex_runtime::http::StatusCode as pavex_runtime::response::IntoResponse>::into_response(
+                        pavex_runtime::http::StatusCode::METHOD_NOT_ALLOWED,
+                    );
+                    response
+                        .headers_mut()
+                        .insert(pavex_runtime::http::header::ALLOW, header_value);
+                    response
+                }
+            }
+        }
+        _ => {
+            <pavex_runtime::http::StatusCode as pavex_runtime::response::IntoResponse>::into_response(
+                pavex_runtime::http::StatusCode::NOT_FOUND,
+            )
+        }
+    }
+}
+pub async fn route_handler_0(
+    v0: app::HttpClient,
+    v1: app::Cache,
+) -> http::Response<
+    http_body::combinators::BoxBody<bytes::Bytes, pavex_runtime::Error>,
+> {
+    let v2 = app::handler(v0, v1);
+    <http::Response::<
+        http_body::combinators::BoxBody::<bytes::Bytes, pavex_runtime::Error>,
+    > as pavex_runtime::response::IntoResponse>::into_response(v2)
+}
\ No newline at end of file
@@ -0,0 +1,44 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+use pavex_runtime::response::Response;
+
+#[derive(Clone)]
+pub struct HttpClient;
+
+#[derive(Debug)]
+pub struct HttpClientError;
+
+impl std::fmt::Display for HttpClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Failed to build the HTTP client")
+    }
+}
+
+impl std::error::Error for HttpClientError {}
+
+pub fn http_client() -> Result<HttpClient, HttpClientError> {
+    todo!()
+}
+
+#[derive(Clone)]
+pub struct Cache;
+
+// It doesn't implement `std::error::Error`: it can't be exposed as the source of
+// `ApplicationStateError`.
+#[derive(Debug)]
+pub struct CacheError;
+
+pub fn cache() -> Result<Cache, CacheError> {
+    todo!()
+}
+
+pub fn handler(_http_client: HttpClient, _cache: Cache) -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::http_client), Lifecycle::Singleton);
+    bp.constructor(f!(crate::cache), Lifecycle::Singleton);
+    bp.route(GET, "/home", f!(crate::handler));
+    bp
+}
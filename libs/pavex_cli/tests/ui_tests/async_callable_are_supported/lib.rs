@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub struct Logger;
 
@@ -40,6 +40,6 @@ pub fn blueprint() -> AppBlueprint {
     bp.constructor(f!(crate::http_client), Lifecycle::Singleton);
     bp.constructor(f!(crate::extract_path), Lifecycle::RequestScoped);
     bp.constructor(f!(crate::logger), Lifecycle::Transient);
-    bp.route(f!(crate::stream_file), "/home");
+    bp.route(GET, "/home", f!(crate::stream_file));
     bp
 }
@@ -0,0 +1,11 @@
+use pavex_builder::{f, router::GET, AppBlueprint};
+
+pub fn handler() -> pavex_runtime::response::Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.route(GET, "/home", f!(crate::handlr));
+    bp
+}
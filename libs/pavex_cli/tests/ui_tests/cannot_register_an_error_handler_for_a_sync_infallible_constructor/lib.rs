@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub fn infallible_constructor() -> PathBuf {
     todo!()
@@ -21,6 +21,6 @@ pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
     bp.constructor(f!(crate::infallible_constructor), Lifecycle::RequestScoped)
         .error_handler(f!(crate::error_handler));
-    bp.route(f!(crate::request_handler), "/home");
+    bp.route(GET, "/home", f!(crate::request_handler));
     bp
 }
@@ -4,7 +4,7 @@ use bytes::{Bytes, BytesMut};
 use http::response::Parts;
 use http::status::StatusCode;
 use http_body::{Empty, Full};
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub fn response() -> pavex_runtime::response::Response {
     todo!()
@@ -60,18 +60,18 @@ pub fn cow_static_u8_slice() -> Cow<'static, [u8]> {
 
 pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
-    bp.route(f!(crate::response), "/response");
-    bp.route(f!(crate::static_str), "/static_str");
-    bp.route(f!(crate::string), "/string");
-    bp.route(f!(crate::vec_u8), "/vec_u8");
-    bp.route(f!(crate::cow_static_str), "/cow_static_str");
-    bp.route(f!(crate::bytes), "/bytes");
-    bp.route(f!(crate::bytes_mut), "/bytes_mut");
-    bp.route(f!(crate::empty), "/empty");
-    bp.route(f!(crate::status_code), "/status_code");
-    bp.route(f!(crate::parts), "/parts");
-    bp.route(f!(crate::full), "/full");
-    bp.route(f!(crate::static_u8_slice), "/static_u8_slice");
-    bp.route(f!(crate::cow_static_u8_slice), "/cow_static_u8_slice");
+    bp.route(GET, "/response", f!(crate::response));
+    bp.route(GET, "/static_str", f!(crate::static_str));
+    bp.route(GET, "/string", f!(crate::string));
+    bp.route(GET, "/vec_u8", f!(crate::vec_u8));
+    bp.route(GET, "/cow_static_str", f!(crate::cow_static_str));
+    bp.route(GET, "/bytes", f!(crate::bytes));
+    bp.route(GET, "/bytes_mut", f!(crate::bytes_mut));
+    bp.route(GET, "/empty", f!(crate::empty));
+    bp.route(GET, "/status_code", f!(crate::status_code));
+    bp.route(GET, "/parts", f!(crate::parts));
+    bp.route(GET, "/full", f!(crate::full));
+    bp.route(GET, "/static_u8_slice", f!(crate::static_u8_slice));
+    bp.route(GET, "/cow_static_u8_slice", f!(crate::cow_static_u8_slice));
     bp
 }
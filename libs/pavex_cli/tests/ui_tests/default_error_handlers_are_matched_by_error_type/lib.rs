@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+use pavex_runtime::{http::Request, hyper::body::Body, response::Response};
+
+pub struct Logger;
+
+pub fn extract_path(_inner: Request<Body>) -> Result<PathBuf, ExtractPathError<String>> {
+    todo!()
+}
+
+#[derive(Debug)]
+pub struct ExtractPathError<T>(T);
+
+pub fn handle_extract_path_error(
+    _e: &ExtractPathError<String>,
+    _logger: Logger,
+) -> pavex_runtime::response::Response {
+    todo!()
+}
+
+pub fn logger() -> Result<Logger, LoggerError> {
+    todo!()
+}
+
+#[derive(Debug)]
+pub struct LoggerError;
+
+pub fn handle_logger_error(_e: &LoggerError) -> Response {
+    todo!()
+}
+
+pub fn handle_any_extract_path_error(_e: &ExtractPathError<String>) -> Response {
+    todo!()
+}
+
+pub fn request_handler(
+    _inner: PathBuf,
+    _logger: Logger,
+    _http_client: HttpClient,
+) -> Result<Response, HandlerError> {
+    todo!()
+}
+
+#[derive(Debug)]
+pub struct HandlerError;
+
+pub fn handle_handler_error(_e: &HandlerError) -> Response {
+    todo!()
+}
+
+pub fn handle_handler_error_at_root(_e: &HandlerError) -> Response {
+    todo!()
+}
+
+#[derive(Clone)]
+pub struct Config;
+
+pub fn config() -> Config {
+    todo!()
+}
+
+#[derive(Clone)]
+pub struct HttpClient;
+
+#[derive(Debug)]
+pub struct HttpClientError;
+
+impl std::fmt::Display for HttpClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Failed to build the HTTP client")
+    }
+}
+
+impl std::error::Error for HttpClientError {}
+
+pub fn http_client(_config: Config) -> Result<HttpClient, HttpClientError> {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.error_handler_for::<LoggerError>(f!(crate::handle_logger_error));
+    bp.error_handler_for::<HandlerError>(f!(crate::handle_handler_error_at_root));
+    // The error handler registered against the constructor takes precedence.
+    bp.error_handler_for::<ExtractPathError<String>>(f!(crate::handle_any_extract_path_error));
+    bp.constructor(f!(crate::http_client), Lifecycle::Singleton);
+    bp.constructor(f!(crate::extract_path), Lifecycle::RequestScoped)
+        .error_handler(f!(crate::handle_extract_path_error));
+    bp.constructor(f!(crate::logger), Lifecycle::Transient);
+    bp.nest("/api", api_blueprint());
+    bp
+}
+
+fn api_blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    // It takes precedence over the one registered against the parent blueprint.
+    bp.error_handler_for::<HandlerError>(f!(crate::handle_handler_error));
+    bp.route(GET, "/home", f!(crate::request_handler));
+    bp
+}
@@ -0,0 +1,54 @@
+use pavex_builder::{f, router::GET, AppBlueprint};
+use pavex_runtime::response::Response;
+
+pub use private::PrivateError;
+
+#[derive(Debug)]
+pub struct HandlerError;
+
+#[derive(Debug)]
+pub struct OtherError;
+
+#[derive(Debug)]
+pub struct UnhandledError;
+
+mod private {
+    #[derive(Debug)]
+    pub struct PrivateError;
+}
+
+pub fn handler() -> Result<Response, HandlerError> {
+    todo!()
+}
+
+pub fn unhandled() -> Result<Response, UnhandledError> {
+    todo!()
+}
+
+pub fn handle_handler_error(_e: &HandlerError) -> Response {
+    todo!()
+}
+
+pub fn handle_handler_error_again(_e: &HandlerError) -> Response {
+    todo!()
+}
+
+pub fn handle_other_error(_e: OtherError) -> Response {
+    todo!()
+}
+
+pub fn handle_private_error(_e: &PrivateError) -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.error_handler_for::<HandlerError>(f!(crate::handle_handler_error));
+    bp.error_handler_for::<HandlerError>(f!(crate::handle_handler_error_again));
+    bp.error_handler_for::<OtherError>(f!(crate::handle_other_error));
+    // `std::any::type_name` spells it as `app::private::PrivateError`, which can't be imported.
+    bp.error_handler_for::<PrivateError>(f!(crate::handle_private_error));
+    bp.route(GET, "/home", f!(crate::handler));
+    bp.route(GET, "/unhandled", f!(crate::unhandled));
+    bp
+}
@@ -0,0 +1,24 @@
+use pavex_builder::{f, router::GET, AppBlueprint};
+use pavex_runtime::http::uri::{InvalidUri, Uri};
+use pavex_runtime::http::StatusCode;
+use pavex_runtime::response::{IntoResponse, Response};
+
+pub fn handler() -> Result<Response, InvalidUri> {
+    let location: Uri = "not a valid URI".parse()?;
+    Ok(location.to_string().into_response())
+}
+
+pub fn handle_invalid_uri(_e: &InvalidUri) -> Response {
+    let mut response = "Invalid URI".into_response();
+    *response.status_mut() = StatusCode::BAD_REQUEST;
+    response
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    // `std::any::type_name` spells `InvalidUri` as `http::uri::InvalidUri`, but `http` is not a
+    // direct dependency of this crate: it's re-exported by `pavex_runtime`.
+    bp.error_handler_for::<InvalidUri>(f!(crate::handle_invalid_uri));
+    bp.route(GET, "/home", f!(crate::handler));
+    bp
+}
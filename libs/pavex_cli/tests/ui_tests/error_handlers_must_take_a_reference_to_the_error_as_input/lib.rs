@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 use pavex_runtime::response::Response;
 
 #[derive(Debug)]
@@ -20,6 +20,6 @@ pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
     bp.constructor(f!(crate::fallible_constructor), Lifecycle::RequestScoped)
         .error_handler(f!(crate::error_handler));
-    bp.route(f!(crate::handler), "/home");
+    bp.route(GET, "/home", f!(crate::handler));
     bp
 }
@@ -0,0 +1,70 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+use pavex_runtime::http::{Request, StatusCode};
+use pavex_runtime::hyper::Body;
+use pavex_runtime::response::{IntoResponse, Response};
+
+// The errors seen by the error observers, in the order they were observed.
+static OBSERVED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[derive(Debug)]
+pub struct CustomError(&'static str);
+
+impl Display for CustomError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} failed", self.0)
+    }
+}
+
+impl std::error::Error for CustomError {}
+
+pub struct First;
+
+pub struct Second;
+
+pub fn first(request: &Request<Body>) -> Result<First, CustomError> {
+    match request.uri().query() {
+        Some("fail=first") => Err(CustomError("first")),
+        _ => Ok(First),
+    }
+}
+
+pub fn second(request: &Request<Body>) -> Result<Second, CustomError> {
+    match request.uri().query() {
+        Some("fail=second") => Err(CustomError("second")),
+        _ => Ok(Second),
+    }
+}
+
+pub fn handler(_first: First, _second: Second) -> Response {
+    "ok".into_response()
+}
+
+// It handles the errors of both `first` and `second`.
+pub fn error_handler(_e: &CustomError) -> Response {
+    let observed = std::mem::take(&mut *OBSERVED.lock().unwrap());
+    let mut response = observed.join(", ").into_response();
+    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    response
+}
+
+pub fn log_error(e: &CustomError) {
+    OBSERVED.lock().unwrap().push(format!("log: {e}"));
+}
+
+pub fn trace_error(e: &pavex_runtime::Error) {
+    OBSERVED.lock().unwrap().push(format!("trace: {e}"));
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.error_handler_for::<CustomError>(f!(crate::error_handler));
+    bp.constructor(f!(crate::first), Lifecycle::RequestScoped);
+    bp.constructor(f!(crate::second), Lifecycle::RequestScoped);
+    bp.route(GET, "/home", f!(crate::handler));
+    bp.error_observer(f!(crate::log_error));
+    bp.error_observer(f!(crate::trace_error));
+    bp
+}
@@ -0,0 +1,51 @@
+use std::sync::Mutex;
+
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+use pavex_runtime::http::StatusCode;
+use pavex_runtime::response::{IntoResponse, Response};
+
+// The errors seen by the error observers, in the order they were observed.
+static OBSERVED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+pub struct Logger;
+
+pub fn logger() -> Logger {
+    Logger
+}
+
+pub fn handler() -> Result<Response, pavex_runtime::Error> {
+    Err(pavex_runtime::Error::new("Something went wrong"))
+}
+
+pub fn error_handler(_e: &pavex_runtime::Error) -> Response {
+    let mut response = OBSERVED.lock().unwrap().join(", ").into_response();
+    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    response
+}
+
+pub fn log_error(e: &pavex_runtime::Error, _logger: Logger) {
+    OBSERVED.lock().unwrap().push(format!("log: {e}"));
+}
+
+pub fn trace_error(_e: &pavex_runtime::Error) {
+    OBSERVED.lock().unwrap().push("trace".into());
+}
+
+#[derive(Debug)]
+pub struct OtherError;
+
+// It is never invoked: no component returns `OtherError`.
+pub fn observe_other_error(_e: &OtherError) {
+    OBSERVED.lock().unwrap().push("other".into());
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::logger), Lifecycle::Transient);
+    bp.route(GET, "/home", f!(crate::handler))
+        .error_handler(f!(crate::error_handler));
+    bp.error_observer(f!(crate::log_error));
+    bp.error_observer(f!(crate::trace_error));
+    bp.error_observer(f!(crate::observe_other_error));
+    bp
+}
@@ -0,0 +1,27 @@
+use pavex_builder::{f, router::GET, AppBlueprint};
+use pavex_runtime::response::Response;
+
+pub fn handler() -> Result<Response, pavex_runtime::Error> {
+    todo!()
+}
+
+pub fn error_handler(_e: &pavex_runtime::Error) -> Response {
+    todo!()
+}
+
+pub fn observer_with_output(_e: &pavex_runtime::Error) -> u64 {
+    todo!()
+}
+
+pub fn observer_taking_error_by_value(_e: pavex_runtime::Error) {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.route(GET, "/home", f!(crate::handler))
+        .error_handler(f!(crate::error_handler));
+    bp.error_observer(f!(crate::observer_with_output));
+    bp.error_observer(f!(crate::observer_taking_error_by_value));
+    bp
+}
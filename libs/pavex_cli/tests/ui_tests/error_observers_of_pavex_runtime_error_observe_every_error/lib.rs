@@ -0,0 +1,53 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+
+use pavex_builder::{f, router::GET, AppBlueprint};
+use pavex_runtime::http::StatusCode;
+use pavex_runtime::response::{IntoResponse, Response};
+
+// The errors seen by the error observers, in the order they were observed.
+static OBSERVED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+#[derive(Debug)]
+pub struct CustomError;
+
+impl Display for CustomError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Custom failure")
+    }
+}
+
+impl std::error::Error for CustomError {}
+
+pub fn handler() -> Result<Response, CustomError> {
+    Err(CustomError)
+}
+
+pub fn error_handler(_e: &CustomError) -> Response {
+    let mut response = OBSERVED.lock().unwrap().join(", ").into_response();
+    *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+    response
+}
+
+// It observes `CustomError` as well, after converting it into a `pavex_runtime::Error`.
+pub fn log_error(e: &pavex_runtime::Error) {
+    OBSERVED.lock().unwrap().push(format!("log: {e}"));
+}
+
+pub fn observe_custom_error(_e: &CustomError) {
+    OBSERVED.lock().unwrap().push("custom".into());
+}
+
+pub fn trace_error(e: &pavex_runtime::Error) {
+    OBSERVED.lock().unwrap().push(format!("trace: {e}"));
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.route(GET, "/home", f!(crate::handler))
+        .error_handler(f!(crate::error_handler));
+    bp.error_observer(f!(crate::log_error));
+    bp.error_observer(f!(crate::observe_custom_error));
+    bp.error_observer(f!(crate::trace_error));
+    bp
+}
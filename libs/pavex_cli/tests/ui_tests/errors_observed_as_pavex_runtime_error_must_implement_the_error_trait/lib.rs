@@ -0,0 +1,26 @@
+use pavex_builder::{f, router::GET, AppBlueprint};
+use pavex_runtime::response::Response;
+
+// It doesn't implement `std::error::Error`.
+#[derive(Debug)]
+pub struct CustomError;
+
+pub fn handler() -> Result<Response, CustomError> {
+    todo!()
+}
+
+pub fn error_handler(_e: &CustomError) -> Response {
+    todo!()
+}
+
+pub fn log_error(_e: &pavex_runtime::Error) {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.route(GET, "/home", f!(crate::handler))
+        .error_handler(f!(crate::error_handler));
+    bp.error_observer(f!(crate::log_error));
+    bp
+}
@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+use pavex_runtime::http::StatusCode;
+use pavex_runtime::response::{IntoResponse, ResponseError};
+use pavex_runtime::{http::Request, hyper::body::Body, response::Response};
+
+pub struct Logger;
+
+pub fn extract_path(inner: Request<Body>) -> Result<PathBuf, ExtractPathError<String>> {
+    Ok(PathBuf::from(inner.uri().path()))
+}
+
+#[derive(Debug)]
+pub struct ExtractPathError<T>(T);
+
+// An explicit error handler takes precedence over the `ResponseError` implementation.
+impl<T> ResponseError for ExtractPathError<T> {}
+
+pub fn handle_extract_path_error(
+    _e: &ExtractPathError<String>,
+    _logger: Logger,
+) -> pavex_runtime::response::Response {
+    StatusCode::BAD_REQUEST.into_response()
+}
+
+pub fn logger() -> Result<Logger, LoggerError> {
+    Ok(Logger)
+}
+
+#[derive(Debug)]
+pub struct LoggerError;
+
+impl ResponseError for LoggerError {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::SERVICE_UNAVAILABLE
+    }
+}
+
+pub fn request_handler(
+    _inner: PathBuf,
+    _logger: Logger,
+    _http_client: HttpClient,
+) -> Result<Response, HandlerError> {
+    Err(HandlerError)
+}
+
+#[derive(Debug)]
+pub struct HandlerError;
+
+impl ResponseError for HandlerError {}
+
+pub struct Config;
+
+#[derive(Clone)]
+pub struct HttpClient;
+
+#[derive(Debug)]
+pub struct HttpClientError;
+
+impl std::fmt::Display for HttpClientError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Failed to build the HTTP client")
+    }
+}
+
+impl std::error::Error for HttpClientError {}
+
+pub fn http_client(_config: Config) -> Result<HttpClient, HttpClientError> {
+    Ok(HttpClient)
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::http_client), Lifecycle::Singleton);
+    bp.constructor(f!(crate::extract_path), Lifecycle::RequestScoped)
+        .error_handler(f!(crate::handle_extract_path_error));
+    bp.constructor(f!(crate::logger), Lifecycle::Transient);
+    bp.route(GET, "/home", f!(crate::request_handler));
+    bp
+}
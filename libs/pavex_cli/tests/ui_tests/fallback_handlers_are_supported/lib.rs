@@ -0,0 +1,30 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+use pavex_runtime::response::Response;
+
+pub struct Config;
+
+#[derive(Clone)]
+pub struct HttpClient;
+
+pub fn http_client(_config: Config) -> HttpClient {
+    todo!()
+}
+
+pub fn home() -> Response {
+    todo!()
+}
+
+pub fn not_found(
+    _request: pavex_runtime::http::Request<pavex_runtime::hyper::body::Body>,
+    _http_client: HttpClient,
+) -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::http_client), Lifecycle::Singleton);
+    bp.route(GET, "/home", f!(crate::home));
+    bp.fallback(f!(crate::not_found));
+    bp
+}
@@ -0,0 +1,27 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+use pavex_runtime::extract::query::{InvalidQueryParams, QueryParams};
+use pavex_runtime::response::Response;
+
+#[derive(serde::Deserialize)]
+pub struct SearchParams {
+    pub q: String,
+}
+
+pub fn search(_params: QueryParams<SearchParams>) -> Response {
+    todo!()
+}
+
+pub fn invalid_query_params(_e: &InvalidQueryParams) -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(
+        f!(pavex_runtime::extract::query::QueryParams::<crate::SearchParms>::extract),
+        Lifecycle::RequestScoped,
+    )
+    .error_handler(f!(crate::invalid_query_params));
+    bp.route(GET, "/search", f!(crate::search));
+    bp
+}
@@ -0,0 +1,56 @@
+use pavex_builder::{
+    f,
+    router::{GET, POST},
+    AppBlueprint, Lifecycle,
+};
+use pavex_runtime::json::{Json, JsonError};
+use pavex_runtime::response::Response;
+
+#[derive(serde::Deserialize)]
+pub struct NewUser {
+    pub name: String,
+}
+
+#[derive(serde::Serialize)]
+pub struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+pub trait Database {}
+
+pub struct Postgres;
+
+impl Database for Postgres {}
+
+pub struct Connection<T>(T);
+
+pub fn connect<T: Database>() -> Connection<T> {
+    todo!()
+}
+
+pub fn create_user(_body: Json<NewUser>) -> Json<User> {
+    todo!()
+}
+
+pub fn ping(_connection: &Connection<Postgres>) -> Response {
+    todo!()
+}
+
+pub fn invalid_json(_e: &JsonError) -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    // The generic parameters are inferred from the types requested by the handlers.
+    bp.constructor(
+        f!(pavex_runtime::json::Json::extract),
+        Lifecycle::RequestScoped,
+    )
+    .error_handler(f!(crate::invalid_json));
+    bp.constructor(f!(crate::connect), Lifecycle::RequestScoped);
+    bp.route(GET, "/ping", f!(crate::ping));
+    bp.route(POST, "/users", f!(crate::create_user));
+    bp
+}
@@ -0,0 +1,22 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+
+pub trait Database {}
+
+pub struct Postgres;
+
+pub struct Connection<T>(T);
+
+pub fn connect<T: Database>() -> Connection<T> {
+    todo!()
+}
+
+pub fn handler(_connection: Connection<Postgres>) -> pavex_runtime::response::Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::connect), Lifecycle::RequestScoped);
+    bp.route(GET, "/home", f!(crate::handler));
+    bp
+}
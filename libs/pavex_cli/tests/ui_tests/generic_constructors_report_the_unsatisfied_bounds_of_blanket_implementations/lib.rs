@@ -0,0 +1,28 @@
+use pavex_builder::{f, router::POST, AppBlueprint, Lifecycle};
+use pavex_runtime::json::{Json, JsonError};
+use pavex_runtime::response::Response;
+
+// `serde::de::DeserializeOwned` is implemented via a blanket implementation
+// for all types that implement `serde::Deserialize`, which this type doesn't.
+pub struct NewUser {
+    pub name: String,
+}
+
+pub fn create_user(_body: Json<NewUser>) -> Response {
+    todo!()
+}
+
+pub fn invalid_json(_e: &JsonError) -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(
+        f!(pavex_runtime::json::Json::extract),
+        Lifecycle::RequestScoped,
+    )
+    .error_handler(f!(crate::invalid_json));
+    bp.route(POST, "/users", f!(crate::create_user));
+    bp
+}
@@ -0,0 +1,33 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+
+pub struct Postgres;
+
+pub struct Settings<T>(T);
+
+pub fn settings<T>() -> Settings<T> {
+    todo!()
+}
+
+pub struct Connection<T>(T);
+
+pub fn connect<T>(_settings: &Settings<T>) -> Connection<T> {
+    todo!()
+}
+
+pub fn handler(_connection: Connection<Postgres>) -> pavex_runtime::response::Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(
+        f!(crate::settings::<crate::Postgres>),
+        Lifecycle::RequestScoped,
+    );
+    bp.constructor(
+        f!(crate::connect::<crate::Postgres>),
+        Lifecycle::RequestScoped,
+    );
+    bp.route(GET, "/home", f!(crate::handler));
+    bp
+}
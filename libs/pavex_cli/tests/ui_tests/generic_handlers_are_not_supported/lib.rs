@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint};
+use pavex_builder::{f, router::GET, AppBlueprint};
 
 pub fn stream_file<T>(_inner: T) -> pavex_runtime::response::Response {
     todo!()
@@ -6,6 +6,6 @@ pub fn stream_file<T>(_inner: T) -> pavex_runtime::response::Response {
 
 pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
-    bp.route(f!(crate::stream_file::<std::path::PathBuf>), "/home");
+    bp.route(GET, "/home", f!(crate::stream_file));
     bp
 }
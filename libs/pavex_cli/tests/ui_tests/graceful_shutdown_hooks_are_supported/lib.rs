@@ -0,0 +1,24 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+
+#[derive(Clone)]
+pub struct ConnectionPool;
+
+pub fn connection_pool() -> ConnectionPool {
+    todo!()
+}
+
+pub fn home() -> pavex_runtime::response::Response {
+    todo!()
+}
+
+pub async fn flush_pool(_pool: &ConnectionPool) {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::connection_pool), Lifecycle::Singleton);
+    bp.route(GET, "/home", f!(crate::home));
+    bp.on_shutdown(f!(crate::flush_pool));
+    bp
+}
@@ -0,0 +1,36 @@
+use pavex_builder::{
+    f,
+    router::{ANY, GET, POST},
+    AppBlueprint,
+};
+use pavex_runtime::response::Response;
+
+pub fn get_home() -> Response {
+    todo!()
+}
+
+pub fn post_home() -> Response {
+    todo!()
+}
+
+pub fn any_method() -> Response {
+    todo!()
+}
+
+pub fn get_mixed() -> Response {
+    todo!()
+}
+
+pub fn any_mixed() -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.route(GET, "/home", f!(crate::get_home));
+    bp.route(POST, "/home", f!(crate::post_home));
+    bp.route(ANY, "/any", f!(crate::any_method));
+    bp.route(GET, "/mixed", f!(crate::get_mixed));
+    bp.route(ANY, "/mixed", f!(crate::any_mixed));
+    bp
+}
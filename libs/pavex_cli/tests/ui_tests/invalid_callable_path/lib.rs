@@ -1,13 +1,13 @@
-use pavex_builder::{AppBlueprint, RawCallable};
+use pavex_builder::{router::GET, AppBlueprint, RawCallable};
 
 pub fn my_f() {}
 
 pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
     let callable = RawCallable {
-        callable: my_f,
         import_path: "my_f,",
+        registered_at: "app",
     };
-    bp.route(callable, "/home");
+    bp.route(GET, "/home", callable);
     bp
 }
@@ -0,0 +1,74 @@
+use pavex_builder::{f, router::POST, AppBlueprint, Lifecycle};
+use pavex_runtime::extract::query::{InvalidQueryParams, QueryParams};
+use pavex_runtime::json::{Json, JsonConfig, JsonError};
+use pavex_runtime::response::{IntoResponse, Response, ResponseError};
+
+#[derive(serde::Deserialize)]
+pub struct NewUser {
+    pub name: String,
+}
+
+#[derive(serde::Deserialize)]
+pub struct CreateUserParams {
+    pub id: u64,
+}
+
+#[derive(serde::Serialize)]
+pub struct User {
+    pub id: u64,
+    pub name: String,
+}
+
+// `Json::extract` takes the request by value, `QueryParams::extract` borrows it.
+pub fn create_user(body: Json<NewUser>, params: QueryParams<CreateUserParams>) -> Json<User> {
+    Json(User {
+        id: params.0.id,
+        name: body.0.name,
+    })
+}
+
+pub fn invalid_json(e: &JsonError) -> Response {
+    let mut response = e.to_string().into_response();
+    *response.status_mut() = e.status_code();
+    response
+}
+
+pub fn invalid_query_params(e: &InvalidQueryParams) -> Response {
+    e.error_response()
+}
+
+#[derive(serde::Deserialize)]
+pub struct NewComment {
+    pub text: String,
+}
+
+pub fn json_config() -> JsonConfig {
+    JsonConfig { body_limit: 32 }
+}
+
+pub fn create_comment(body: Json<NewComment>) -> Response {
+    body.0.text.into_response()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::json_config), Lifecycle::Singleton);
+    bp.constructor(
+        f!(pavex_runtime::json::Json::<crate::NewUser>::extract),
+        Lifecycle::RequestScoped,
+    )
+    .error_handler(f!(crate::invalid_json));
+    bp.constructor(
+        f!(pavex_runtime::extract::query::QueryParams::<crate::CreateUserParams>::extract),
+        Lifecycle::RequestScoped,
+    )
+    .error_handler(f!(crate::invalid_query_params));
+    // No error handler: failures are converted into a response via `ResponseError`.
+    bp.constructor(
+        f!(pavex_runtime::json::Json::<crate::NewComment>::extract_with_config),
+        Lifecycle::RequestScoped,
+    );
+    bp.route(POST, "/users", f!(crate::create_user));
+    bp.route(POST, "/comments", f!(crate::create_comment));
+    bp
+}
@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint};
+use pavex_builder::{f, router::GET, AppBlueprint};
 
 pub fn handler() -> pavex_runtime::response::Response {
     todo!()
@@ -6,6 +6,6 @@ pub fn handler() -> pavex_runtime::response::Response {
 
 pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
-    bp.route(f!(handler), "/home");
+    bp.route(GET, "/home", f!(handler));
     bp
 }
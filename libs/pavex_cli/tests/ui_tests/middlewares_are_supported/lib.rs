@@ -0,0 +1,35 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+use pavex_runtime::middleware::Next;
+use pavex_runtime::response::Response;
+
+#[derive(Clone)]
+pub struct Logger;
+
+pub fn logger() -> Logger {
+    todo!()
+}
+
+pub async fn log_requests(next: Next, _logger: &Logger) -> Response {
+    next.run().await
+}
+
+pub async fn require_auth(next: Next) -> Response {
+    next.run().await
+}
+
+pub fn home() -> Response {
+    todo!()
+}
+
+pub fn admin(_logger: &Logger) -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::logger), Lifecycle::Singleton);
+    bp.wrap(f!(crate::log_requests));
+    bp.route(GET, "/home", f!(crate::home));
+    bp.route(GET, "/admin", f!(crate::admin)).wrap(f!(crate::require_auth));
+    bp
+}
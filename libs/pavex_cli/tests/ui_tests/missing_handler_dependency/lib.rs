@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use pavex_builder::{f, AppBlueprint};
+use pavex_builder::{f, router::GET, AppBlueprint};
 
 pub fn stream_file(_inner: PathBuf) -> pavex_runtime::response::Response {
     todo!()
@@ -8,6 +8,6 @@ pub fn stream_file(_inner: PathBuf) -> pavex_runtime::response::Response {
 
 pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
-    bp.route(f!(crate::stream_file), "/home");
+    bp.route(GET, "/home", f!(crate::stream_file));
     bp
 }
@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub fn header1() -> http_01::header::HeaderName {
     todo!()
@@ -19,6 +19,6 @@ pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
     bp.constructor(f!(crate::header1), Lifecycle::RequestScoped);
     bp.constructor(f!(crate::header2), Lifecycle::RequestScoped);
-    bp.route(f!(crate::stream_file), "/home");
+    bp.route(GET, "/home", f!(crate::stream_file));
     bp
 }
@@ -0,0 +1,21 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+
+pub struct User;
+
+#[derive(Clone)]
+pub struct Config;
+
+pub fn admin() -> User {
+    todo!()
+}
+
+pub fn list_users(_user: User, _config: &Config) -> pavex_runtime::response::Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::admin), Lifecycle::RequestScoped);
+    bp.route(GET, "/users", f!(crate::list_users));
+    bp
+}
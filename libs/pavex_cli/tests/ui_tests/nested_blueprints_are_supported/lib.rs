@@ -0,0 +1,23 @@
+use admin::{Config, User};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+
+pub fn config() -> Config {
+    todo!()
+}
+
+pub fn anonymous() -> User {
+    todo!()
+}
+
+pub fn home(_user: User) -> pavex_runtime::response::Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::config), Lifecycle::Singleton);
+    bp.constructor(f!(crate::anonymous), Lifecycle::RequestScoped);
+    bp.route(GET, "/home", f!(crate::home));
+    bp.nest("/admin", admin::blueprint());
+    bp
+}
@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint};
+use pavex_builder::{f, router::GET, AppBlueprint};
 
 pub fn c() -> Box<dyn std::error::Error> {
     todo!()
@@ -6,6 +6,6 @@ pub fn c() -> Box<dyn std::error::Error> {
 
 pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
-    bp.route(f!(crate::c), "/home");
+    bp.route(GET, "/home", f!(crate::c));
     bp
 }
@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub fn request_scoped() -> Result<String, ErrorType> {
     todo!()
@@ -22,7 +22,7 @@ pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
     bp.constructor(f!(crate::request_scoped), Lifecycle::RequestScoped)
         .error_handler(f!(crate::error_handler));
-    bp.route(f!(crate::handler), "/home")
+    bp.route(GET, "/home", f!(crate::handler))
         .error_handler(f!(crate::error_handler));
     bp
 }
@@ -1,6 +1,6 @@
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
-#[derive(Clone)]
+// Singletons that are only ever borrowed are not required to implement `Clone`.
 pub struct Singleton;
 
 impl Singleton {
@@ -34,6 +34,6 @@ pub fn blueprint() -> AppBlueprint {
     bp.constructor(f!(crate::Singleton::new), Lifecycle::Singleton);
     bp.constructor(f!(crate::request_scoped), Lifecycle::RequestScoped);
     bp.constructor(f!(crate::transient), Lifecycle::Transient);
-    bp.route(f!(crate::stream_file), "/home");
+    bp.route(GET, "/home", f!(crate::stream_file));
     bp
 }
@@ -1,5 +1,5 @@
 use dep::{new_logger, Logger};
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub fn handler(logger: Logger) -> pavex_runtime::response::Response {
     todo!()
@@ -8,6 +8,6 @@ pub fn handler(logger: Logger) -> pavex_runtime::response::Response {
 pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
     bp.constructor(f!(new_logger), Lifecycle::Singleton);
-    bp.route(f!(crate::handler), "/home");
+    bp.route(GET, "/home", f!(crate::handler));
     bp
 }
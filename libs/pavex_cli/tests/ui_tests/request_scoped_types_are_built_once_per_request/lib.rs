@@ -0,0 +1,44 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+use pavex_runtime::middleware::Next;
+use pavex_runtime::response::Response;
+
+#[derive(Clone)]
+pub struct Logger;
+
+pub fn logger() -> Logger {
+    todo!()
+}
+
+#[derive(Clone)]
+pub struct Session;
+
+pub fn session() -> Session {
+    todo!()
+}
+
+pub async fn log_requests(next: Next, _logger: &Logger) -> Response {
+    next.run().await
+}
+
+pub async fn track_session(next: Next, _session: &Session) -> Response {
+    next.run().await
+}
+
+pub fn home(_session: Session) -> Response {
+    todo!()
+}
+
+pub fn about() -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::logger), Lifecycle::Singleton);
+    bp.constructor(f!(crate::session), Lifecycle::RequestScoped);
+    bp.wrap(f!(crate::log_requests));
+    bp.route(GET, "/home", f!(crate::home))
+        .wrap(f!(crate::track_session));
+    bp.route(GET, "/about", f!(crate::about));
+    bp
+}
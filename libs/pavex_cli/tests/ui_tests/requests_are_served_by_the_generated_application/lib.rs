@@ -0,0 +1,33 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+use pavex_runtime::http::StatusCode;
+use pavex_runtime::response::{IntoResponse, Response};
+
+pub struct Config;
+
+#[derive(Clone)]
+pub struct HttpClient;
+
+pub fn http_client(_config: Config) -> HttpClient {
+    HttpClient
+}
+
+pub fn home() -> Response {
+    "Welcome home!".into_response()
+}
+
+pub fn not_found(
+    request: pavex_runtime::http::Request<pavex_runtime::hyper::body::Body>,
+    _http_client: HttpClient,
+) -> Response {
+    let mut response = format!("`{}` does not exist", request.uri().path()).into_response();
+    *response.status_mut() = StatusCode::NOT_FOUND;
+    response
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::http_client), Lifecycle::Singleton);
+    bp.route(GET, "/home", f!(crate::home));
+    bp.fallback(f!(crate::not_found));
+    bp
+}
@@ -0,0 +1,20 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+use pavex_runtime::extract::RouteParams;
+use pavex_runtime::response::Response;
+
+pub struct HomeId(pub u32);
+
+pub fn home_id(_params: RouteParams) -> HomeId {
+    todo!()
+}
+
+pub fn get_home(_home_id: HomeId) -> Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::home_id), Lifecycle::RequestScoped);
+    bp.route(GET, "/home/:home_id", f!(crate::get_home));
+    bp
+}
@@ -0,0 +1,23 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+
+pub struct Session;
+
+pub fn session() -> Session {
+    todo!()
+}
+
+pub fn home() -> pavex_runtime::response::Response {
+    todo!()
+}
+
+pub fn close_session(_session: &Session) {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::session), Lifecycle::RequestScoped);
+    bp.route(GET, "/home", f!(crate::home));
+    bp.on_shutdown(f!(crate::close_session));
+    bp
+}
@@ -0,0 +1,37 @@
+use std::time::Duration;
+
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+
+#[derive(Clone)]
+pub struct Config;
+
+#[derive(Clone)]
+pub struct ConnectionPool;
+
+#[derive(Debug)]
+pub struct PoolError;
+
+impl std::fmt::Display for PoolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Failed to connect to the database")
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+pub async fn connection_pool(_config: Config) -> Result<ConnectionPool, PoolError> {
+    todo!()
+}
+
+pub fn handler(_pool: ConnectionPool) -> pavex_runtime::response::Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::connection_pool), Lifecycle::Singleton)
+        .startup_timeout(Duration::from_secs(5))
+        .retry(3, Duration::from_millis(500));
+    bp.route(GET, "/home", f!(crate::handler));
+    bp
+}
@@ -0,0 +1,63 @@
+use std::time::Duration;
+
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+
+pub struct Logger;
+
+pub async fn logger() -> Logger {
+    todo!()
+}
+
+#[derive(Clone)]
+pub struct Config;
+
+pub fn config() -> Config {
+    todo!()
+}
+
+#[derive(Clone)]
+pub struct HttpClient;
+
+pub async fn http_client(_config: Config) -> HttpClient {
+    todo!()
+}
+
+pub struct Credentials;
+
+pub fn credentials() -> Credentials {
+    todo!()
+}
+
+#[derive(Clone)]
+pub struct Pool;
+
+#[derive(Debug)]
+pub struct PoolError;
+
+pub async fn pool(_credentials: Credentials) -> Result<Pool, PoolError> {
+    todo!()
+}
+
+pub fn handler(
+    _logger: Logger,
+    _config: Config,
+    _client: HttpClient,
+    _pool: Pool,
+) -> pavex_runtime::response::Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(f!(crate::logger), Lifecycle::RequestScoped)
+        .startup_timeout(Duration::from_secs(1));
+    bp.constructor(f!(crate::config), Lifecycle::Singleton)
+        .startup_timeout(Duration::from_secs(1));
+    bp.constructor(f!(crate::http_client), Lifecycle::Singleton)
+        .retry(3, Duration::from_millis(100));
+    bp.constructor(f!(crate::credentials), Lifecycle::Singleton);
+    bp.constructor(f!(crate::pool), Lifecycle::Singleton)
+        .retry(3, Duration::from_millis(100));
+    bp.route(GET, "/home", f!(crate::handler));
+    bp
+}
@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint};
+use pavex_builder::{f, router::GET, AppBlueprint};
 
 pub struct Streamer;
 
@@ -10,6 +10,6 @@ impl Streamer {
 
 pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
-    bp.route(f!(crate::Streamer::stream_file), "/home");
+    bp.route(GET, "/home", f!(crate::Streamer::stream_file));
     bp
 }
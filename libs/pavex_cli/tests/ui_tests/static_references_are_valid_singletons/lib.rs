@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub fn static_str() -> &'static str {
     todo!()
@@ -11,6 +11,6 @@ pub fn handler(_x: &'static str) -> pavex_runtime::response::Response {
 pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
     bp.constructor(f!(crate::static_str), Lifecycle::Singleton);
-    bp.route(f!(crate::handler), "/handler");
+    bp.route(GET, "/handler", f!(crate::handler));
     bp
 }
@@ -0,0 +1,21 @@
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
+
+pub struct Logger<T>(T);
+
+pub fn new_logger<T>() -> Logger<T> {
+    todo!()
+}
+
+pub fn handler(_logger: Logger<String>) -> pavex_runtime::response::Response {
+    todo!()
+}
+
+pub fn blueprint() -> AppBlueprint {
+    let mut bp = AppBlueprint::new();
+    bp.constructor(
+        f!(crate::new_logger::<std::string::String, std::string::String>),
+        Lifecycle::Singleton,
+    );
+    bp.route(GET, "/home", f!(crate::handler));
+    bp
+}
@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub struct Logger<T>(T);
 
@@ -13,6 +13,6 @@ pub fn handler<T>(logger: Logger<T>) -> pavex_runtime::response::Response {
 pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
     bp.constructor(f!(crate::new_logger::<String>), Lifecycle::Singleton);
-    bp.route(f!(crate::handler::<std::string::String>), "/home");
+    bp.route(GET, "/home", f!(crate::handler::<std::string::String>));
     bp
 }
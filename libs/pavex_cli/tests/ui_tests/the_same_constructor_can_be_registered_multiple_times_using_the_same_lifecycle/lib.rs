@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub struct Logger;
 
@@ -18,6 +18,6 @@ pub fn blueprint() -> AppBlueprint {
     let mut bp = AppBlueprint::new();
     bp.constructor(f!(crate::new_logger), Lifecycle::Singleton);
     bp.constructor(f!(crate::new_logger), Lifecycle::RequestScoped);
-    bp.route(f!(crate::Streamer::stream_file), "/home");
+    bp.route(GET, "/home", f!(crate::Streamer::stream_file));
     bp
 }
@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub struct A;
 
@@ -74,6 +74,6 @@ pub fn blueprint() -> AppBlueprint {
         f!(<crate::C as crate::GenericTrait<std::string::String>>::a_method),
         Lifecycle::RequestScoped,
     );
-    bp.route(f!(crate::handler), "/home");
+    bp.route(GET, "/home", f!(crate::handler));
     bp
 }
@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub struct A;
 
@@ -29,6 +29,6 @@ pub fn blueprint() -> AppBlueprint {
         f!(<crate::B as crate::GenericTrait<crate::A>>::a_method_using_the_trait_generic_param),
         Lifecycle::RequestScoped,
     );
-    bp.route(f!(crate::handler), "/home");
+    bp.route(GET, "/home", f!(crate::handler));
     bp
 }
@@ -1,4 +1,4 @@
-use pavex_builder::{f, AppBlueprint, Lifecycle};
+use pavex_builder::{f, router::GET, AppBlueprint, Lifecycle};
 
 pub type MyTupleAlias = (bool, char, u8);
 
@@ -16,6 +16,6 @@ pub fn blueprint() -> AppBlueprint {
         f!(crate::constructor_with_output_tuple),
         Lifecycle::Singleton,
     );
-    bp.route(f!(crate::handler_with_input_tuple), "/home");
+    bp.route(GET, "/home", f!(crate::handler_with_input_tuple));
     bp
 }
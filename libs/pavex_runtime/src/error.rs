@@ -43,6 +43,16 @@ impl Error {
         }
     }
 
+    /// Create a new `Error` from a reference to another error.
+    ///
+    /// The new `Error` owns a snapshot of the original one: its `Display` and `Debug`
+    /// representations, as well as the whole chain of sources, are preserved.
+    /// `pavex` uses it to invoke error observers that take `&pavex_runtime::Error` as input
+    /// when a constructor or a request handler fails with a different error type.
+    pub fn from_ref<E: std::error::Error + ?Sized>(error: &E) -> Self {
+        Self::new(ErrorSnapshot::new(error))
+    }
+
     /// Convert an `Error` back into the underlying boxed trait object.
     pub fn into_inner(self) -> Box<dyn std::error::Error + Send + Sync> {
         self.inner
@@ -60,3 +70,93 @@ impl std::error::Error for Error {
         Some(&*self.inner)
     }
 }
+
+/// An owned copy of the observable behaviour of an error, used by [`Error::from_ref`].
+struct ErrorSnapshot {
+    display: String,
+    debug: String,
+    source: Option<Box<ErrorSnapshot>>,
+}
+
+impl ErrorSnapshot {
+    fn new<E: std::error::Error + ?Sized>(error: &E) -> Self {
+        Self {
+            display: error.to_string(),
+            debug: format!("{:?}", error),
+            source: error.source().map(|s| Box::new(ErrorSnapshot::new(s))),
+        }
+    }
+}
+
+impl fmt::Debug for ErrorSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.debug)
+    }
+}
+
+impl fmt::Display for ErrorSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.display)
+    }
+}
+
+impl std::error::Error for ErrorSnapshot {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|s| s as &(dyn std::error::Error + 'static))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error as _;
+    use std::fmt;
+
+    use super::Error;
+
+    #[derive(Debug)]
+    struct Outer(Inner);
+
+    #[derive(Debug)]
+    struct Inner;
+
+    impl fmt::Display for Outer {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Outer failure")
+        }
+    }
+
+    impl fmt::Display for Inner {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "Inner failure")
+        }
+    }
+
+    impl std::error::Error for Outer {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            Some(&self.0)
+        }
+    }
+
+    impl std::error::Error for Inner {}
+
+    #[test]
+    fn from_ref_preserves_the_display_and_debug_representations() {
+        let error = Error::from_ref(&Outer(Inner));
+        assert_eq!(error.to_string(), "Outer failure");
+        assert_eq!(format!("{:?}", error.into_inner()), "Outer(Inner)");
+    }
+
+    #[test]
+    fn from_ref_preserves_the_chain_of_sources() {
+        let error = Error::from_ref(&Outer(Inner));
+        let mut chain = vec![];
+        let mut current = error.source();
+        while let Some(e) = current {
+            chain.push(e.to_string());
+            current = e.source();
+        }
+        assert_eq!(chain, vec!["Outer failure", "Inner failure"]);
+    }
+}
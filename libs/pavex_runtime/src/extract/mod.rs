@@ -0,0 +1,11 @@
+//! Extract data from incoming requests.
+//!
+//! The types in this module are injected by `pavex` into the processing context of each
+//! incoming request: your constructors and request handlers can take them as inputs without
+//! having to register a constructor for them.  
+//! The exception is [`query::QueryParams`]: you need to register its constructor for each
+//! type you want to deserialize the query string into.
+pub use route::{InvalidRouteParams, RouteParams};
+
+pub mod query;
+pub mod route;
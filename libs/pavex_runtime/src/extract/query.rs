@@ -0,0 +1,173 @@
+//! Extract typed data from the query string of an incoming request.
+use std::fmt::{Display, Formatter};
+
+use http::{Request, StatusCode};
+use hyper::Body;
+use serde::de::DeserializeOwned;
+
+use crate::response::ResponseError;
+
+/// The query parameters of an incoming request, deserialized into a type of your choice.
+///
+/// `QueryParams` is not provided by the framework: you need to register its constructor,
+/// [`QueryParams::extract`]. Its generic parameter is inferred from the types requested by
+/// your handlers, therefore a single registration covers all of them.
+/// Failures are reported as [`InvalidQueryParams`]: unless you register an error handler for
+/// it, they are converted into a `400 Bad Request` response via its [`ResponseError`]
+/// implementation.
+///
+/// ```rust
+/// use pavex_runtime::extract::query::{InvalidQueryParams, QueryParams};
+/// use pavex_runtime::http::StatusCode;
+/// use pavex_runtime::response::{IntoResponse, Response};
+///
+/// #[derive(serde::Deserialize)]
+/// pub struct SearchParams {
+///     pub q: String,
+///     pub page: Option<u32>,
+/// }
+///
+/// pub fn search(params: QueryParams<SearchParams>) -> Response {
+///     todo!()
+/// }
+///
+/// pub fn invalid_query_params(_e: &InvalidQueryParams) -> Response {
+///     StatusCode::BAD_REQUEST.into_response()
+/// }
+/// ```
+///
+/// and then, in your blueprint:
+///
+/// ```rust,ignore
+/// bp.constructor(
+///     f!(pavex_runtime::extract::query::QueryParams::extract),
+///     Lifecycle::RequestScoped,
+/// )
+/// .error_handler(f!(crate::invalid_query_params));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryParams<T>(pub T);
+
+impl<T> QueryParams<T>
+where
+    T: DeserializeOwned,
+{
+    /// Deserialize the query string of the incoming request into `T`.
+    ///
+    /// A request without a query string is treated as if it had an empty one.
+    pub fn extract(request: &Request<Body>) -> Result<QueryParams<T>, InvalidQueryParams> {
+        let query = request.uri().query().unwrap_or_default();
+        serde_urlencoded::from_str(query)
+            .map(QueryParams)
+            .map_err(|e| InvalidQueryParams {
+                message: e.to_string(),
+            })
+    }
+
+    /// Consume the extractor and return the deserialized query parameters.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// The error returned by [`QueryParams::extract`] when the query string of the incoming request
+/// cannot be deserialized into the target type.
+#[derive(Debug)]
+pub struct InvalidQueryParams {
+    message: String,
+}
+
+impl Display for InvalidQueryParams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid query parameters: {}", self.message)
+    }
+}
+
+impl std::error::Error for InvalidQueryParams {}
+
+impl ResponseError for InvalidQueryParams {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{Request, StatusCode};
+    use hyper::Body;
+
+    use crate::response::ResponseError;
+
+    use super::QueryParams;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct SearchParams {
+        q: String,
+        page: Option<u32>,
+    }
+
+    fn request(uri: &str) -> Request<Body> {
+        Request::builder().uri(uri).body(Body::empty()).unwrap()
+    }
+
+    #[test]
+    fn a_missing_query_string_is_treated_as_an_empty_one() {
+        let params: QueryParams<Vec<(String, String)>> =
+            QueryParams::extract(&request("/search")).unwrap();
+        assert!(params.into_inner().is_empty());
+
+        #[derive(Debug, serde::Deserialize)]
+        struct Pagination {
+            page: Option<u32>,
+        }
+        let params: QueryParams<Pagination> = QueryParams::extract(&request("/search")).unwrap();
+        assert_eq!(params.0.page, None);
+
+        assert!(QueryParams::<SearchParams>::extract(&request("/search")).is_err());
+    }
+
+    #[test]
+    fn percent_encoded_values_are_decoded() {
+        let params: QueryParams<SearchParams> =
+            QueryParams::extract(&request("/search?q=caf%C3%A9%20au+lait&page=2")).unwrap();
+        assert_eq!(
+            params.into_inner(),
+            SearchParams {
+                q: "café au lait".into(),
+                page: Some(2),
+            }
+        );
+    }
+
+    #[test]
+    fn repeated_keys_are_preserved_in_order_for_sequences() {
+        let params: QueryParams<Vec<(String, String)>> =
+            QueryParams::extract(&request("/search?tag=a&tag=b&q=c")).unwrap();
+        assert_eq!(
+            params.into_inner(),
+            vec![
+                ("tag".to_string(), "a".to_string()),
+                ("tag".to_string(), "b".to_string()),
+                ("q".to_string(), "c".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_keys_are_rejected_for_structs() {
+        let e = QueryParams::<SearchParams>::extract(&request("/search?q=a&q=b")).unwrap_err();
+        assert!(e.to_string().contains("duplicate field `q`"), "{e}");
+    }
+
+    #[test]
+    fn invalid_query_params_are_converted_into_a_400() {
+        let e = QueryParams::<SearchParams>::extract(&request("/search?q=rust&page=first"))
+            .unwrap_err();
+        assert!(
+            e.to_string().starts_with("Invalid query parameters: "),
+            "{e}"
+        );
+        assert_eq!(e.status_code(), StatusCode::BAD_REQUEST);
+        assert_eq!(e.error_response().status(), StatusCode::BAD_REQUEST);
+    }
+}
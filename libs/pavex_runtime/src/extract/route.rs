@@ -0,0 +1,381 @@
+//! Extract the parameters captured by the route that matched an incoming request.
+use std::fmt::{Display, Formatter};
+
+use http::StatusCode;
+use serde::de::value::{MapDeserializer, StrDeserializer};
+use serde::de::{DeserializeSeed, EnumAccess, IntoDeserializer, VariantAccess, Visitor};
+use serde::{forward_to_deserialize_any, Deserialize, Deserializer};
+
+use crate::response::ResponseError;
+
+/// The parameters captured by the route that matched an incoming request.
+///
+/// If a request handler has been registered for `/users/:id`, an incoming request for
+/// `/users/42` will capture a route parameter named `id` with `42` as its value.
+/// Values are percent-decoded: `/users/John%20Doe` captures `John Doe`.
+///
+/// `RouteParams` is provided by the framework: constructors and request handlers can take it
+/// as input without registering a constructor for it.
+/// You will usually want to register a constructor that deserializes the route parameters into
+/// a dedicated type via [`RouteParams::deserialize`]:
+///
+/// ```rust
+/// use pavex_runtime::extract::{InvalidRouteParams, RouteParams};
+///
+/// #[derive(serde::Deserialize)]
+/// pub struct UserRouteParams {
+///     pub id: u64,
+/// }
+///
+/// pub fn user_route_params(params: RouteParams) -> Result<UserRouteParams, InvalidRouteParams> {
+///     params.deserialize()
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RouteParams(Vec<(String, String)>);
+
+impl RouteParams {
+    /// Retrieve the value of the route parameter named `key`, if it was captured.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Iterate over the captured route parameters, as `(name, value)` pairs, in the order they
+    /// appear in the route path.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// The number of captured route parameters.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// `true` if the matched route did not capture any parameter.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Deserialize the captured route parameters into a type of your choice.
+    ///
+    /// The route parameters are exposed as a map: you will usually deserialize them into a
+    /// struct with a field for each parameter.  
+    /// Parameter values are parsed into the type of the corresponding field (e.g. `u64`,
+    /// `bool`, etc.).
+    pub fn deserialize<'a, T>(&'a self) -> Result<T, InvalidRouteParams>
+    where
+        T: Deserialize<'a>,
+    {
+        let deserializer = MapDeserializer::new(
+            self.0
+                .iter()
+                .map(|(k, v)| (k.as_str(), ParamValueDeserializer(v.as_str()))),
+        );
+        T::deserialize(deserializer)
+    }
+}
+
+impl<'k, 'v> From<matchit::Params<'k, 'v>> for RouteParams {
+    fn from(params: matchit::Params<'k, 'v>) -> Self {
+        Self(
+            params
+                .iter()
+                .map(|(k, v)| (k.to_owned(), percent_decode(v)))
+                .collect(),
+        )
+    }
+}
+
+/// Percent-decode the value of a route parameter.
+///
+/// Byte sequences that are not valid UTF-8 once decoded are replaced with
+/// `U+FFFD REPLACEMENT CHARACTER`.
+fn percent_decode(value: &str) -> String {
+    percent_encoding::percent_decode_str(value)
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+/// The error returned by [`RouteParams::deserialize`] when the captured route parameters
+/// cannot be deserialized into the target type.
+///
+/// Unless you register an error handler for it, it is converted into a `400 Bad Request`
+/// response via its [`ResponseError`] implementation.
+#[derive(Debug)]
+pub struct InvalidRouteParams {
+    message: String,
+}
+
+impl Display for InvalidRouteParams {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid route parameters: {}", self.message)
+    }
+}
+
+impl std::error::Error for InvalidRouteParams {}
+
+impl ResponseError for InvalidRouteParams {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+}
+
+impl serde::de::Error for InvalidRouteParams {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self {
+            message: msg.to_string(),
+        }
+    }
+}
+
+/// Deserialize the value of a single route parameter, parsing it into the requested type
+/// if necessary.
+struct ParamValueDeserializer<'de>(&'de str);
+
+impl<'de> IntoDeserializer<'de, InvalidRouteParams> for ParamValueDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! parse_value {
+    ($($deserialize:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $deserialize<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let value = self.0.parse().map_err(|e| {
+                    <InvalidRouteParams as serde::de::Error>::custom(format!(
+                        "`{}` cannot be parsed: {}",
+                        self.0, e
+                    ))
+                })?;
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for ParamValueDeserializer<'de> {
+    type Error = InvalidRouteParams;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_str(self.0)
+    }
+
+    parse_value! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self)
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl<'de> EnumAccess<'de> for ParamValueDeserializer<'de> {
+    type Error = InvalidRouteParams;
+    type Variant = UnitOnlyVariant;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let deserializer: StrDeserializer<'de, InvalidRouteParams> = self.0.into_deserializer();
+        let value = seed.deserialize(deserializer)?;
+        Ok((value, UnitOnlyVariant))
+    }
+}
+
+/// Route parameters can only be deserialized into unit enum variants.
+struct UnitOnlyVariant;
+
+impl<'de> VariantAccess<'de> for UnitOnlyVariant {
+    type Error = InvalidRouteParams;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        Err(serde::de::Error::custom(
+            "route parameters can only be deserialized into unit enum variants",
+        ))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(serde::de::Error::custom(
+            "route parameters can only be deserialized into unit enum variants",
+        ))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(serde::de::Error::custom(
+            "route parameters can only be deserialized into unit enum variants",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::RouteParams;
+
+    fn params(pairs: &[(&str, &str)]) -> RouteParams {
+        RouteParams(
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        )
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct UserParams {
+        id: u64,
+        active: bool,
+        score: f32,
+    }
+
+    #[test]
+    fn numeric_params_are_parsed() {
+        let p = params(&[("id", "42"), ("active", "true"), ("score", "1.5")]);
+        let parsed: UserParams = p.deserialize().unwrap();
+        assert_eq!(
+            parsed,
+            UserParams {
+                id: 42,
+                active: true,
+                score: 1.5
+            }
+        );
+    }
+
+    #[test]
+    fn missing_params_are_rejected() {
+        let p = params(&[("id", "42"), ("active", "true")]);
+        let e = p.deserialize::<UserParams>().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Invalid route parameters: missing field `score`"
+        );
+    }
+
+    #[test]
+    fn missing_optional_params_are_deserialized_as_none() {
+        #[derive(Deserialize)]
+        struct Params {
+            id: Option<u64>,
+        }
+
+        let parsed: Params = params(&[]).deserialize().unwrap();
+        assert_eq!(parsed.id, None);
+        let parsed: Params = params(&[("id", "7")]).deserialize().unwrap();
+        assert_eq!(parsed.id, Some(7));
+    }
+
+    #[test]
+    fn invalid_params_are_rejected() {
+        let p = params(&[("id", "-1"), ("active", "true"), ("score", "1.5")]);
+        let e = p.deserialize::<UserParams>().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "Invalid route parameters: `-1` cannot be parsed: invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn unit_enum_variants_are_supported() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum Kind {
+            Admin,
+            Guest,
+        }
+        #[derive(Deserialize)]
+        struct Params {
+            kind: Kind,
+        }
+
+        let parsed: Params = params(&[("kind", "guest")]).deserialize().unwrap();
+        assert_eq!(parsed.kind, Kind::Guest);
+        assert!(params(&[("kind", "root")]).deserialize::<Params>().is_err());
+    }
+
+    #[test]
+    fn values_are_percent_decoded() {
+        let mut router = matchit::Router::new();
+        router.insert("/users/:name", ()).unwrap();
+        let matched = router.at("/users/John%20Doe%F0%9F%A6%80").unwrap();
+        let p = RouteParams::from(matched.params);
+        assert_eq!(p.get("name"), Some("John Doe🦀"));
+
+        let matched = router.at("/users/%FF").unwrap();
+        let p = RouteParams::from(matched.params);
+        assert_eq!(p.get("name"), Some("\u{FFFD}"));
+    }
+}
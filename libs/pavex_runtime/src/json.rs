@@ -0,0 +1,378 @@
+//! Read JSON payloads from incoming requests and send JSON payloads back to the caller.
+use std::fmt::{Display, Formatter};
+
+use bytes::Bytes;
+use http::{header, HeaderMap, HeaderValue, Request, StatusCode};
+use http_body::{Full, LengthLimitError, Limited};
+use hyper::Body;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::response::{IntoResponse, Response, ResponseError};
+
+/// The maximum size, in bytes, of a request body that [`Json::extract`] is willing to buffer
+/// in memory before giving up.
+///
+/// Use [`Json::extract_with_config`] if you need a different limit.
+pub const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// A JSON payload.
+///
+/// `Json` can be used:
+///
+/// - as the output type of a constructor, to deserialize the body of an incoming request
+///   via [`Json::extract`];
+/// - as the output type of a request handler (or as the `Ok` variant of a fallible one),
+///   to send a JSON payload back to the caller with `Content-Type: application/json`.
+///
+/// [`JsonError`] implements [`ResponseError`]: if you don't register an error handler for it,
+/// failures are converted into a response with the status code returned by
+/// [`JsonError::status_code`].
+///
+/// ```rust
+/// use pavex_runtime::json::{Json, JsonError};
+/// use pavex_runtime::response::{IntoResponse, Response, ResponseError};
+///
+/// #[derive(serde::Deserialize)]
+/// pub struct NewUser {
+///     pub name: String,
+/// }
+///
+/// #[derive(serde::Serialize)]
+/// pub struct User {
+///     pub id: u64,
+///     pub name: String,
+/// }
+///
+/// pub fn create_user(body: Json<NewUser>) -> Json<User> {
+///     Json(User { id: 1, name: body.0.name })
+/// }
+///
+/// pub fn invalid_json(e: &JsonError) -> Response {
+///     e.status_code().into_response()
+/// }
+/// ```
+///
+/// and then, in your blueprint:
+///
+/// ```rust,ignore
+/// bp.constructor(
+///     f!(pavex_runtime::json::Json::<crate::NewUser>::extract),
+///     Lifecycle::RequestScoped,
+/// )
+/// .error_handler(f!(crate::invalid_json));
+/// bp.route(POST, "/users", f!(crate::create_user));
+/// ```
+///
+/// `Json::extract` takes the incoming request by value, but it can be combined with
+/// constructors that borrow it (e.g. [`QueryParams::extract`]): `pavex` makes sure that the
+/// borrows happen first.
+///
+/// [`QueryParams::extract`]: crate::extract::query::QueryParams::extract
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Json<T>(pub T);
+
+impl<T> Json<T>
+where
+    T: DeserializeOwned,
+{
+    /// Buffer the body of the incoming request and deserialize it as JSON into `T`.
+    ///
+    /// The request must carry a `Content-Type` header set to `application/json`
+    /// (or to another JSON-based media type, e.g. `application/problem+json`) and its
+    /// body must not be larger than [`DEFAULT_BODY_LIMIT`] bytes.
+    pub async fn extract(request: Request<Body>) -> Result<Json<T>, JsonError> {
+        Self::extract_with_config(request, &JsonConfig::default()).await
+    }
+
+    /// Same as [`Json::extract`], but the body of the incoming request must not be larger than
+    /// [`JsonConfig::body_limit`] bytes.
+    ///
+    /// Register a constructor for [`JsonConfig`] to use it:
+    ///
+    /// ```rust
+    /// use pavex_runtime::json::JsonConfig;
+    ///
+    /// pub fn json_config() -> JsonConfig {
+    ///     JsonConfig { body_limit: 64 * 1024 }
+    /// }
+    /// ```
+    ///
+    /// and then, in your blueprint:
+    ///
+    /// ```rust,ignore
+    /// bp.constructor(f!(crate::json_config), Lifecycle::Singleton);
+    /// bp.constructor(
+    ///     f!(pavex_runtime::json::Json::<crate::NewUser>::extract_with_config),
+    ///     Lifecycle::RequestScoped,
+    /// )
+    /// .error_handler(f!(crate::invalid_json));
+    /// ```
+    pub async fn extract_with_config(
+        request: Request<Body>,
+        config: &JsonConfig,
+    ) -> Result<Json<T>, JsonError> {
+        check_json_content_type(request.headers())?;
+        let limit = config.body_limit;
+        let body = Limited::new(request.into_body(), limit);
+        let bytes = hyper::body::to_bytes(body).await.map_err(|e| {
+            if e.downcast_ref::<LengthLimitError>().is_some() {
+                JsonError::PayloadTooLarge { limit }
+            } else {
+                JsonError::CannotReadBody(e.to_string())
+            }
+        })?;
+        serde_json::from_slice(&bytes)
+            .map(Json)
+            .map_err(JsonError::InvalidPayload)
+    }
+
+    /// Consume the extractor and return the deserialized payload.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+/// The configuration used by [`Json::extract_with_config`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonConfig {
+    /// The maximum size, in bytes, of a request body that [`Json::extract_with_config`] is
+    /// willing to buffer in memory before giving up.
+    pub body_limit: usize,
+}
+
+impl Default for JsonConfig {
+    fn default() -> Self {
+        Self {
+            body_limit: DEFAULT_BODY_LIMIT,
+        }
+    }
+}
+
+fn check_json_content_type(headers: &HeaderMap) -> Result<(), JsonError> {
+    let Some(content_type) = headers.get(header::CONTENT_TYPE) else {
+        return Err(JsonError::MissingContentType);
+    };
+    let unsupported = || {
+        JsonError::UnsupportedContentType(String::from_utf8_lossy(content_type.as_bytes()).into())
+    };
+    let mime = content_type
+        .to_str()
+        .ok()
+        .and_then(|s| s.parse::<mime::Mime>().ok())
+        .ok_or_else(unsupported)?;
+    let is_json = mime.type_() == mime::APPLICATION
+        && (mime.subtype() == mime::JSON || mime.suffix() == Some(mime::JSON));
+    if is_json {
+        Ok(())
+    } else {
+        Err(unsupported())
+    }
+}
+
+impl<T> IntoResponse for Json<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        match serde_json::to_vec(&self.0) {
+            Ok(body) => {
+                let mut res = Full::from(Bytes::from(body)).into_response();
+                res.headers_mut().insert(
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static(mime::APPLICATION_JSON.as_ref()),
+                );
+                res
+            }
+            Err(e) => {
+                let mut res = e.to_string().into_response();
+                *res.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                res
+            }
+        }
+    }
+}
+
+/// The error returned by [`Json::extract`] and [`Json::extract_with_config`] when the body
+/// of the incoming request cannot be deserialized as a JSON payload of the expected type.
+#[derive(Debug)]
+pub enum JsonError {
+    /// The request does not have a `Content-Type` header.
+    MissingContentType,
+    /// The `Content-Type` header of the request is not a JSON media type.
+    UnsupportedContentType(String),
+    /// The request body is larger than the configured limit.
+    PayloadTooLarge {
+        /// The maximum size of the request body, in bytes.
+        limit: usize,
+    },
+    /// Something went wrong while reading the request body.
+    CannotReadBody(String),
+    /// The request body is not valid JSON or does not match the shape of the target type.
+    InvalidPayload(serde_json::Error),
+}
+
+impl ResponseError for JsonError {
+    /// The status code that best describes this error to the caller:
+    ///
+    /// - `415 Unsupported Media Type` if the `Content-Type` header is missing or wrong;
+    /// - `413 Payload Too Large` if the body exceeds the size limit;
+    /// - `400 Bad Request` otherwise.
+    fn status_code(&self) -> StatusCode {
+        match self {
+            JsonError::MissingContentType | JsonError::UnsupportedContentType(_) => {
+                StatusCode::UNSUPPORTED_MEDIA_TYPE
+            }
+            JsonError::PayloadTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            JsonError::CannotReadBody(_) | JsonError::InvalidPayload(_) => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl Display for JsonError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonError::MissingContentType => {
+                write!(f, "Expected a `Content-Type: application/json` header")
+            }
+            JsonError::UnsupportedContentType(content_type) => write!(
+                f,
+                "Expected a JSON content type, but the request has `Content-Type: {content_type}`"
+            ),
+            JsonError::PayloadTooLarge { limit } => {
+                write!(f, "The request body is larger than {limit} bytes")
+            }
+            JsonError::CannotReadBody(e) => write!(f, "Failed to read the request body: {e}"),
+            JsonError::InvalidPayload(e) => write!(f, "Invalid JSON payload: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for JsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JsonError::InvalidPayload(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use http::{header, Request, StatusCode};
+    use hyper::Body;
+
+    use crate::response::ResponseError;
+
+    use super::{Json, JsonConfig, JsonError, DEFAULT_BODY_LIMIT};
+
+    fn json_request(content_type: Option<&str>, body: impl Into<Body>) -> Request<Body> {
+        let mut builder = Request::builder();
+        if let Some(content_type) = content_type {
+            builder = builder.header(header::CONTENT_TYPE, content_type);
+        }
+        builder.body(body.into()).unwrap()
+    }
+
+    /// A JSON string literal that takes exactly `size` bytes once serialized.
+    fn json_string_of_size(size: usize) -> String {
+        format!("\"{}\"", "a".repeat(size - 2))
+    }
+
+    #[tokio::test]
+    async fn json_content_types_are_accepted() {
+        for content_type in [
+            "application/json",
+            "application/json; charset=utf-8",
+            "application/problem+json",
+            "application/vnd.api+json",
+        ] {
+            let request = json_request(Some(content_type), r#"{"name":"Ursula"}"#);
+            let payload: Json<serde_json::Value> = Json::extract(request).await.unwrap();
+            assert_eq!(payload.0["name"], "Ursula", "{content_type}");
+        }
+    }
+
+    #[tokio::test]
+    async fn non_json_content_types_are_rejected() {
+        for content_type in [
+            "text/plain",
+            "text/json",
+            "application/json-seq",
+            "application/problem+xml",
+            "not a mime type",
+        ] {
+            let request = json_request(Some(content_type), r#"{"name":"Ursula"}"#);
+            let e = Json::<serde_json::Value>::extract(request)
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(&e, JsonError::UnsupportedContentType(c) if c == content_type),
+                "{content_type}: {e:?}"
+            );
+            assert_eq!(e.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        }
+    }
+
+    #[tokio::test]
+    async fn a_missing_content_type_is_rejected() {
+        let request = json_request(None, r#"{"name":"Ursula"}"#);
+        let e = Json::<serde_json::Value>::extract(request)
+            .await
+            .unwrap_err();
+        assert!(matches!(e, JsonError::MissingContentType));
+        assert_eq!(e.status_code(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[tokio::test]
+    async fn invalid_payloads_are_rejected() {
+        let request = json_request(Some("application/json"), r#"{"name":"#);
+        let e = Json::<serde_json::Value>::extract(request)
+            .await
+            .unwrap_err();
+        assert!(matches!(e, JsonError::InvalidPayload(_)));
+        assert_eq!(e.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn bodies_up_to_the_default_limit_are_accepted() {
+        let body = json_string_of_size(DEFAULT_BODY_LIMIT);
+        let request = json_request(Some("application/json"), body);
+        let payload: Json<String> = Json::extract(request).await.unwrap();
+        assert_eq!(payload.0.len(), DEFAULT_BODY_LIMIT - 2);
+    }
+
+    #[tokio::test]
+    async fn bodies_larger_than_the_default_limit_are_rejected() {
+        let body = json_string_of_size(DEFAULT_BODY_LIMIT + 1);
+        let request = json_request(Some("application/json"), body);
+        let e = Json::<String>::extract(request).await.unwrap_err();
+        assert!(matches!(
+            e,
+            JsonError::PayloadTooLarge {
+                limit: DEFAULT_BODY_LIMIT
+            }
+        ));
+        assert_eq!(e.status_code(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+
+    #[tokio::test]
+    async fn the_body_limit_can_be_configured() {
+        let config = JsonConfig { body_limit: 16 };
+
+        let request = json_request(Some("application/json"), json_string_of_size(16));
+        let payload: Json<String> = Json::extract_with_config(request, &config).await.unwrap();
+        assert_eq!(payload.0.len(), 14);
+
+        let request = json_request(Some("application/json"), json_string_of_size(17));
+        let e = Json::<String>::extract_with_config(request, &config)
+            .await
+            .unwrap_err();
+        assert!(matches!(e, JsonError::PayloadTooLarge { limit: 16 }));
+    }
+
+    #[test]
+    fn the_default_config_uses_the_default_body_limit() {
+        assert_eq!(JsonConfig::default().body_limit, 2 * 1024 * 1024);
+    }
+}
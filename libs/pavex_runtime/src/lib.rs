@@ -7,4 +7,9 @@ pub use error::Error;
 
 pub mod body;
 pub mod error;
+pub mod extract;
+pub mod json;
+pub mod middleware;
 pub mod response;
+pub mod startup;
+pub mod testing;
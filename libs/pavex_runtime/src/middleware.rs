@@ -0,0 +1,76 @@
+//! Run logic before and after the request handler (e.g. timing, authentication, request ids).
+use std::future::Future;
+
+use futures_util::future::BoxFuture;
+use futures_util::FutureExt;
+use http::Request;
+use hyper::Body;
+
+use crate::response::Response;
+
+/// The remaining part of the request processing pipeline: the middlewares registered after
+/// the current one and, at the very end, the request handler.
+///
+/// A middleware is a callable that takes `Next` as input, alongside any other dependency it
+/// might need, and returns a type that implements
+/// [`IntoResponse`](crate::response::IntoResponse).
+/// It can inspect or modify the incoming request via [`Next::request`] and
+/// [`Next::request_mut`] and it can then decide whether to invoke the rest of the pipeline,
+/// via [`Next::run`], or to return a response right away.
+///
+/// ```rust
+/// use std::time::Instant;
+/// use pavex_runtime::middleware::Next;
+/// use pavex_runtime::response::Response;
+///
+/// pub async fn timer(next: Next) -> Response {
+///     let start = Instant::now();
+///     let response = next.run().await;
+///     println!("The request was processed in {:?}", start.elapsed());
+///     response
+/// }
+/// ```
+///
+/// `Next` is provided by the framework: you don't need to register a constructor for it.
+/// Middlewares cannot take the incoming request as an input parameter, since it is owned by
+/// `Next`: use [`Next::request`] instead.
+pub struct Next {
+    request: Request<Body>,
+    handler: Box<dyn FnOnce(Request<Body>) -> BoxFuture<'static, Response> + Send>,
+}
+
+impl Next {
+    /// Create a new [`Next`] instance.
+    ///
+    /// `handler` is invoked, with `request` as input, when [`Next::run`] is called.
+    /// You don't need to call this constructor yourself: `pavex` uses it in the generated code
+    /// to chain your middlewares together.
+    pub fn new<F, Fut>(request: Request<Body>, handler: F) -> Self
+    where
+        F: FnOnce(Request<Body>) -> Fut + Send + 'static,
+        Fut: Future<Output = Response> + Send + 'static,
+    {
+        Self {
+            request,
+            handler: Box::new(move |request| handler(request).boxed()),
+        }
+    }
+
+    /// A reference to the incoming request.
+    pub fn request(&self) -> &Request<Body> {
+        &self.request
+    }
+
+    /// A mutable reference to the incoming request.
+    ///
+    /// Changes will be visible to the middlewares and the request handler that come after
+    /// the current one.
+    pub fn request_mut(&mut self) -> &mut Request<Body> {
+        &mut self.request
+    }
+
+    /// Invoke the rest of the request processing pipeline and return its response.
+    pub async fn run(self) -> Response {
+        (self.handler)(self.request).await
+    }
+}
@@ -1,4 +1,5 @@
 pub use into_response::{IntoResponse, Response};
+pub use response_error::ResponseError;
 
 // Most of this module is an adaptation of the corresponding
 // module in `axum-core`
@@ -29,3 +30,4 @@ pub use into_response::{IntoResponse, Response};
 // IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER
 // DEALINGS IN THE SOFTWARE.
 mod into_response;
+mod response_error;
@@ -0,0 +1,46 @@
+use http::StatusCode;
+
+use super::{IntoResponse, Response};
+
+/// Convert an error into an HTTP response.
+///
+/// If the error type returned by a fallible constructor or request handler implements
+/// `ResponseError`, you don't need to register an error handler for it: `pavex` will call
+/// [`ResponseError::error_response`] to build the response when the component fails.
+/// An error handler registered via `pavex_builder`, either for the component or for its error
+/// type, still takes precedence.
+///
+/// The default implementation returns an empty response with a `500 Internal Server Error`
+/// status code.
+/// Override [`ResponseError::status_code`] to pick a different status code, or
+/// [`ResponseError::error_response`] to customise the response further.
+///
+/// ```rust
+/// use pavex_runtime::http::StatusCode;
+/// use pavex_runtime::response::ResponseError;
+///
+/// #[derive(Debug)]
+/// pub struct UserNotFound;
+///
+/// impl ResponseError for UserNotFound {
+///     fn status_code(&self) -> StatusCode {
+///         StatusCode::NOT_FOUND
+///     }
+/// }
+/// ```
+pub trait ResponseError {
+    /// The status code of the response built by [`ResponseError::error_response`].
+    fn status_code(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+
+    /// Build an HTTP response out of the error.
+    ///
+    /// It takes `&self` because error handlers only get a reference to the error.
+    fn error_response(&self) -> Response {
+        self.status_code().into_response()
+    }
+}
+
+/// Errors wrapped in `pavex_runtime::Error` are turned into a `500 Internal Server Error`.
+impl ResponseError for crate::Error {}
@@ -0,0 +1,226 @@
+//! Enforce the startup policies of singleton constructors (timeouts and retries).
+//!
+//! The functions in this module are invoked by the code generated by `pavex` to build the
+//! application state: you don't need to call them directly.
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use std::time::Duration;
+
+/// The error returned when a singleton constructor does not complete within the timeout
+/// configured via `Constructor::startup_timeout`.
+#[derive(Debug, Clone)]
+pub struct TimeoutError {
+    timeout: Duration,
+}
+
+impl TimeoutError {
+    /// The timeout that was exceeded.
+    pub fn timeout(&self) -> Duration {
+        self.timeout
+    }
+}
+
+impl Display for TimeoutError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Timed out after {:?}", self.timeout)
+    }
+}
+
+impl std::error::Error for TimeoutError {}
+
+/// The error returned by a fallible singleton constructor with a startup policy.
+///
+/// If retries were configured, it is the error returned by the last attempt.
+#[derive(Debug)]
+pub enum StartupError<E> {
+    /// The constructor did not complete in time.
+    TimedOut(TimeoutError),
+    /// The constructor returned an error.
+    Failed(E),
+}
+
+impl<E: Display> Display for StartupError<E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StartupError::TimedOut(e) => Display::fmt(e, f),
+            StartupError::Failed(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for StartupError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StartupError::TimedOut(_) => None,
+            // The error is transparent: we display it directly, therefore we skip it here.
+            StartupError::Failed(e) => e.source(),
+        }
+    }
+}
+
+/// Wait for an infallible constructor to complete, failing if it takes longer than `timeout`.
+pub async fn with_timeout<Fut, T>(timeout: Duration, future: Fut) -> Result<T, TimeoutError>
+where
+    Fut: Future<Output = T>,
+{
+    tokio::time::timeout(timeout, future)
+        .await
+        .map_err(|_| TimeoutError { timeout })
+}
+
+/// Wait for a fallible constructor to complete, failing if it takes longer than `timeout`.
+pub async fn try_with_timeout<Fut, T, E>(
+    timeout: Duration,
+    future: Fut,
+) -> Result<T, StartupError<E>>
+where
+    Fut: Future<Output = Result<T, E>>,
+{
+    match tokio::time::timeout(timeout, future).await {
+        Ok(outcome) => outcome.map_err(StartupError::Failed),
+        Err(_) => Err(StartupError::TimedOut(TimeoutError { timeout })),
+    }
+}
+
+/// Invoke a fallible constructor, via `attempt`, until it succeeds or `max_retries` retries
+/// have been performed, waiting for `backoff` between consecutive attempts.
+///
+/// If `timeout` is set, each attempt is allowed to take at most `timeout`.
+pub async fn retry<F, Fut, T, E>(
+    max_retries: u32,
+    backoff: Duration,
+    timeout: Option<Duration>,
+    mut attempt: F,
+) -> Result<T, StartupError<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut n_retries = 0;
+    loop {
+        let outcome = match timeout {
+            Some(timeout) => try_with_timeout(timeout, attempt()).await,
+            None => attempt().await.map_err(StartupError::Failed),
+        };
+        match outcome {
+            Ok(value) => return Ok(value),
+            Err(e) if n_retries >= max_retries => return Err(e),
+            Err(_) => {
+                n_retries += 1;
+                tokio::time::sleep(backoff).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::time::Duration;
+
+    use tokio::time::{sleep, Instant};
+
+    use super::{retry, try_with_timeout, with_timeout, StartupError};
+
+    #[tokio::test(start_paused = true)]
+    async fn timeouts_are_enforced() {
+        let start = Instant::now();
+        let outcome = with_timeout(Duration::from_secs(1), sleep(Duration::from_secs(10))).await;
+
+        let e = outcome.unwrap_err();
+        assert_eq!(e.timeout(), Duration::from_secs(1));
+        assert_eq!(start.elapsed(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn constructors_that_complete_in_time_are_not_interrupted() {
+        let outcome = try_with_timeout(Duration::from_secs(1), async {
+            sleep(Duration::from_millis(500)).await;
+            Ok::<_, ()>(42)
+        })
+        .await;
+        assert!(matches!(outcome, Ok(42)));
+
+        let outcome =
+            try_with_timeout(Duration::from_secs(1), async { Err::<(), _>("boom") }).await;
+        assert!(matches!(outcome, Err(StartupError::Failed("boom"))));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn fallible_constructors_can_time_out() {
+        let outcome = try_with_timeout(Duration::from_secs(1), async {
+            sleep(Duration::from_secs(10)).await;
+            Ok::<_, ()>(42)
+        })
+        .await;
+        let Err(StartupError::TimedOut(e)) = outcome else {
+            panic!("Expected a timeout")
+        };
+        assert_eq!(e.timeout(), Duration::from_secs(1));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_stop_at_the_first_success() {
+        let n_attempts = Cell::new(0);
+        let start = Instant::now();
+        let outcome = retry(5, Duration::from_secs(2), None, || {
+            n_attempts.set(n_attempts.get() + 1);
+            let attempt = n_attempts.get();
+            async move {
+                if attempt < 3 {
+                    Err(attempt)
+                } else {
+                    Ok(attempt)
+                }
+            }
+        })
+        .await;
+
+        assert!(matches!(outcome, Ok(3)));
+        assert_eq!(n_attempts.get(), 3);
+        // We waited for the backoff after each of the two failed attempts.
+        assert_eq!(start.elapsed(), Duration::from_secs(4));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn the_error_of_the_last_attempt_is_returned_when_retries_run_out() {
+        let n_attempts = Cell::new(0);
+        let start = Instant::now();
+        let outcome = retry(3, Duration::from_secs(2), None, || {
+            n_attempts.set(n_attempts.get() + 1);
+            let attempt = n_attempts.get();
+            async move { Err::<(), _>(attempt) }
+        })
+        .await;
+
+        assert!(matches!(outcome, Err(StartupError::Failed(4))));
+        // The first attempt, followed by 3 retries.
+        assert_eq!(n_attempts.get(), 4);
+        // There is no backoff after the last attempt.
+        assert_eq!(start.elapsed(), Duration::from_secs(6));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn each_attempt_is_subject_to_the_timeout() {
+        let n_attempts = Cell::new(0);
+        let start = Instant::now();
+        let outcome = retry(
+            2,
+            Duration::from_secs(2),
+            Some(Duration::from_secs(1)),
+            || {
+                n_attempts.set(n_attempts.get() + 1);
+                async {
+                    sleep(Duration::from_secs(10)).await;
+                    Ok::<_, ()>(())
+                }
+            },
+        )
+        .await;
+
+        assert!(matches!(outcome, Err(StartupError::TimedOut(_))));
+        assert_eq!(n_attempts.get(), 3);
+        // Three attempts that time out, with a backoff after the first two.
+        assert_eq!(start.elapsed(), Duration::from_secs(3 + 2 * 2));
+    }
+}
@@ -0,0 +1,130 @@
+//! Send requests to your application in memory, without binding a TCP port.
+//!
+//! The code generated by `pavex` exposes an `into_service` function, which wraps the request
+//! processing pipeline of your application into a [`Service`].
+//! [`TestClient`] drives that service directly:
+//!
+//! ```rust,ignore
+//! use pavex_runtime::http::StatusCode;
+//! use pavex_runtime::testing::{read_body, TestClient};
+//!
+//! #[tokio::test]
+//! async fn home_says_hello() {
+//!     // `application` is the crate generated by `pavex`.
+//!     let application_state = application::build_application_state().await;
+//!     let client = TestClient::new(application::into_service(application_state).unwrap());
+//!
+//!     let response = client.get("/home").await;
+//!
+//!     assert_eq!(response.status(), StatusCode::OK);
+//!     assert_eq!(read_body(response).await.unwrap(), "Hello!");
+//! }
+//! ```
+use std::convert::Infallible;
+
+use bytes::Bytes;
+use futures_util::future::poll_fn;
+use http::Request;
+use hyper::service::Service;
+use hyper::Body;
+
+use crate::response::Response;
+use crate::Error;
+
+/// A client that sends requests to an application in memory, via the [`Service`] returned
+/// by its generated `into_service` function.
+///
+/// Requests go through the same routing and request processing logic that is used by
+/// `run`, but there is no network (or HTTP parsing) involved.
+#[derive(Clone)]
+pub struct TestClient<S> {
+    service: S,
+}
+
+impl<S> TestClient<S>
+where
+    S: Service<Request<Body>, Response = Response, Error = Infallible> + Clone,
+{
+    /// Create a new client for the given service.
+    pub fn new(service: S) -> Self {
+        Self { service }
+    }
+
+    /// Process `request` and return the response produced by the application.
+    pub async fn send(&self, request: Request<Body>) -> Response {
+        let mut service = self.service.clone();
+        let outcome = match poll_fn(|cx| service.poll_ready(cx)).await {
+            Ok(()) => service.call(request).await,
+            Err(e) => Err(e),
+        };
+        match outcome {
+            Ok(response) => response,
+            Err(e) => match e {},
+        }
+    }
+
+    /// Send a `GET` request, with an empty body, to the given path (e.g. `/home?name=Ursula`).
+    pub async fn get(&self, path: &str) -> Response {
+        let request = Request::get(path)
+            .body(Body::empty())
+            .expect("Failed to build a GET request: is the path valid?");
+        self.send(request).await
+    }
+}
+
+/// Collect the body of `response` in memory.
+pub async fn read_body(response: Response) -> Result<Bytes, Error> {
+    hyper::body::to_bytes(response.into_body()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use http::{Method, Request, StatusCode};
+    use hyper::service::service_fn;
+    use hyper::Body;
+
+    use crate::response::{IntoResponse, Response};
+
+    use super::{read_body, TestClient};
+
+    /// Reply with the method and the path of the request, followed by its body.
+    async fn echo(request: Request<Body>) -> Result<Response, Infallible> {
+        let prefix = format!("{} {}\n", request.method(), request.uri());
+        let body = hyper::body::to_bytes(request.into_body()).await.unwrap();
+        let mut payload = prefix.into_bytes();
+        payload.extend_from_slice(&body);
+        Ok(payload.into_response())
+    }
+
+    #[tokio::test]
+    async fn get_sends_a_bodyless_request_to_the_given_path() {
+        let client = TestClient::new(service_fn(echo));
+
+        let response = client.get("/home?name=Ursula").await;
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            read_body(response).await.unwrap(),
+            "GET /home?name=Ursula\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn send_forwards_the_request_as_is() {
+        let client = TestClient::new(service_fn(echo));
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri("/users")
+            .body(Body::from("Ursula"))
+            .unwrap();
+
+        let response = client.send(request).await;
+
+        assert_eq!(read_body(response).await.unwrap(), "POST /users\nUrsula");
+        // The client can be used again: every request is processed by a clone of the service.
+        let response = client.get("/users").await;
+        assert_eq!(read_body(response).await.unwrap(), "GET /users\n");
+    }
+}
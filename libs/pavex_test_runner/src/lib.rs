@@ -1,3 +1,4 @@
+use std::collections::BTreeMap;
 use std::fmt::Write;
 use std::path::PathBuf;
 use std::process::Output;
@@ -83,6 +84,18 @@ struct TestConfig {
     /// Ignore the test if set to `true`.
     #[serde(default)]
     ignore: bool,
+    /// HTTP requests that should be sent to the generated application, once it's up and running,
+    /// alongside the responses we expect to get back.
+    ///
+    /// If empty, the generated application is only compiled, never executed.
+    #[serde(default)]
+    requests: Vec<RequestExpectation>,
+    /// Rust expressions, evaluated in order, that should be passed as inputs to
+    /// `build_application_state` before sending the requests in [`TestConfig::requests`].
+    ///
+    /// The crate under test can be referred to as `app` (e.g. `app::Config::default()`).
+    #[serde(default)]
+    application_state_inputs: Vec<String>,
 }
 
 #[derive(serde::Deserialize)]
@@ -123,6 +136,51 @@ impl ExpectedOutcome {
     }
 }
 
+#[derive(serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+struct RequestExpectation {
+    /// The HTTP method of the request. Defaults to `GET`.
+    #[serde(default = "RequestExpectation::get")]
+    method: String,
+    /// The path (and query) of the request, e.g. `/home?name=Ursula`.
+    path: String,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    #[serde(default)]
+    body: String,
+    /// What the generated application should send back.
+    #[serde(skip_serializing)]
+    expected: ExpectedResponse,
+}
+
+impl RequestExpectation {
+    fn get() -> String {
+        "GET".into()
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+struct ExpectedResponse {
+    status: u16,
+    /// Headers that must be present in the response, with the given values.
+    /// Headers that are not listed here are not checked.
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    /// If not set, the body of the response is not checked.
+    #[serde(default)]
+    body: Option<String>,
+}
+
+/// The response returned by the generated application, as reported by the harness binary
+/// generated in [`TestData::seed_runtime_harness`].
+#[derive(serde::Deserialize)]
+struct ActualResponse {
+    status: u16,
+    headers: BTreeMap<String, String>,
+    body: String,
+}
+
 /// Auxiliary data attached to each test definition for convenient retrieval.
 /// It's used in [`run_test`].
 struct TestData {
@@ -171,11 +229,17 @@ impl TestData {
                 self.definition_directory.join(&filepath.path),
                 dep_source_directory.join("lib.rs"),
             )?;
+            // Ephemeral dependencies can define their own blueprints (e.g. to be nested into
+            // the one of the crate under test).
             let mut cargo_toml = toml! {
                 [package]
                 name = "dummy"
                 version = "0.1.0"
                 edition = "2021"
+
+                [dependencies]
+                pavex_builder = { path = "../../../../../libs/pavex_builder" }
+                pavex_runtime = { path = "../../../../../libs/pavex_runtime" }
             };
             cargo_toml["package"]["name"] = dependency_name.to_owned().into();
             fs_err::write(
@@ -234,15 +298,32 @@ impl TestData {
             toml::to_string(&cargo_config)?,
         )?;
 
+        // `pavex_cli check` is invoked before `pavex_cli generate`: it must reach the same
+        // verdict, with the same diagnostics, without writing anything to disk.
         let main_rs = format!(
             r#"use app::blueprint;
+use std::io::Write;
+use std::path::Path;
 use std::str::FromStr;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {{
     let path = std::path::PathBuf::from_str("blueprint.json")?;
     blueprint().persist(&path)?;
+    let cli = "../../../target/{cli_profile}/pavex_cli";
 
-    let status = std::process::Command::new("../../../target/{cli_profile}/pavex_cli")
+    let _ = std::fs::remove_dir_all("generated_app");
+    let _ = std::fs::remove_file("diagnostics.dot");
+    let check = std::process::Command::new(cli)
+        .arg("check")
+        .arg("-b")
+        .arg(&path)
+        .output()?;
+    if Path::new("generated_app").exists() || Path::new("diagnostics.dot").exists() {{
+        eprintln!("`pavex_cli check` wrote to disk, but it should only report diagnostics");
+        std::process::exit(2);
+    }}
+
+    let generate = std::process::Command::new(cli)
         .arg("generate")
         .arg("-b")
         .arg(&path)
@@ -250,12 +331,143 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {{
         .arg("diagnostics.dot")
         .arg("-o")
         .arg("generated_app")
-        .status()?;
-       
-    if !status.success() {{
-        std::process::exit(1); 
+        .output()?;
+    std::io::stdout().write_all(&generate.stdout)?;
+    std::io::stderr().write_all(&generate.stderr)?;
+
+    if check.status.success() != generate.status.success() || check.stderr != generate.stderr {{
+        eprintln!(
+            "`pavex_cli check` and `pavex_cli generate` disagree.\n\t--- CHECK STDERR:\n{{}}",
+            String::from_utf8_lossy(&check.stderr)
+        );
+        std::process::exit(2);
+    }}
+    if !generate.status.success() {{
+        std::process::exit(1);
+    }}
+
+    Ok(())
+}}"#
+        );
+        fs_err::write(source_directory.join("main.rs"), main_rs)?;
+
+        if !test_config.requests.is_empty() {
+            self.seed_runtime_harness(test_config)?;
+        }
+        Ok(())
+    }
+
+    /// Set up a binary crate that launches the generated application on an ephemeral localhost
+    /// port and sends it the requests listed in the test configuration.
+    ///
+    /// The harness prints the responses it gets back to stdout, as JSON, in the same order
+    /// as the requests: it's up to the test runner to check them against our expectations.
+    fn seed_runtime_harness(&self, test_config: &TestConfig) -> Result<(), anyhow::Error> {
+        let harness_directory = self.runtime_directory.join("runtime_harness");
+        let source_directory = harness_directory.join("src");
+        fs_err::create_dir_all(&source_directory).context(
+            "Failed to create the source directory for the runtime harness when setting up the test runtime environment",
+        )?;
+        // The harness is a workspace on its own: the generated application doesn't exist yet
+        // when the crate under test is built and executed, so it can't be a workspace member.
+        let cargo_toml = toml! {
+            [workspace]
+
+            [package]
+            name = "runtime_harness"
+            version = "0.1.0"
+            edition = "2021"
+
+            [dependencies]
+            app = { path = ".." }
+            application = { path = "../generated_app" }
+            pavex_runtime = { path = "../../../../libs/pavex_runtime" }
+            hyper = { version = "0.14", features = ["client", "http1", "tcp"] }
+            serde_json = "1"
+            tokio = { version = "1", features = ["rt-multi-thread", "macros"] }
+        };
+        fs_err::write(
+            harness_directory.join("Cargo.toml"),
+            toml::to_string(&cargo_toml)?,
+        )?;
+        fs_err::write(
+            harness_directory.join("requests.json"),
+            serde_json::to_string(&test_config.requests)?,
+        )?;
+
+        let application_state_inputs = test_config.application_state_inputs.join(", ");
+        let main_rs = format!(
+            r#"use pavex_runtime::hyper;
+
+trait IntoApplicationState {{
+    fn into_application_state(self) -> application::ApplicationState;
+}}
+
+impl IntoApplicationState for application::ApplicationState {{
+    fn into_application_state(self) -> application::ApplicationState {{
+        self
+    }}
+}}
+
+impl<E: std::fmt::Debug> IntoApplicationState for Result<application::ApplicationState, E> {{
+    fn into_application_state(self) -> application::ApplicationState {{
+        self.expect("Failed to build the application state")
+    }}
+}}
+
+async fn send_requests(
+    address: std::net::SocketAddr,
+    requests: serde_json::Value,
+) -> Result<serde_json::Value, Box<dyn std::error::Error>> {{
+    let client = hyper::Client::new();
+    let mut responses = Vec::new();
+    for request in requests.as_array().unwrap() {{
+        let mut builder = hyper::Request::builder()
+            .method(request["method"].as_str().unwrap())
+            .uri(format!("http://{{address}}{{}}", request["path"].as_str().unwrap()));
+        for (name, value) in request["headers"].as_object().unwrap() {{
+            builder = builder.header(name.as_str(), value.as_str().unwrap());
+        }}
+        let body = hyper::Body::from(request["body"].as_str().unwrap().to_owned());
+        let response = client.request(builder.body(body)?).await?;
+        let status = response.status().as_u16();
+        let headers: serde_json::Map<String, serde_json::Value> = response
+            .headers()
+            .iter()
+            .map(|(name, value)| {{
+                (
+                    name.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into(),
+                )
+            }})
+            .collect();
+        let body = hyper::body::to_bytes(response.into_body()).await?;
+        responses.push(serde_json::json!({{
+            "status": status,
+            "headers": headers,
+            "body": String::from_utf8_lossy(&body),
+        }}));
+    }}
+    Ok(responses.into())
+}}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {{
+    let requests = serde_json::from_str(&std::fs::read_to_string("requests.json")?)?;
+    let application_state = application::build_application_state({application_state_inputs})
+        .await
+        .into_application_state();
+    let incoming = hyper::server::conn::AddrIncoming::bind(&([127, 0, 0, 1], 0).into())?;
+    let address = incoming.local_addr();
+    let server = application::run(hyper::Server::builder(incoming), application_state);
+    tokio::select! {{
+        outcome = server => {{
+            return Err(format!("The server stopped before all requests were sent: {{outcome:?}}").into());
+        }}
+        responses = send_requests(address, requests) => {{
+            println!("{{}}", responses?);
+        }}
     }}
-     
     Ok(())
 }}"#
         );
@@ -367,6 +579,14 @@ fn _run_test(
     .unwrap();
     let codegen_outcome = app_code_snapshot.verify(&actual_app_code);
 
+    if let Err(msg) = verify_route_request_cannot_panic(&actual_app_code) {
+        return Ok(TestOutcome {
+            outcome: Err(msg),
+            codegen_output,
+            compilation_output: None,
+        });
+    }
+
     if diagnostics_outcome.is_err() {
         return Ok(TestOutcome {
             outcome: Err(
@@ -403,6 +623,16 @@ fn _run_test(
         });
     }
 
+    if !test_config.requests.is_empty() {
+        if let Err(msg) = verify_runtime_behaviour(test_config, test)? {
+            return Ok(TestOutcome {
+                outcome: Err(msg),
+                codegen_output,
+                compilation_output: Some(compilation_output),
+            });
+        }
+    }
+
     Ok(TestOutcome {
         outcome: Ok(()),
         codegen_output,
@@ -410,6 +640,104 @@ fn _run_test(
     })
 }
 
+/// Launch the generated application, via the runtime harness, and check that it returns the
+/// expected responses to the requests listed in the test configuration.
+fn verify_runtime_behaviour(
+    test_config: &TestConfig,
+    test: &TestData,
+) -> Result<Result<(), String>, anyhow::Error> {
+    let output = std::process::Command::new("cargo")
+        .env("RUSTFLAGS", "-Awarnings")
+        .arg("run")
+        .arg("--quiet")
+        // Share the target directory with the crate under test to avoid building the same
+        // dependencies twice.
+        .arg("--target-dir")
+        .arg("../target")
+        .current_dir(test.runtime_directory.join("runtime_harness"))
+        .output()
+        .unwrap();
+    let harness_output: CommandOutput = (&output).try_into()?;
+    if !output.status.success() {
+        return Ok(Err(format!(
+            "We failed to run the generated application and send it the requests listed in \
+            `test_config.toml`.\n\n\t--- STDOUT:\n{}\n\t--- STDERR:\n{}",
+            harness_output.stdout, harness_output.stderr
+        )));
+    }
+    let responses: Vec<ActualResponse> = serde_json::from_str(&harness_output.stdout)
+        .context("Failed to deserialize the responses reported by the runtime harness")?;
+
+    let mut msg = String::new();
+    for (request, actual) in test_config.requests.iter().zip(responses) {
+        let expected = &request.expected;
+        let mut mismatches = Vec::new();
+        if expected.status != actual.status {
+            mismatches.push(format!(
+                "Expected status code `{}`, got `{}`",
+                expected.status, actual.status
+            ));
+        }
+        for (name, expected_value) in &expected.headers {
+            match actual.headers.get(&name.to_lowercase()) {
+                Some(value) if value == expected_value => {}
+                Some(value) => mismatches.push(format!(
+                    "Expected `{name}` header to be `{expected_value}`, got `{value}`"
+                )),
+                None => mismatches.push(format!("Expected a `{name}` header, found none")),
+            }
+        }
+        if let Some(expected_body) = &expected.body {
+            if expected_body != &actual.body {
+                mismatches.push(format!(
+                    "Expected the response body to be `{expected_body}`, got `{}`",
+                    actual.body
+                ));
+            }
+        }
+        if !mismatches.is_empty() {
+            writeln!(
+                &mut msg,
+                "Unexpected response to `{} {}`:",
+                request.method, request.path
+            )
+            .unwrap();
+            for mismatch in mismatches {
+                writeln!(&mut msg, "\t- {mismatch}").unwrap();
+            }
+        }
+    }
+    if msg.is_empty() {
+        Ok(Ok(()))
+    } else {
+        Ok(Err(msg))
+    }
+}
+
+/// Incoming requests that do not match any of the registered routes must be handled
+/// gracefully (i.e. with a fallback handler or a `404 Not Found`).
+/// We check that the generated request dispatcher does not contain any panicking construct.
+fn verify_route_request_cannot_panic(app_code: &str) -> Result<(), String> {
+    let start = app_code.find("async fn route_request(").ok_or_else(|| {
+        "The generated code does not contain a `route_request` function".to_string()
+    })?;
+    let dispatcher = &app_code[start..];
+    // `prettyplease` puts the closing brace of top-level functions at the start of a line.
+    let dispatcher = match dispatcher.find("\n}") {
+        Some(end) => &dispatcher[..end],
+        None => dispatcher,
+    };
+    for panicking_construct in [".expect(", ".unwrap()", "panic!", "unreachable!", "todo!"] {
+        if dispatcher.contains(panicking_construct) {
+            return Err(format!(
+                "The generated `route_request` function contains `{panicking_construct}`: it might \
+                panic when processing a request that does not match any of the registered routes."
+            ));
+        }
+    }
+    Ok(())
+}
+
 struct TestOutcome {
     outcome: Result<(), String>,
     codegen_output: CommandOutput,